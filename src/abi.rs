@@ -0,0 +1,815 @@
+use anyhow::{anyhow, bail, Context, Result};
+use primitive_types::U256;
+use serde::Deserialize;
+use std::fmt;
+use tiny_keccak::{Hasher, Keccak};
+
+const WORD: usize = 32;
+
+pub fn keccak256(data: &[u8]) -> [u8; 32] {
+    let mut hasher = Keccak::v256();
+    let mut output = [0u8; 32];
+    hasher.update(data);
+    hasher.finalize(&mut output);
+    output
+}
+
+pub(crate) fn decode_hex(value: &str) -> Result<Vec<u8>> {
+    let value = value.trim();
+    let digits = value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+        .unwrap_or(value);
+
+    if digits.len() % 2 == 1 {
+        hex::decode(format!("0{}", digits))
+    } else {
+        hex::decode(digits)
+    }
+    .with_context(|| format!("Invalid hex string: {}", value))
+}
+
+pub(crate) fn encode_hex(bytes: &[u8]) -> String {
+    format!("0x{}", hex::encode(bytes))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum AbiType {
+    Address,
+    Bool,
+    Uint(usize),
+    Int(usize),
+    FixedBytes(usize),
+    Bytes,
+    String,
+    Array(Box<AbiType>),
+    FixedArray(Box<AbiType>, usize),
+    Tuple(Vec<AbiType>),
+}
+
+impl AbiType {
+    // Parses a Solidity type, canonicalizing aliases such as `uint` and `byte`.
+    pub fn parse(value: &str) -> Result<Self> {
+        let value = value.trim();
+
+        if let Some(inner) = value.strip_suffix(']') {
+            let open = inner
+                .rfind('[')
+                .ok_or_else(|| anyhow!("Invalid array type: {}", value))?;
+            let element = AbiType::parse(&inner[..open])?;
+            let length = inner[open + 1..].trim();
+
+            return if length.is_empty() {
+                Ok(AbiType::Array(Box::new(element)))
+            } else {
+                let length = length
+                    .parse()
+                    .with_context(|| format!("Invalid array length in type: {}", value))?;
+                Ok(AbiType::FixedArray(Box::new(element), length))
+            };
+        }
+
+        let tuple = value.strip_prefix("tuple").unwrap_or(value).trim();
+        if let Some(components) = tuple.strip_prefix('(').and_then(|t| t.strip_suffix(')')) {
+            let components = split_top_level(components)?
+                .into_iter()
+                .map(|component| parse_param(component).map(|param| param.kind))
+                .collect::<Result<Vec<_>>>()?;
+            return Ok(AbiType::Tuple(components));
+        }
+
+        let elementary = match value {
+            "address" => AbiType::Address,
+            "bool" => AbiType::Bool,
+            "string" => AbiType::String,
+            "bytes" => AbiType::Bytes,
+            "byte" => AbiType::FixedBytes(1),
+            "function" => AbiType::FixedBytes(24),
+            "uint" => AbiType::Uint(256),
+            "int" => AbiType::Int(256),
+            _ => {
+                if let Some(bits) = value.strip_prefix("uint") {
+                    AbiType::Uint(parse_bits(bits, value)?)
+                } else if let Some(bits) = value.strip_prefix("int") {
+                    AbiType::Int(parse_bits(bits, value)?)
+                } else if let Some(size) = value.strip_prefix("bytes") {
+                    match size.parse::<usize>() {
+                        Ok(size) if (1..=32).contains(&size) => AbiType::FixedBytes(size),
+                        _ => bail!("Unsupported ABI type: {}", value),
+                    }
+                } else {
+                    bail!("Unsupported ABI type: {}", value)
+                }
+            }
+        };
+
+        Ok(elementary)
+    }
+
+    pub fn is_dynamic(&self) -> bool {
+        match self {
+            AbiType::Bytes | AbiType::String | AbiType::Array(_) => true,
+            AbiType::FixedArray(element, _) => element.is_dynamic(),
+            AbiType::Tuple(components) => components.iter().any(AbiType::is_dynamic),
+            _ => false,
+        }
+    }
+
+    // Size of the type in the head of an encoding.
+    fn head_size(&self) -> usize {
+        match self {
+            _ if self.is_dynamic() => WORD,
+            AbiType::FixedArray(element, length) => element.head_size() * length,
+            AbiType::Tuple(components) => components.iter().map(AbiType::head_size).sum(),
+            _ => WORD,
+        }
+    }
+}
+
+impl fmt::Display for AbiType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AbiType::Address => write!(f, "address"),
+            AbiType::Bool => write!(f, "bool"),
+            AbiType::Uint(bits) => write!(f, "uint{}", bits),
+            AbiType::Int(bits) => write!(f, "int{}", bits),
+            AbiType::FixedBytes(size) => write!(f, "bytes{}", size),
+            AbiType::Bytes => write!(f, "bytes"),
+            AbiType::String => write!(f, "string"),
+            AbiType::Array(element) => write!(f, "{}[]", element),
+            AbiType::FixedArray(element, length) => write!(f, "{}[{}]", element, length),
+            AbiType::Tuple(components) => {
+                let components: Vec<String> = components.iter().map(|c| c.to_string()).collect();
+                write!(f, "({})", components.join(","))
+            }
+        }
+    }
+}
+
+fn parse_bits(bits: &str, value: &str) -> Result<usize> {
+    match bits.parse::<usize>() {
+        Ok(bits) if bits > 0 && bits <= 256 && bits % 8 == 0 => Ok(bits),
+        _ => bail!("Unsupported ABI type: {}", value),
+    }
+}
+
+// Splits on commas that are not nested inside parentheses or brackets.
+fn split_top_level(value: &str) -> Result<Vec<&str>> {
+    let mut parts = Vec::new();
+    let mut depth = 0i32;
+    let mut start = 0;
+
+    for (index, character) in value.char_indices() {
+        match character {
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(value[start..index].trim());
+                start = index + 1;
+            }
+            _ => {}
+        }
+        if depth < 0 {
+            bail!("Unbalanced parentheses in: {}", value);
+        }
+    }
+
+    if depth != 0 {
+        bail!("Unbalanced parentheses in: {}", value);
+    }
+
+    let last = value[start..].trim();
+    if !last.is_empty() || !parts.is_empty() {
+        parts.push(last);
+    }
+    if parts.iter().any(|part| part.is_empty()) {
+        bail!("Empty parameter in: {}", value);
+    }
+
+    Ok(parts)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AbiParam {
+    pub name: String,
+    pub kind: AbiType,
+    pub indexed: bool,
+}
+
+// Parses a human-readable parameter such as `address indexed from` or
+// `(address to, uint256 amount)[] calldata orders`.
+fn parse_param(value: &str) -> Result<AbiParam> {
+    let value = value.trim();
+    let type_end = if value.starts_with('(') || value.starts_with("tuple(") {
+        let mut depth = 0;
+        let mut end = value.len();
+        for (index, character) in value.char_indices() {
+            match character {
+                '(' => depth += 1,
+                ')' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        end = index + 1;
+                        break;
+                    }
+                }
+                _ => {}
+            }
+        }
+        let suffix = &value[end..];
+        end + suffix
+            .find(|c: char| c.is_whitespace())
+            .unwrap_or(suffix.len())
+    } else {
+        value
+            .find(|c: char| c.is_whitespace())
+            .unwrap_or(value.len())
+    };
+
+    let kind = AbiType::parse(&value[..type_end])?;
+    let mut indexed = false;
+    let mut name = String::new();
+
+    for word in value[type_end..].split_whitespace() {
+        match word {
+            "indexed" => indexed = true,
+            "memory" | "calldata" | "storage" | "payable" => {}
+            _ => name = word.to_string(),
+        }
+    }
+
+    Ok(AbiParam {
+        name,
+        kind,
+        indexed,
+    })
+}
+
+// Splits `name(params) modifiers` into its three parts.
+fn split_signature(value: &str) -> Result<(&str, &str, &str)> {
+    let open = value
+        .find('(')
+        .ok_or_else(|| anyhow!("Missing parameter list in: {}", value))?;
+
+    let mut depth = 0;
+    for (index, character) in value[open..].char_indices() {
+        match character {
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    let close = open + index;
+                    return Ok((
+                        value[..open].trim(),
+                        &value[open + 1..close],
+                        value[close + 1..].trim(),
+                    ));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    bail!("Unbalanced parentheses in: {}", value)
+}
+
+fn parse_params(value: &str) -> Result<Vec<AbiParam>> {
+    split_top_level(value)?
+        .into_iter()
+        .map(parse_param)
+        .collect()
+}
+
+fn canonical_signature(name: &str, params: &[AbiParam]) -> String {
+    let types: Vec<String> = params.iter().map(|param| param.kind.to_string()).collect();
+    format!("{}({})", name, types.join(","))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AbiEvent {
+    pub name: String,
+    pub inputs: Vec<AbiParam>,
+    pub anonymous: bool,
+}
+
+impl AbiEvent {
+    // Parses `event Transfer(address indexed from, address indexed to, uint256 value)`.
+    // The `event` keyword is optional.
+    pub fn parse(value: &str) -> Result<Self> {
+        let value = value.trim();
+        let value = value.strip_prefix("event ").unwrap_or(value);
+        let (name, params, modifiers) = split_signature(value)?;
+
+        if name.is_empty() {
+            bail!("Missing event name in: {}", value);
+        }
+
+        Ok(Self {
+            name: name.to_string(),
+            inputs: parse_params(params)?,
+            anonymous: modifiers.split_whitespace().any(|word| word == "anonymous"),
+        })
+    }
+
+    pub fn signature(&self) -> String {
+        canonical_signature(&self.name, &self.inputs)
+    }
+
+    pub fn topic0(&self) -> [u8; 32] {
+        keccak256(self.signature().as_bytes())
+    }
+
+    // Number of topics a log of this event carries.
+    pub fn topic_count(&self) -> usize {
+        let indexed = self.inputs.iter().filter(|input| input.indexed).count();
+        if self.anonymous {
+            indexed
+        } else {
+            indexed + 1
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AbiFunction {
+    pub name: String,
+    pub inputs: Vec<AbiParam>,
+    pub outputs: Vec<AbiParam>,
+}
+
+impl AbiFunction {
+    // Parses `function transfer(address to, uint256 amount) returns (bool)`.
+    // The `function` keyword is optional.
+    pub fn parse(value: &str) -> Result<Self> {
+        let value = value.trim();
+        let value = value.strip_prefix("function ").unwrap_or(value);
+        let (name, params, modifiers) = split_signature(value)?;
+
+        if name.is_empty() {
+            bail!("Missing function name in: {}", value);
+        }
+
+        let outputs = match modifiers.find("returns") {
+            Some(index) => {
+                let (_, returns, _) = split_signature(&modifiers[index..])?;
+                parse_params(returns)?
+            }
+            None => Vec::new(),
+        };
+
+        Ok(Self {
+            name: name.to_string(),
+            inputs: parse_params(params)?,
+            outputs,
+        })
+    }
+
+    pub fn signature(&self) -> String {
+        canonical_signature(&self.name, &self.inputs)
+    }
+
+    pub fn selector(&self) -> [u8; 4] {
+        let hash = keccak256(self.signature().as_bytes());
+        [hash[0], hash[1], hash[2], hash[3]]
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Abi {
+    pub events: Vec<AbiEvent>,
+    pub functions: Vec<AbiFunction>,
+    // Constructor parameters, when the ABI declares a constructor.
+    pub constructor: Option<Vec<AbiParam>>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum JsonAbiDocument {
+    Items(Vec<JsonAbiItem>),
+    // solc, Hardhat and Foundry artifacts nest the ABI under an `abi` key.
+    Artifact { abi: Vec<JsonAbiItem> },
+}
+
+#[derive(Deserialize)]
+struct JsonAbiItem {
+    #[serde(rename = "type", default)]
+    item_type: String,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    inputs: Vec<JsonAbiParam>,
+    #[serde(default)]
+    outputs: Vec<JsonAbiParam>,
+    #[serde(default)]
+    anonymous: bool,
+}
+
+#[derive(Deserialize)]
+struct JsonAbiParam {
+    #[serde(default)]
+    name: String,
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    indexed: bool,
+    #[serde(default)]
+    components: Vec<JsonAbiParam>,
+}
+
+impl JsonAbiParam {
+    fn to_type_string(&self) -> String {
+        match self.kind.strip_prefix("tuple") {
+            Some(suffix) => {
+                let components: Vec<String> =
+                    self.components.iter().map(|c| c.to_type_string()).collect();
+                format!("({}){}", components.join(","), suffix)
+            }
+            None => self.kind.clone(),
+        }
+    }
+
+    fn to_param(&self) -> Result<AbiParam> {
+        Ok(AbiParam {
+            name: self.name.clone(),
+            kind: AbiType::parse(&self.to_type_string())?,
+            indexed: self.indexed,
+        })
+    }
+}
+
+impl Abi {
+    pub fn from_json(json: &str) -> Result<Self> {
+        let document: JsonAbiDocument =
+            serde_json::from_str(json).context("Failed to parse ABI JSON")?;
+        let items = match document {
+            JsonAbiDocument::Items(items) => items,
+            JsonAbiDocument::Artifact { abi } => abi,
+        };
+
+        let mut abi = Abi::default();
+        for item in items {
+            let inputs = item
+                .inputs
+                .iter()
+                .map(JsonAbiParam::to_param)
+                .collect::<Result<Vec<_>>>()?;
+
+            match item.item_type.as_str() {
+                "event" => abi.events.push(AbiEvent {
+                    name: item.name,
+                    inputs,
+                    anonymous: item.anonymous,
+                }),
+                "function" | "" => abi.functions.push(AbiFunction {
+                    name: item.name,
+                    inputs,
+                    outputs: item
+                        .outputs
+                        .iter()
+                        .map(JsonAbiParam::to_param)
+                        .collect::<Result<Vec<_>>>()?,
+                }),
+                "constructor" => abi.constructor = Some(inputs),
+                _ => {}
+            }
+        }
+
+        Ok(abi)
+    }
+
+    // Parses human-readable lines such as `event Approval(...)` and `function approve(...)`.
+    pub fn from_human_readable<I, S>(lines: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut abi = Abi::default();
+
+        for line in lines {
+            let line = line.as_ref().trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            if line.starts_with("event ") {
+                abi.events.push(AbiEvent::parse(line)?);
+            } else if line.starts_with("function ") {
+                abi.functions.push(AbiFunction::parse(line)?);
+            } else if line.starts_with("constructor") {
+                let (_, params, _) = split_signature(line)?;
+                abi.constructor = Some(parse_params(params)?);
+            } else {
+                bail!(
+                    "Expected an `event`, `function` or `constructor` declaration: {}",
+                    line
+                );
+            }
+        }
+
+        Ok(abi)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AbiValue {
+    Address(String),
+    Bool(bool),
+    Uint(U256),
+    // Two's complement representation of the signed value.
+    Int(U256),
+    FixedBytes(Vec<u8>),
+    Bytes(Vec<u8>),
+    String(String),
+    Array(Vec<AbiValue>),
+    Tuple(Vec<AbiValue>),
+    // Indexed event parameters of dynamic types only carry the keccak hash of the value.
+    Hashed([u8; 32]),
+}
+
+impl fmt::Display for AbiValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AbiValue::Address(address) => write!(f, "{}", address),
+            AbiValue::Bool(value) => write!(f, "{}", value),
+            AbiValue::Uint(value) => write!(f, "{}", value),
+            AbiValue::Int(value) => {
+                if value.bit(255) {
+                    write!(f, "-{}", (!*value).overflowing_add(U256::one()).0)
+                } else {
+                    write!(f, "{}", value)
+                }
+            }
+            AbiValue::FixedBytes(bytes) | AbiValue::Bytes(bytes) => {
+                write!(f, "{}", encode_hex(bytes))
+            }
+            AbiValue::Hashed(hash) => write!(f, "{}", encode_hex(hash)),
+            AbiValue::String(value) => write!(f, "{}", value),
+            AbiValue::Array(values) => {
+                let values: Vec<String> = values.iter().map(|v| v.to_string()).collect();
+                write!(f, "[{}]", values.join(", "))
+            }
+            AbiValue::Tuple(values) => {
+                let values: Vec<String> = values.iter().map(|v| v.to_string()).collect();
+                write!(f, "({})", values.join(", "))
+            }
+        }
+    }
+}
+
+// Decodes ABI-encoded `data` as a sequence of `types`.
+pub fn decode(types: &[AbiType], data: &[u8]) -> Result<Vec<AbiValue>> {
+    decode_sequence(types, data, 0)
+}
+
+// Decodes a single static value stored in one word, such as an indexed event topic.
+pub(crate) fn decode_word(kind: &AbiType, word: &[u8]) -> Result<AbiValue> {
+    decode_value(kind, word, 0)
+}
+
+fn read_word(data: &[u8], at: usize) -> Result<&[u8]> {
+    data.get(at..at + WORD).ok_or_else(|| {
+        anyhow!(
+            "ABI data too short: need {} bytes, have {}",
+            at + WORD,
+            data.len()
+        )
+    })
+}
+
+fn read_usize(data: &[u8], at: usize) -> Result<usize> {
+    let value = U256::from_big_endian(read_word(data, at)?);
+    if value > U256::from(data.len()) {
+        bail!(
+            "ABI offset or length {} exceeds data size {}",
+            value,
+            data.len()
+        );
+    }
+    Ok(value.as_usize())
+}
+
+fn decode_sequence(types: &[AbiType], data: &[u8], base: usize) -> Result<Vec<AbiValue>> {
+    let mut values = Vec::with_capacity(types.len());
+    let mut offset = base;
+
+    for kind in types {
+        if kind.is_dynamic() {
+            let pointer = read_usize(data, offset)?;
+            values.push(decode_value(kind, data, base + pointer)?);
+        } else {
+            values.push(decode_value(kind, data, offset)?);
+        }
+        offset += kind.head_size();
+    }
+
+    Ok(values)
+}
+
+fn decode_value(kind: &AbiType, data: &[u8], at: usize) -> Result<AbiValue> {
+    match kind {
+        AbiType::Address => {
+            let word = read_word(data, at)?;
+            Ok(AbiValue::Address(encode_hex(&word[12..])))
+        }
+        AbiType::Bool => {
+            let word = read_word(data, at)?;
+            match U256::from_big_endian(word) {
+                value if value.is_zero() => Ok(AbiValue::Bool(false)),
+                value if value == U256::one() => Ok(AbiValue::Bool(true)),
+                value => bail!("Invalid bool value: {}", value),
+            }
+        }
+        AbiType::Uint(_) => Ok(AbiValue::Uint(U256::from_big_endian(read_word(data, at)?))),
+        AbiType::Int(_) => Ok(AbiValue::Int(U256::from_big_endian(read_word(data, at)?))),
+        AbiType::FixedBytes(size) => {
+            Ok(AbiValue::FixedBytes(read_word(data, at)?[..*size].to_vec()))
+        }
+        AbiType::Bytes | AbiType::String => {
+            let length = read_usize(data, at)?;
+            let bytes = data
+                .get(at + WORD..at + WORD + length)
+                .ok_or_else(|| anyhow!("ABI data too short for {} bytes", length))?
+                .to_vec();
+
+            if *kind == AbiType::Bytes {
+                Ok(AbiValue::Bytes(bytes))
+            } else {
+                Ok(AbiValue::String(
+                    String::from_utf8_lossy(&bytes).into_owned(),
+                ))
+            }
+        }
+        AbiType::Array(element) => {
+            let length = read_usize(data, at)?;
+            let elements = repeat_checked(element, length, data, at + WORD)?;
+            Ok(AbiValue::Array(decode_sequence(
+                &elements,
+                data,
+                at + WORD,
+            )?))
+        }
+        AbiType::FixedArray(element, length) => {
+            let elements = repeat_checked(element, *length, data, at)?;
+            Ok(AbiValue::Array(decode_sequence(&elements, data, at)?))
+        }
+        AbiType::Tuple(components) => Ok(AbiValue::Tuple(decode_sequence(components, data, at)?)),
+    }
+}
+
+// Guards against lengths that cannot fit in the remaining data before allocating.
+fn repeat_checked(
+    element: &AbiType,
+    length: usize,
+    data: &[u8],
+    at: usize,
+) -> Result<Vec<AbiType>> {
+    let needed = element
+        .head_size()
+        .checked_mul(length)
+        .and_then(|size| size.checked_add(at))
+        .ok_or_else(|| anyhow!("ABI array length {} is too large", length))?;
+
+    if needed > data.len() {
+        bail!("ABI data too short for array of {} elements", length);
+    }
+
+    Ok(vec![element.clone(); length])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn word(value: u64) -> [u8; 32] {
+        let mut word = [0u8; 32];
+        word[24..].copy_from_slice(&value.to_be_bytes());
+        word
+    }
+
+    fn padded(bytes: &[u8]) -> [u8; 32] {
+        let mut word = [0u8; 32];
+        word[..bytes.len()].copy_from_slice(bytes);
+        word
+    }
+
+    #[test]
+    fn hashes_transfer_signature() {
+        let event = AbiEvent::parse(
+            "event Transfer(address indexed from, address indexed to, uint256 value)",
+        )
+        .unwrap();
+
+        assert_eq!(event.signature(), "Transfer(address,address,uint256)");
+        assert_eq!(
+            encode_hex(&event.topic0()),
+            "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef"
+        );
+        assert_eq!(event.topic_count(), 3);
+    }
+
+    #[test]
+    fn canonicalizes_type_aliases() {
+        assert_eq!(AbiType::parse("uint").unwrap(), AbiType::Uint(256));
+        assert_eq!(AbiType::parse("int").unwrap(), AbiType::Int(256));
+        assert_eq!(AbiType::parse("byte").unwrap(), AbiType::FixedBytes(1));
+        assert_eq!(
+            AbiType::parse("(address,uint)[2][]").unwrap().to_string(),
+            "(address,uint256)[2][]"
+        );
+        assert!(AbiType::parse("uint7").is_err());
+        assert!(AbiType::parse("bytes33").is_err());
+    }
+
+    #[test]
+    fn parses_functions_with_tuples_and_returns() {
+        let function = AbiFunction::parse(
+            "function fill((address maker, uint amount)[] calldata orders, bytes data) external returns (bool)",
+        )
+        .unwrap();
+
+        assert_eq!(function.signature(), "fill((address,uint256)[],bytes)");
+        assert_eq!(function.inputs[0].name, "orders");
+        assert_eq!(function.outputs[0].kind, AbiType::Bool);
+
+        let transfer = AbiFunction::parse("transfer(address,uint256)").unwrap();
+        assert_eq!(encode_hex(&transfer.selector()), "0xa9059cbb");
+    }
+
+    #[test]
+    fn reads_json_abi_and_artifacts() {
+        let items = r#"[
+            {"type": "event", "name": "Approval", "anonymous": false, "inputs": [
+                {"name": "owner", "type": "address", "indexed": true},
+                {"name": "spender", "type": "address", "indexed": true},
+                {"name": "value", "type": "uint256", "indexed": false}
+            ]},
+            {"type": "function", "name": "swap", "inputs": [
+                {"name": "path", "type": "tuple[]", "components": [
+                    {"name": "token", "type": "address"},
+                    {"name": "fee", "type": "uint24"}
+                ]}
+            ], "outputs": []},
+            {"type": "constructor", "inputs": [{"name": "owner", "type": "address"}]}
+        ]"#;
+        let abi = Abi::from_json(items).unwrap();
+        assert_eq!(
+            abi.events[0].signature(),
+            "Approval(address,address,uint256)"
+        );
+        assert_eq!(abi.functions[0].signature(), "swap((address,uint24)[])");
+        assert_eq!(abi.constructor.as_ref().map(Vec::len), Some(1));
+
+        let artifact = format!(r#"{{"contractName": "Token", "abi": {}}}"#, items);
+        assert_eq!(Abi::from_json(&artifact).unwrap().events.len(), 1);
+    }
+
+    #[test]
+    fn decodes_static_and_dynamic_values() {
+        let mut data = Vec::new();
+        data.extend(word(42));
+        data.extend(word(0x60));
+        data.extend(word(0xa0));
+        data.extend(word(5));
+        data.extend(padded(b"hello"));
+        data.extend(word(2));
+        data.extend(word(1));
+        data.extend(word(2));
+
+        let types = [
+            AbiType::Uint(256),
+            AbiType::String,
+            AbiType::Array(Box::new(AbiType::Uint(8))),
+        ];
+        let values = decode(&types, &data).unwrap();
+
+        assert_eq!(
+            values,
+            vec![
+                AbiValue::Uint(U256::from(42)),
+                AbiValue::String("hello".to_string()),
+                AbiValue::Array(vec![
+                    AbiValue::Uint(U256::one()),
+                    AbiValue::Uint(U256::from(2))
+                ]),
+            ]
+        );
+    }
+
+    #[test]
+    fn formats_negative_ints() {
+        let values = decode(&[AbiType::Int(256)], &[0xff; 32]).unwrap();
+        assert_eq!(values[0].to_string(), "-1");
+    }
+
+    #[test]
+    fn rejects_truncated_and_oversized_data() {
+        assert!(decode(&[AbiType::Uint(256)], &[0; 31]).is_err());
+        assert!(decode(&[AbiType::Bool], &word(2)).is_err());
+
+        // An array claiming more elements than the data holds.
+        let mut data = Vec::new();
+        data.extend(word(0x20));
+        data.extend(word(1_000));
+        assert!(decode(&[AbiType::Array(Box::new(AbiType::Uint(256)))], &data).is_err());
+    }
+}
@@ -0,0 +1,679 @@
+use crate::{AccountKeyHistory, Address, ContractInfo, EventLogResult, KaiaScan, NftTransfer};
+use anyhow::Result;
+use async_stream::stream;
+use futures::stream::Stream;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+const ACCOUNT_POLL_INTERVAL: Duration = Duration::from_secs(5);
+const ACCOUNT_PAGE_SIZE: u32 = 100;
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AccountEvent {
+    // A token balance of a watched address increased since the previous poll.
+    TokenReceived {
+        address: Address,
+        contract: ContractInfo,
+        amount: u64,
+        balance: u64,
+    },
+    NftTransferred {
+        address: Address,
+        transfer: NftTransfer,
+    },
+    EventLogged {
+        address: Address,
+        log: EventLogResult,
+    },
+    KeyUpdated {
+        address: Address,
+        key: AccountKeyHistory,
+    },
+}
+
+// Scan position of one block-numbered feed of an address. `block` is the highest block the
+// feed has been fully fetched up to; the next poll scans again from that block, and `seen`
+// holds the keys of events already emitted at or above it, keyed to their block number.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeedCursor {
+    pub block: Option<u64>,
+    pub seen: HashMap<String, u64>,
+}
+
+impl FeedCursor {
+    fn is_new(&mut self, key: String, block_number: u64) -> bool {
+        self.seen.insert(key, block_number).is_none()
+    }
+
+    // Moves the cursor to the highest block fetched in a completed poll and forgets the
+    // events below it, which no later poll returns.
+    fn commit(&mut self, highest: Option<u64>) {
+        self.block = self.block.max(highest);
+        if let Some(block) = self.block {
+            self.seen.retain(|_, block_number| *block_number >= block);
+        }
+    }
+}
+
+// Cursors of the block-numbered feeds of one address. Each feed keeps its own cursor, since
+// one feed may have seen later blocks than the other.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccountCursor {
+    pub nft_transfers: FeedCursor,
+    pub event_logs: FeedCursor,
+}
+
+impl AccountCursor {
+    // Starts both feeds at `block`, so nothing older than it is ever emitted.
+    pub fn at_block(block: u64) -> Self {
+        let feed = FeedCursor {
+            block: Some(block),
+            seen: HashMap::new(),
+        };
+        Self {
+            nft_transfers: feed.clone(),
+            event_logs: feed,
+        }
+    }
+}
+
+// Persists the cursors of each watched address, so a restarted watcher picks up where the
+// previous one stopped. Cursors are saved only after a poll of the address fully succeeded.
+pub trait CursorStore {
+    fn load(&mut self, address: &Address) -> Result<Option<AccountCursor>>;
+    fn save(&mut self, address: &Address, cursor: &AccountCursor) -> Result<()>;
+}
+
+#[derive(Debug, Default)]
+pub struct MemoryCursorStore {
+    cursors: HashMap<Address, AccountCursor>,
+}
+
+impl CursorStore for MemoryCursorStore {
+    fn load(&mut self, address: &Address) -> Result<Option<AccountCursor>> {
+        Ok(self.cursors.get(address).cloned())
+    }
+
+    fn save(&mut self, address: &Address, cursor: &AccountCursor) -> Result<()> {
+        self.cursors.insert(address.clone(), cursor.clone());
+        Ok(())
+    }
+}
+
+pub struct AccountWatcher<'a, S> {
+    client: &'a KaiaScan,
+    addresses: Vec<Address>,
+    store: S,
+    poll_interval: Duration,
+    start_block: Option<u64>,
+}
+
+#[derive(Default)]
+struct AccountState {
+    cursor: AccountCursor,
+    balances: Option<HashMap<String, u64>>,
+    keys: Option<HashSet<String>>,
+}
+
+impl KaiaScan {
+    // Watches `addresses` for new events. An address without a stored cursor starts from
+    // the beginning of its history, so its first poll emits every past NFT transfer and
+    // event log; use `starting_at_block` to start from the chain head instead.
+    pub fn watch_accounts<S: CursorStore>(
+        &self,
+        addresses: impl IntoIterator<Item = Address>,
+        store: S,
+    ) -> AccountWatcher<'_, S> {
+        AccountWatcher {
+            client: self,
+            addresses: addresses.into_iter().collect(),
+            store,
+            poll_interval: ACCOUNT_POLL_INTERVAL,
+            start_block: None,
+        }
+    }
+}
+
+impl<'a, S: CursorStore + 'a> AccountWatcher<'a, S> {
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    // Addresses without a stored cursor start at `block` instead of at their first event.
+    // Events in `block` itself are still emitted. Stored cursors take precedence.
+    pub fn starting_at_block(mut self, block: u64) -> Self {
+        self.start_block = Some(block);
+        self
+    }
+
+    // Polls every watched address and yields each new event once. Dropping the stream
+    // stops the watcher.
+    pub fn into_stream(mut self) -> impl Stream<Item = Result<AccountEvent>> + 'a {
+        stream! {
+            let mut states: HashMap<Address, AccountState> = HashMap::new();
+
+            for address in &self.addresses {
+                match self.store.load(address) {
+                    Ok(cursor) => {
+                        let cursor = cursor
+                            .or_else(|| self.start_block.map(AccountCursor::at_block))
+                            .unwrap_or_default();
+                        states.insert(address.clone(), AccountState { cursor, ..Default::default() });
+                    }
+                    Err(err) => yield Err(err),
+                }
+            }
+
+            loop {
+                for address in &self.addresses {
+                    let state = states.entry(address.clone()).or_default();
+
+                    let mut events = Vec::new();
+                    let polled = self.poll_account(address, state, &mut events).await;
+
+                    for event in events {
+                        yield Ok(event);
+                    }
+                    match polled {
+                        Ok(()) => {
+                            if let Err(err) = self.store.save(address, &state.cursor) {
+                                yield Err(err);
+                            }
+                        }
+                        Err(err) => yield Err(err),
+                    }
+                }
+
+                tokio::time::sleep(self.poll_interval).await;
+            }
+        }
+    }
+
+    // Events found before a failing request are still pushed, since they are already
+    // marked as seen. A feed's cursor only moves once all its pages were fetched.
+    async fn poll_account(
+        &self,
+        address: &Address,
+        state: &mut AccountState,
+        events: &mut Vec<AccountEvent>,
+    ) -> Result<()> {
+        self.poll_nft_transfers(address, &mut state.cursor.nft_transfers, events)
+            .await?;
+        self.poll_event_logs(address, &mut state.cursor.event_logs, events)
+            .await?;
+        self.poll_token_balances(address, state, events).await?;
+        self.poll_key_histories(address, state, events).await
+    }
+
+    async fn poll_nft_transfers(
+        &self,
+        address: &Address,
+        cursor: &mut FeedCursor,
+        events: &mut Vec<AccountEvent>,
+    ) -> Result<()> {
+        let mut highest = None;
+        let mut page = 1;
+
+        loop {
+            let response = self
+                .client
+                .get_account_nft_transfers(
+                    address.clone(),
+                    Some(page),
+                    Some(ACCOUNT_PAGE_SIZE),
+                    None,
+                    cursor.block,
+                    None,
+                )
+                .await?;
+
+            for transfer in response.results {
+                let key = format!(
+                    "nft:{}:{}:{}:{}",
+                    transfer.transaction_hash,
+                    transfer.contract.contract_address,
+                    transfer.token_id,
+                    transfer.to
+                );
+                highest = highest.max(Some(transfer.block_id));
+                if cursor.is_new(key, transfer.block_id) {
+                    events.push(AccountEvent::NftTransferred {
+                        address: address.clone(),
+                        transfer,
+                    });
+                }
+            }
+
+            if response.paging.last {
+                cursor.commit(highest);
+                return Ok(());
+            }
+            page += 1;
+        }
+    }
+
+    async fn poll_event_logs(
+        &self,
+        address: &Address,
+        cursor: &mut FeedCursor,
+        events: &mut Vec<AccountEvent>,
+    ) -> Result<()> {
+        let mut highest = None;
+        let mut page = 1;
+
+        loop {
+            let response = self
+                .client
+                .get_account_event_logs(
+                    address.clone(),
+                    Some(page),
+                    Some(ACCOUNT_PAGE_SIZE),
+                    None,
+                    cursor.block,
+                    None,
+                )
+                .await?;
+
+            for log in response.results {
+                let key = format!("log:{}:{}", log.transaction_hash, log.log_index);
+                highest = highest.max(Some(log.block_number));
+                if cursor.is_new(key, log.block_number) {
+                    events.push(AccountEvent::EventLogged {
+                        address: address.clone(),
+                        log,
+                    });
+                }
+            }
+
+            if response.pagings.last {
+                cursor.commit(highest);
+                return Ok(());
+            }
+            page += 1;
+        }
+    }
+
+    // Balances carry no block number, so receipts are detected by diffing against the
+    // previous poll. The first poll only records a baseline.
+    async fn poll_token_balances(
+        &self,
+        address: &Address,
+        state: &mut AccountState,
+        events: &mut Vec<AccountEvent>,
+    ) -> Result<()> {
+        let mut balances = HashMap::new();
+        let mut contracts = Vec::new();
+        let mut page = 1;
+
+        loop {
+            let response = self
+                .client
+                .get_account_token_balances(address.clone(), Some(page), Some(ACCOUNT_PAGE_SIZE))
+                .await?;
+
+            for token in response.results {
+                balances.insert(token.contract.contract_address.clone(), token.balance);
+                contracts.push(token.contract);
+            }
+
+            if response.paging.last {
+                break;
+            }
+            page += 1;
+        }
+
+        if let Some(previous) = &state.balances {
+            for contract in contracts {
+                let balance = balances[&contract.contract_address];
+                let before = previous
+                    .get(&contract.contract_address)
+                    .copied()
+                    .unwrap_or(0);
+
+                if balance > before {
+                    events.push(AccountEvent::TokenReceived {
+                        address: address.clone(),
+                        contract,
+                        amount: balance - before,
+                        balance,
+                    });
+                }
+            }
+        }
+
+        state.balances = Some(balances);
+        Ok(())
+    }
+
+    async fn poll_key_histories(
+        &self,
+        address: &Address,
+        state: &mut AccountState,
+        events: &mut Vec<AccountEvent>,
+    ) -> Result<()> {
+        let response = self
+            .client
+            .get_account_key_histories(address.as_ref(), Some(1), Some(ACCOUNT_PAGE_SIZE as i32))
+            .await?;
+
+        let first_poll = state.keys.is_none();
+        let keys = state.keys.get_or_insert_with(HashSet::new);
+
+        for key in response.results {
+            let id = format!("{}:{}:{}", key.key_type, key.public_key, key.created_at);
+            if keys.insert(id) && !first_poll {
+                events.push(AccountEvent::KeyUpdated {
+                    address: address.clone(),
+                    key,
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+    use mockito::Matcher;
+    use serde_json::json;
+    use std::sync::{Arc, Mutex};
+    use tokio::sync::Notify;
+
+    const WATCHED: &str = "0xw";
+
+    #[derive(Clone, Default)]
+    struct SharedStore(Arc<Mutex<HashMap<Address, AccountCursor>>>, Arc<Notify>);
+
+    impl CursorStore for SharedStore {
+        fn load(&mut self, address: &Address) -> Result<Option<AccountCursor>> {
+            Ok(self.0.lock().unwrap().get(address).cloned())
+        }
+
+        fn save(&mut self, address: &Address, cursor: &AccountCursor) -> Result<()> {
+            self.0
+                .lock()
+                .unwrap()
+                .insert(address.clone(), cursor.clone());
+            self.1.notify_one();
+            Ok(())
+        }
+    }
+
+    // Client without a request timeout: with the clock paused, a pending timeout would be
+    // auto-advanced while a request waits on the mock server.
+    fn client(server: &mockito::Server) -> KaiaScan {
+        let mut client = KaiaScan::new(false).unwrap().with_base_url(server.url());
+        client.client = reqwest::Client::new();
+        client
+    }
+
+    // Runs the watcher until `polls` polls of the single watched address were saved and
+    // returns the events yielded so far.
+    async fn run_polls(
+        watcher: AccountWatcher<'_, SharedStore>,
+        polls: usize,
+    ) -> Vec<AccountEvent> {
+        let saved = watcher.store.1.clone();
+        let mut events = Vec::new();
+        let stream = watcher.into_stream().for_each(|event| {
+            events.push(event.unwrap());
+            futures::future::ready(())
+        });
+
+        tokio::select! {
+            _ = stream => unreachable!("the watcher stream never ends"),
+            _ = async {
+                for _ in 0..polls {
+                    saved.notified().await;
+                }
+            } => {}
+        }
+        events
+    }
+
+    fn empty_event_logs() -> String {
+        json!({
+            "results": [],
+            "pagings": { "total_count": 0, "current_page": 1, "last": true, "total_page": 1 },
+            "property": {},
+        })
+        .to_string()
+    }
+
+    fn nft_transfer(transaction_hash: &str, block_id: u64) -> serde_json::Value {
+        json!({
+            "contract": { "contract_address": "0xc", "contract_type": "kip17" },
+            "blockId": block_id,
+            "transactionHash": transaction_hash,
+            "feePayer": "",
+            "transactionIndex": 0,
+            "datetime": "2024-01-01T00:00:00Z",
+            "from": "0xf",
+            "to": WATCHED,
+            "tokenId": "1",
+            "tokenCount": 1,
+        })
+    }
+
+    fn paging() -> serde_json::Value {
+        json!({ "current_page": 1, "last": true, "total_count": 0, "total_page": 1 })
+    }
+
+    async fn mock_quiet_feeds(server: &mut mockito::Server) {
+        server
+            .mock(
+                "GET",
+                Matcher::Regex(r"^/api/v1/accounts/0xw/token-balances".into()),
+            )
+            .with_body(
+                json!({
+                    "results": [],
+                    "paging": { "totalCount": 0, "currentPage": 1, "last": true, "totalPage": 1 },
+                    "property": null,
+                })
+                .to_string(),
+            )
+            .create_async()
+            .await;
+        server
+            .mock(
+                "GET",
+                Matcher::Regex(r"^/api/v1/accounts/0xw/key-histories".into()),
+            )
+            .with_body(json!({ "paging": paging(), "results": [] }).to_string())
+            .create_async()
+            .await;
+    }
+
+    fn transaction_hash(event: &AccountEvent) -> &str {
+        match event {
+            AccountEvent::NftTransferred { transfer, .. } => &transfer.transaction_hash,
+            other => panic!("unexpected event {:?}", other),
+        }
+    }
+
+    #[test]
+    fn feed_cursor_forgets_events_below_committed_block() {
+        let mut cursor = FeedCursor::default();
+        assert!(cursor.is_new("a".to_string(), 5));
+        assert!(cursor.is_new("b".to_string(), 7));
+        assert!(!cursor.is_new("a".to_string(), 5));
+
+        cursor.commit(Some(7));
+        assert_eq!(cursor.block, Some(7));
+        assert!(cursor.is_new("a".to_string(), 5));
+        assert!(!cursor.is_new("b".to_string(), 7));
+
+        cursor.commit(None);
+        assert_eq!(cursor.block, Some(7));
+    }
+
+    #[test]
+    fn cursor_at_block_starts_both_feeds() {
+        let cursor = AccountCursor::at_block(42);
+        assert_eq!(cursor.nft_transfers.block, Some(42));
+        assert_eq!(cursor.event_logs.block, Some(42));
+        assert!(cursor.nft_transfers.seen.is_empty());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn resumes_each_feed_from_its_own_cursor() {
+        let mut server = mockito::Server::new_async().await;
+        let client = client(&server);
+        mock_quiet_feeds(&mut server).await;
+
+        // The NFT feed emitted 0xa in block 10 before the restart; 0xb in the same block
+        // was never emitted.
+        let store = SharedStore::default();
+        store.0.lock().unwrap().insert(
+            Address::new(WATCHED),
+            AccountCursor {
+                nft_transfers: FeedCursor {
+                    block: Some(10),
+                    seen: HashMap::from([("nft:0xa:0xc:1:0xw".to_string(), 10)]),
+                },
+                event_logs: FeedCursor {
+                    block: Some(4),
+                    seen: HashMap::new(),
+                },
+            },
+        );
+
+        server
+            .mock(
+                "GET",
+                Matcher::Regex(r"^/api/v1/accounts/0xw/nft-transfers\?.*blockNumberStart=10".into()),
+            )
+            .with_body(
+                json!({
+                    "results": [nft_transfer("0xa", 10), nft_transfer("0xb", 10), nft_transfer("0xd", 11)],
+                    "paging": paging(),
+                    "property": null,
+                })
+                .to_string(),
+            )
+            .create_async()
+            .await;
+        server
+            .mock(
+                "GET",
+                Matcher::Regex(r"^/api/v1/accounts/0xw/nft-transfers\?.*blockNumberStart=11".into()),
+            )
+            .with_body(
+                json!({ "results": [nft_transfer("0xd", 11)], "paging": paging(), "property": null })
+                    .to_string(),
+            )
+            .create_async()
+            .await;
+        let logs = server
+            .mock(
+                "GET",
+                Matcher::Regex(r"^/api/v1/accounts/0xw/event-logs\?.*blockNumberStart=4".into()),
+            )
+            .with_body(empty_event_logs())
+            .expect_at_least(1)
+            .create_async()
+            .await;
+
+        // Run a few polls; the cursor is saved after the events of a poll are yielded.
+        let watcher = client.watch_accounts([Address::new(WATCHED)], store.clone());
+        let events = run_polls(watcher, 3).await;
+
+        let hashes: Vec<&str> = events.iter().map(transaction_hash).collect();
+        assert_eq!(hashes, vec!["0xb", "0xd"]);
+        logs.assert_async().await;
+
+        let saved = store.0.lock().unwrap()[&Address::new(WATCHED)].clone();
+        assert_eq!(saved.nft_transfers.block, Some(11));
+        assert_eq!(saved.event_logs.block, Some(4));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn starts_new_addresses_at_the_given_block() {
+        let mut server = mockito::Server::new_async().await;
+        let client = client(&server);
+        mock_quiet_feeds(&mut server).await;
+
+        let transfers = server
+            .mock(
+                "GET",
+                Matcher::Regex(r"^/api/v1/accounts/0xw/nft-transfers\?.*blockNumberStart=50".into()),
+            )
+            .with_body(
+                json!({ "results": [nft_transfer("0xe", 50)], "paging": paging(), "property": null })
+                    .to_string(),
+            )
+            .expect(2)
+            .create_async()
+            .await;
+        let logs = server
+            .mock(
+                "GET",
+                Matcher::Regex(r"^/api/v1/accounts/0xw/event-logs\?.*blockNumberStart=50".into()),
+            )
+            .with_body(empty_event_logs())
+            .expect(2)
+            .create_async()
+            .await;
+
+        let store = SharedStore::default();
+        let watcher = client
+            .watch_accounts([Address::new(WATCHED)], store.clone())
+            .starting_at_block(50);
+        let events = run_polls(watcher, 2).await;
+
+        let hashes: Vec<&str> = events.iter().map(transaction_hash).collect();
+        assert_eq!(hashes, vec!["0xe"]);
+        transfers.assert_async().await;
+        logs.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn does_not_save_cursor_after_failed_poll() {
+        let mut server = mockito::Server::new_async().await;
+        let client = KaiaScan::new(false).unwrap().with_base_url(server.url());
+        mock_quiet_feeds(&mut server).await;
+
+        server
+            .mock(
+                "GET",
+                Matcher::Regex(r"^/api/v1/accounts/0xw/nft-transfers".into()),
+            )
+            .with_body(
+                json!({
+                    "results": [nft_transfer("0xa", 10)],
+                    "paging": paging(),
+                    "property": null,
+                })
+                .to_string(),
+            )
+            .create_async()
+            .await;
+        server
+            .mock(
+                "GET",
+                Matcher::Regex(r"^/api/v1/accounts/0xw/event-logs".into()),
+            )
+            .with_status(500)
+            .create_async()
+            .await;
+
+        let store = SharedStore::default();
+        let results: Vec<Result<AccountEvent>> = client
+            .watch_accounts([Address::new(WATCHED)], store.clone())
+            .into_stream()
+            .take(2)
+            .collect()
+            .await;
+
+        assert_eq!(transaction_hash(results[0].as_ref().unwrap()), "0xa");
+        assert!(results[1].is_err());
+        assert!(store.0.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,133 @@
+use crate::{Address, BlockDetails, KaiaScan, TokenInfo, TransactionDetails};
+use anyhow::Result;
+use futures::stream::{self, StreamExt};
+use std::future::Future;
+
+pub(crate) const DEFAULT_BATCH_CONCURRENCY: usize = 8;
+
+impl KaiaScan {
+    // Runs `fetch` for every input with at most `batch_concurrency` requests in flight.
+    // Results come back in input order and a failed item does not abort the rest.
+    async fn run_batch<I, F, Fut, T>(&self, inputs: I, fetch: F) -> Vec<Result<T>>
+    where
+        I: IntoIterator,
+        F: FnMut(I::Item) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        stream::iter(inputs)
+            .map(fetch)
+            .buffered(self.batch_concurrency)
+            .collect()
+            .await
+    }
+
+    pub async fn get_transactions_batch<S>(
+        &self,
+        transaction_hashes: &[S],
+    ) -> Vec<Result<TransactionDetails>>
+    where
+        S: AsRef<str>,
+    {
+        // Inputs are owned so the returned future stays `Send` when spawned.
+        let hashes = transaction_hashes
+            .iter()
+            .map(|hash| hash.as_ref().to_string());
+        self.run_batch(
+            hashes,
+            |hash| async move { self.get_transaction(&hash).await },
+        )
+        .await
+    }
+
+    pub async fn get_blocks_batch(&self, block_numbers: &[i64]) -> Vec<Result<BlockDetails>> {
+        self.run_batch(block_numbers.iter().copied(), |block_number| {
+            self.get_block(block_number)
+        })
+        .await
+    }
+
+    pub async fn get_token_infos_batch(
+        &self,
+        token_addresses: &[Address],
+    ) -> Vec<Result<TokenInfo>> {
+        self.run_batch(token_addresses.iter().cloned(), |token_address| {
+            self.get_fungible_token(token_address)
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    fn block(block_id: i64) -> String {
+        json!({
+            "block_id": block_id,
+            "datetime": "2024-01-01T00:00:00Z",
+            "hash": format!("0x{}", block_id),
+            "total_transaction_count": 0,
+            "block_reward": { "minted": "0", "total_fee": "0", "burnt_fee": "0" },
+        })
+        .to_string()
+    }
+
+    #[tokio::test]
+    async fn keeps_input_order_and_failed_items() {
+        let mut server = mockito::Server::new_async().await;
+        for block_id in [3, 1] {
+            server
+                .mock(
+                    "GET",
+                    format!("/api/v1/blocks?blockNumber={}", block_id).as_str(),
+                )
+                .with_body(block(block_id))
+                .create_async()
+                .await;
+        }
+        server
+            .mock("GET", "/api/v1/blocks?blockNumber=2")
+            .with_status(500)
+            .create_async()
+            .await;
+        let client = KaiaScan::new(false)
+            .unwrap()
+            .with_base_url(server.url())
+            .with_batch_concurrency(2);
+
+        let results = client.get_blocks_batch(&[3, 2, 1]).await;
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].as_ref().unwrap().block_id, 3);
+        assert!(results[1].is_err());
+        assert_eq!(results[2].as_ref().unwrap().block_id, 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn honors_the_concurrency_limit() {
+        let client = KaiaScan::new(false).unwrap().with_batch_concurrency(3);
+        let in_flight = AtomicUsize::new(0);
+        let most = AtomicUsize::new(0);
+
+        let results = client
+            .run_batch(0..10u64, |item| {
+                let (in_flight, most) = (&in_flight, &most);
+                async move {
+                    let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                    most.fetch_max(now, Ordering::SeqCst);
+                    // Later items finish first, so order comes from the batch, not timing.
+                    tokio::time::sleep(Duration::from_millis(100 - item * 10)).await;
+                    in_flight.fetch_sub(1, Ordering::SeqCst);
+                    Ok(item)
+                }
+            })
+            .await;
+
+        let results: Vec<u64> = results.into_iter().map(Result::unwrap).collect();
+        assert_eq!(results, (0..10).collect::<Vec<_>>());
+        assert_eq!(most.load(Ordering::SeqCst), 3);
+    }
+}
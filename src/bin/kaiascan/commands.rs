@@ -0,0 +1,328 @@
+use crate::config::ConfigCommand;
+use crate::export::{self, ExportCommand};
+use crate::output::{self, OutputFormat};
+use crate::tui;
+use crate::watch::{self, WatchCommand};
+use anyhow::Result;
+use clap::{Args, Subcommand, ValueEnum};
+use rustsdk::{Address, EventSignature, KaiaScan};
+use serde_json::{json, Value};
+use std::path::PathBuf;
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Show a block, or the latest block when no number is given
+    Block { number: Option<i64> },
+    /// List blocks
+    Blocks(BlocksArgs),
+    /// Show a transaction, its event logs or its input data
+    Tx(TxArgs),
+    /// Query an account
+    #[command(subcommand)]
+    Account(AccountCommand),
+    /// Show a fungible token
+    Token { address: String },
+    /// Show an NFT item
+    Nft { address: String, token_id: String },
+    /// Query a contract
+    #[command(subcommand)]
+    Contract(ContractCommand),
+    /// Show burns of a block, or the latest burns
+    Burns(BurnsArgs),
+    /// Show the rewards of a block
+    Rewards { block: i64 },
+    /// Show KAIA price and network summary
+    Info,
+    /// Follow new blocks or account activity until Ctrl-C
+    #[command(subcommand)]
+    Watch(WatchCommand),
+    /// Dump paged data to CSV or NDJSON files
+    #[command(subcommand)]
+    Export(ExportCommand),
+    /// Browse blocks, transactions and addresses interactively
+    Tui,
+    /// List, select and show config profiles
+    #[command(subcommand)]
+    Config(ConfigCommand),
+}
+
+#[derive(Debug, Args)]
+pub struct PageArgs {
+    #[arg(long)]
+    page: Option<u32>,
+    #[arg(long)]
+    size: Option<u32>,
+}
+
+#[derive(Debug, Args)]
+pub struct BlocksArgs {
+    block_number: i64,
+    #[arg(long)]
+    start: Option<i64>,
+    #[arg(long)]
+    end: Option<i64>,
+    #[arg(long)]
+    page: Option<i32>,
+    #[arg(long)]
+    size: Option<i32>,
+}
+
+#[derive(Debug, Args)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
+pub struct TxArgs {
+    #[arg(required = true)]
+    hash: Option<String>,
+    #[command(subcommand)]
+    command: Option<TxCommand>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum TxCommand {
+    /// List the event logs of a transaction
+    Logs {
+        hash: String,
+        /// Event signature, e.g. "Transfer(address,address,uint256)"
+        #[arg(long)]
+        signature: Option<EventSignature>,
+        #[command(flatten)]
+        page: PageArgs,
+    },
+    /// Show the input data of a transaction
+    Input {
+        hash: String,
+        /// Decode the calldata against known function signatures
+        #[arg(long)]
+        decode: bool,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum NftStandard {
+    Kip17,
+    Kip37,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum AccountCommand {
+    /// List fungible token balances
+    Tokens {
+        address: String,
+        #[command(flatten)]
+        page: PageArgs,
+    },
+    /// List NFT balances
+    Nfts {
+        address: String,
+        #[arg(long, value_enum, default_value = "kip17")]
+        standard: NftStandard,
+        #[command(flatten)]
+        page: PageArgs,
+    },
+    /// List event logs emitted by an account
+    Logs {
+        address: String,
+        #[arg(long)]
+        signature: Option<EventSignature>,
+        #[arg(long)]
+        from_block: Option<u64>,
+        #[arg(long)]
+        to_block: Option<u64>,
+        #[command(flatten)]
+        page: PageArgs,
+    },
+    /// Show native, token and NFT holdings with their USD value
+    Portfolio { address: String },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ContractCommand {
+    /// Show verified source code and compiler settings
+    Source {
+        address: String,
+        /// Write the source files into this directory
+        #[arg(long)]
+        out_dir: Option<PathBuf>,
+    },
+}
+
+#[derive(Debug, Args)]
+pub struct BurnsArgs {
+    #[arg(long, conflicts_with_all = ["page", "size"])]
+    block: Option<i64>,
+    #[arg(long)]
+    page: Option<i32>,
+    #[arg(long)]
+    size: Option<i32>,
+}
+
+pub async fn run(client: &KaiaScan, command: Command, format: OutputFormat) -> Result<()> {
+    match command {
+        Command::Block {
+            number: Some(number),
+        } => output::print(&client.get_block(number).await?, format),
+        Command::Block { number: None } => output::print(&client.get_latest_block().await?, format),
+        Command::Blocks(args) => {
+            let blocks = client
+                .get_blocks(
+                    args.block_number,
+                    args.start,
+                    args.end,
+                    args.page,
+                    args.size,
+                )
+                .await?;
+            output::print(&blocks, format)
+        }
+        Command::Tx(TxArgs {
+            command: Some(command),
+            ..
+        }) => run_tx(client, command, format).await,
+        Command::Tx(TxArgs { hash, .. }) => {
+            let hash = hash.unwrap_or_default();
+            output::print(&client.get_transaction(&hash).await?, format)
+        }
+        Command::Account(command) => run_account(client, command, format).await,
+        Command::Token { address } => {
+            let token = client.get_fungible_token(Address::new(address)).await?;
+            output::print(&token, format)
+        }
+        Command::Nft { address, token_id } => {
+            let item = client
+                .get_nft_item(Address::new(address), &token_id)
+                .await?;
+            output::print(&item, format)
+        }
+        Command::Contract(ContractCommand::Source { address, out_dir }) => {
+            let source = client
+                .get_contract_source_code(Address::new(address))
+                .await?;
+            if let Some(out_dir) = out_dir {
+                for path in source.write_sources(&out_dir)? {
+                    eprintln!("Wrote {}", path.display());
+                }
+            }
+            output::print(&source, format)
+        }
+        Command::Burns(BurnsArgs {
+            block: Some(block), ..
+        }) => output::print(&client.get_block_burns(block).await?, format),
+        Command::Burns(args) => {
+            let burns = client.get_latest_block_burns(args.page, args.size).await?;
+            output::print(&burns, format)
+        }
+        Command::Rewards { block } => {
+            output::print(&client.get_block_rewards(block).await?, format)
+        }
+        Command::Info => output::print(&client.get_kaia_info().await?, format),
+        Command::Watch(command) => watch::run(client, command, format).await,
+        Command::Export(command) => export::run(client, command).await,
+        Command::Tui => tui::run(client.clone()).await,
+        Command::Config(_) => unreachable!("config commands run without a client"),
+    }
+}
+
+async fn run_tx(client: &KaiaScan, command: TxCommand, format: OutputFormat) -> Result<()> {
+    match command {
+        TxCommand::Logs {
+            hash,
+            signature,
+            page,
+        } => {
+            let logs = client
+                .get_transaction_event_logs(
+                    &hash,
+                    page.page,
+                    page.size,
+                    signature.map(String::from),
+                )
+                .await?;
+            output::print(&logs, format)
+        }
+        TxCommand::Input {
+            hash,
+            decode: false,
+        } => output::print(&client.get_transaction_input_data(&hash).await?, format),
+        TxCommand::Input { hash, decode: true } => {
+            let input = client.get_transaction_input_data(&hash).await?;
+            let decoding = client.decode_input_data(&input)?;
+
+            // One row per candidate signature; more than one means the selector collides.
+            let candidates: Vec<Value> = decoding
+                .candidates
+                .iter()
+                .map(|call| {
+                    let params: serde_json::Map<String, Value> = call
+                        .params
+                        .iter()
+                        .map(|(name, value)| (name.clone(), Value::String(value.to_string())))
+                        .collect();
+                    json!({
+                        "selector": format!("0x{}", hex::encode(decoding.selector)),
+                        "name": call.name,
+                        "signature": call.signature,
+                        "params": params,
+                    })
+                })
+                .collect();
+            output::print(&candidates, format)
+        }
+    }
+}
+
+async fn run_account(
+    client: &KaiaScan,
+    command: AccountCommand,
+    format: OutputFormat,
+) -> Result<()> {
+    match command {
+        AccountCommand::Tokens { address, page } => {
+            let balances = client
+                .get_account_token_balances(Address::new(address), page.page, page.size)
+                .await?;
+            output::print(&balances, format)
+        }
+        AccountCommand::Nfts {
+            address,
+            standard: NftStandard::Kip17,
+            page,
+        } => {
+            let balances = client
+                .get_account_kip17_nft_balances(Address::new(address), page.page, page.size)
+                .await?;
+            output::print(&balances, format)
+        }
+        AccountCommand::Nfts {
+            address,
+            standard: NftStandard::Kip37,
+            page,
+        } => {
+            let balances = client
+                .get_account_kip37_nft_balances(Address::new(address), page.page, page.size)
+                .await?;
+            output::print(&balances, format)
+        }
+        AccountCommand::Logs {
+            address,
+            signature,
+            from_block,
+            to_block,
+            page,
+        } => {
+            let logs = client
+                .get_account_event_logs(
+                    Address::new(address),
+                    page.page,
+                    page.size,
+                    signature.map(String::from),
+                    from_block,
+                    to_block,
+                )
+                .await?;
+            output::print(&logs, format)
+        }
+        AccountCommand::Portfolio { address } => {
+            let portfolio = client.get_account_portfolio(Address::new(address)).await?;
+            output::print(&portfolio, format)
+        }
+    }
+}
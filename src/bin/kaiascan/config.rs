@@ -0,0 +1,95 @@
+use crate::output::{self, OutputFormat};
+use anyhow::{bail, Result};
+use clap::Subcommand;
+use rustsdk::{Config, Profile};
+use serde_json::json;
+use std::path::Path;
+
+#[derive(Debug, Subcommand)]
+pub enum ConfigCommand {
+    /// List the profiles of the config file
+    List,
+    /// Make a profile the active one
+    Use { name: String },
+    /// Show a profile, else the one selected by --profile, else the active one
+    Show { name: Option<String> },
+}
+
+// Shortest API key of which a prefix is shown.
+const MASK_MIN_LENGTH: usize = 16;
+
+// Keeps API keys out of terminal scrollback. Short keys are hidden completely, since a
+// prefix would give away too much of them.
+fn mask(api_key: &str) -> String {
+    if api_key.chars().count() < MASK_MIN_LENGTH {
+        return "****".to_string();
+    }
+    let visible: String = api_key.chars().take(4).collect();
+    format!("{}****", visible)
+}
+
+fn describe(name: &str, profile: &Profile, active: bool) -> serde_json::Value {
+    json!({
+        "name": name,
+        "active": active,
+        "base_url": profile.base_url.as_deref().unwrap_or("mainnet"),
+        "api_key": profile.api_key.as_deref().map(mask),
+        "api_key_env": profile.api_key_env,
+        "timeout_secs": profile.timeout_secs,
+        "rate_limit": profile.rate_limit,
+        "output": profile.output,
+    })
+}
+
+pub fn run(
+    command: ConfigCommand,
+    mut config: Config,
+    path: &Path,
+    selected: Option<String>,
+    format: OutputFormat,
+) -> Result<()> {
+    match command {
+        ConfigCommand::List => {
+            let profiles: Vec<serde_json::Value> = config
+                .profiles
+                .iter()
+                .map(|(name, profile)| {
+                    json!({
+                        "name": name,
+                        "active": config.active.as_deref() == Some(name.as_str()),
+                        "base_url": profile.base_url.as_deref().unwrap_or("mainnet"),
+                    })
+                })
+                .collect();
+            output::print(&profiles, format)
+        }
+        ConfigCommand::Use { name } => {
+            config.set_active(&name)?;
+            config.save(path)?;
+            eprintln!("Active profile is now {}", name);
+            Ok(())
+        }
+        ConfigCommand::Show { name } => {
+            let name = match name.or(selected).or_else(|| config.active.clone()) {
+                Some(name) => name,
+                None => bail!("No active profile; pass a profile name"),
+            };
+            let profile = config.profile(&name)?;
+            let active = config.active.as_deref() == Some(name.as_str());
+            output::print(&describe(&name, profile, active), format)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn masks_short_keys_completely() {
+        assert_eq!(mask(""), "****");
+        assert_eq!(mask("secret"), "****");
+        assert_eq!(mask("abcdefghijklmno"), "****");
+        assert_eq!(mask("abcdefghijklmnop"), "abcd****");
+    }
+}
@@ -0,0 +1,351 @@
+use anyhow::{bail, Context, Result};
+use clap::{Subcommand, ValueEnum};
+use futures::{pin_mut, Stream, StreamExt};
+use indicatif::{ProgressBar, ProgressStyle};
+use rustsdk::{Address, KaiaScan, NftTransfer, TokenTransfer, TransferPage};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+// Column order of exported rows. Token transfers leave the NFT columns empty and the other
+// way round, so both kinds share one file.
+const COLUMNS: [&str; 13] = [
+    "kind",
+    "block_id",
+    "transaction_hash",
+    "transaction_index",
+    "datetime",
+    "from",
+    "to",
+    "fee_payer",
+    "contract_address",
+    "contract_type",
+    "amount",
+    "token_id",
+    "token_count",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Csv,
+    Ndjson,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ExportCommand {
+    /// Write every token and NFT transfer of an account to a file
+    AccountTransfers {
+        address: String,
+        #[arg(long, default_value_t = 0)]
+        from_block: u64,
+        /// Defaults to the latest block when the export starts
+        #[arg(long)]
+        to_block: Option<u64>,
+        #[arg(long, value_enum, default_value = "csv")]
+        format: ExportFormat,
+        #[arg(short = 'o', long = "output-file")]
+        file: PathBuf,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Phase {
+    Token,
+    Nft,
+}
+
+// Saved next to the output file after every page. `bytes` is the output length after the
+// last completed page, so a page that was only partly written is dropped on resume.
+#[derive(Debug, Serialize, Deserialize)]
+struct Progress {
+    address: String,
+    from_block: u64,
+    to_block: u64,
+    format: ExportFormat,
+    phase: Phase,
+    next_page: u32,
+    bytes: u64,
+}
+
+#[derive(Debug, Serialize)]
+struct TransferRow<'a> {
+    kind: &'static str,
+    block_id: u64,
+    transaction_hash: &'a str,
+    transaction_index: u32,
+    datetime: &'a str,
+    from: &'a str,
+    to: &'a str,
+    fee_payer: &'a str,
+    contract_address: &'a str,
+    contract_type: &'a str,
+    amount: Option<u64>,
+    token_id: Option<&'a str>,
+    token_count: Option<u64>,
+}
+
+impl<'a> From<&'a TokenTransfer> for TransferRow<'a> {
+    fn from(transfer: &'a TokenTransfer) -> Self {
+        Self {
+            kind: "token",
+            block_id: transfer.block_id,
+            transaction_hash: &transfer.transaction_hash,
+            transaction_index: transfer.transaction_index,
+            datetime: &transfer.datetime,
+            from: &transfer.from,
+            to: &transfer.to,
+            fee_payer: &transfer.fee_payer,
+            contract_address: &transfer.contract.contract_address,
+            contract_type: &transfer.contract.contract_type,
+            amount: Some(transfer.amount),
+            token_id: None,
+            token_count: None,
+        }
+    }
+}
+
+impl<'a> From<&'a NftTransfer> for TransferRow<'a> {
+    fn from(transfer: &'a NftTransfer) -> Self {
+        Self {
+            kind: "nft",
+            block_id: transfer.block_id,
+            transaction_hash: &transfer.transaction_hash,
+            transaction_index: transfer.transaction_index,
+            datetime: &transfer.datetime,
+            from: &transfer.from,
+            to: &transfer.to,
+            fee_payer: &transfer.fee_payer,
+            contract_address: &transfer.contract.contract_address,
+            contract_type: &transfer.contract.contract_type,
+            amount: None,
+            token_id: Some(&transfer.token_id),
+            token_count: Some(transfer.token_count),
+        }
+    }
+}
+
+fn progress_path(file: &Path) -> PathBuf {
+    let mut path = file.as_os_str().to_owned();
+    path.push(".progress");
+    PathBuf::from(path)
+}
+
+fn load_progress(path: &Path) -> Result<Option<Progress>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let json =
+        fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let progress = serde_json::from_str(&json)
+        .with_context(|| format!("Failed to parse {}", path.display()))?;
+    Ok(Some(progress))
+}
+
+// Written to a temporary file first so a crash never leaves a truncated progress file.
+fn save_progress(path: &Path, progress: &Progress) -> Result<()> {
+    let temporary = path.with_extension("progress.tmp");
+    fs::write(&temporary, serde_json::to_vec(progress)?)
+        .with_context(|| format!("Failed to write {}", temporary.display()))?;
+    fs::rename(&temporary, path).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+fn encode_rows<'a>(
+    rows: impl Iterator<Item = TransferRow<'a>>,
+    format: ExportFormat,
+) -> Result<Vec<u8>> {
+    match format {
+        ExportFormat::Csv => {
+            let mut writer = csv::WriterBuilder::new()
+                .has_headers(false)
+                .from_writer(Vec::new());
+            for row in rows {
+                writer.serialize(row)?;
+            }
+            Ok(writer.into_inner()?)
+        }
+        ExportFormat::Ndjson => {
+            let mut buffer = Vec::new();
+            for row in rows {
+                serde_json::to_writer(&mut buffer, &row)?;
+                buffer.push(b'\n');
+            }
+            Ok(buffer)
+        }
+    }
+}
+
+pub async fn run(client: &KaiaScan, command: ExportCommand) -> Result<()> {
+    match command {
+        ExportCommand::AccountTransfers {
+            address,
+            from_block,
+            to_block,
+            format,
+            file,
+        } => export_account_transfers(client, address, from_block, to_block, format, &file).await,
+    }
+}
+
+async fn export_account_transfers(
+    client: &KaiaScan,
+    address: String,
+    from_block: u64,
+    to_block: Option<u64>,
+    format: ExportFormat,
+    file: &Path,
+) -> Result<()> {
+    let progress_file = progress_path(file);
+
+    let (mut progress, mut output) = match load_progress(&progress_file)? {
+        Some(progress) => {
+            if !progress.address.eq_ignore_ascii_case(&address)
+                || progress.from_block != from_block
+                || to_block.is_some_and(|to_block| to_block != progress.to_block)
+                || progress.format != format
+            {
+                bail!(
+                    "{} belongs to a different export; remove it to start over",
+                    progress_file.display()
+                );
+            }
+
+            let output = OpenOptions::new()
+                .append(true)
+                .open(file)
+                .with_context(|| format!("Failed to open {}", file.display()))?;
+            output.set_len(progress.bytes)?;
+            eprintln!(
+                "Resuming {} transfers at page {}",
+                match progress.phase {
+                    Phase::Token => "token",
+                    Phase::Nft => "NFT",
+                },
+                progress.next_page
+            );
+            (progress, output)
+        }
+        None => {
+            // Pin the upper bound so pages stay stable across resumes.
+            let to_block = match to_block {
+                Some(to_block) => to_block,
+                None => client.get_latest_block().await?.block_id as u64,
+            };
+            if from_block > to_block {
+                bail!(
+                    "--from-block {} is after --to-block {}",
+                    from_block,
+                    to_block
+                );
+            }
+
+            let mut output = File::create(file)
+                .with_context(|| format!("Failed to create {}", file.display()))?;
+            let mut bytes = 0;
+            if format == ExportFormat::Csv {
+                let header = format!("{}\n", COLUMNS.join(","));
+                output.write_all(header.as_bytes())?;
+                bytes = header.len() as u64;
+            }
+
+            let progress = Progress {
+                address,
+                from_block,
+                to_block,
+                format,
+                phase: Phase::Token,
+                next_page: 1,
+                bytes,
+            };
+            save_progress(&progress_file, &progress)?;
+            (progress, output)
+        }
+    };
+
+    let address = Address::new(progress.address.clone());
+    let range = progress.from_block..=progress.to_block;
+    let mut rows = 0;
+
+    if progress.phase == Phase::Token {
+        let pages =
+            client.account_token_transfer_pages(address.clone(), range.clone(), progress.next_page);
+        rows += write_pages(
+            pages,
+            "token transfers",
+            &mut output,
+            &mut progress,
+            &progress_file,
+        )
+        .await?;
+
+        progress.phase = Phase::Nft;
+        progress.next_page = 1;
+        save_progress(&progress_file, &progress)?;
+    }
+
+    let pages = client.account_nft_transfer_pages(address, range, progress.next_page);
+    rows += write_pages(
+        pages,
+        "NFT transfers",
+        &mut output,
+        &mut progress,
+        &progress_file,
+    )
+    .await?;
+
+    output.sync_all()?;
+    fs::remove_file(&progress_file)
+        .with_context(|| format!("Failed to remove {}", progress_file.display()))?;
+    eprintln!("Wrote {} transfers to {}", rows, file.display());
+
+    Ok(())
+}
+
+// Appends every page to `output` and records it as completed before fetching the next one.
+async fn write_pages<T>(
+    pages: impl Stream<Item = Result<TransferPage<T>>>,
+    label: &'static str,
+    output: &mut File,
+    progress: &mut Progress,
+    progress_file: &Path,
+) -> Result<usize>
+where
+    for<'a> TransferRow<'a>: From<&'a T>,
+{
+    pin_mut!(pages);
+
+    let bar = ProgressBar::new(0).with_style(
+        ProgressStyle::with_template("{msg:>16} [{bar:40}] {pos}/{len} pages")?
+            .progress_chars("=> "),
+    );
+    bar.set_message(label);
+    bar.set_position(progress.next_page.saturating_sub(1) as u64);
+
+    let mut rows = 0;
+
+    while let Some(page) = pages.next().await {
+        let page = page.with_context(|| {
+            format!(
+                "Export stopped at {} page {}; rerun the same command to resume",
+                label, progress.next_page
+            )
+        })?;
+
+        let buffer = encode_rows(page.results.iter().map(TransferRow::from), progress.format)?;
+        output.write_all(&buffer)?;
+        output.flush()?;
+        rows += page.results.len();
+
+        progress.bytes += buffer.len() as u64;
+        progress.next_page = page.page + 1;
+        save_progress(progress_file, progress)?;
+
+        bar.set_length(page.total_page.max(page.page) as u64);
+        bar.set_position(page.page as u64);
+    }
+
+    bar.finish();
+    Ok(rows)
+}
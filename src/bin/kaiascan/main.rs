@@ -0,0 +1,180 @@
+mod commands;
+mod config;
+mod export;
+mod output;
+mod tui;
+mod watch;
+
+use anyhow::Context;
+use clap::{Parser, ValueEnum};
+use commands::Command;
+use output::OutputFormat;
+use rustsdk::{ApiError, Config, KaiaScan};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+// Exit codes, one per error class so scripts can tell failures apart.
+const EXIT_FAILURE: u8 = 1;
+const EXIT_NETWORK: u8 = 3;
+const EXIT_UNAUTHORIZED: u8 = 4;
+const EXIT_NOT_FOUND: u8 = 5;
+const EXIT_RATE_LIMITED: u8 = 6;
+const EXIT_API: u8 = 7;
+const EXIT_DECODE: u8 = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Network {
+    Mainnet,
+    #[value(alias = "testnet")]
+    Kairos,
+}
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "kaiascan",
+    version,
+    about = "Query the Kaia blockchain through KaiaScan"
+)]
+struct Cli {
+    /// Config file [default: ~/.config/kaiascan/config.toml]
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
+
+    /// Profile from the config file [default: the active profile]
+    #[arg(long, global = true, env = "KAIASCAN_PROFILE")]
+    profile: Option<String>,
+
+    /// Overrides the profile's base URL
+    #[arg(long, global = true, value_enum, env = "KAIASCAN_NETWORK")]
+    network: Option<Network>,
+
+    #[arg(long, global = true, env = "KAIASCAN_API_KEY", hide_env_values = true)]
+    api_key: Option<String>,
+
+    /// Output format [default: the profile's, else json]
+    #[arg(long, global = true, value_enum)]
+    output: Option<OutputFormat>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+fn exit_code(err: &anyhow::Error) -> u8 {
+    let Some(api_error) = err
+        .chain()
+        .find_map(|cause| cause.downcast_ref::<ApiError>())
+    else {
+        return EXIT_FAILURE;
+    };
+
+    match api_error {
+        ApiError::Request(_) => EXIT_NETWORK,
+        ApiError::Status {
+            status: 401 | 403, ..
+        } => EXIT_UNAUTHORIZED,
+        ApiError::Status { status: 404, .. } => EXIT_NOT_FOUND,
+        ApiError::Status { status: 429, .. } => EXIT_RATE_LIMITED,
+        ApiError::Status { .. } => EXIT_API,
+        ApiError::Decode(_) => EXIT_DECODE,
+    }
+}
+
+async fn run(cli: Cli) -> anyhow::Result<()> {
+    // Without a config path (no HOME or XDG_CONFIG_HOME), there is no config to load, as in
+    // `Config::load_default`.
+    let config_path = cli.config.or_else(Config::default_path);
+    let config = match &config_path {
+        Some(path) => Config::load(path)?,
+        None => Config::default(),
+    };
+
+    // Config commands must work even when the active profile is missing or broken, since
+    // they are how it gets fixed.
+    if let Command::Config(command) = cli.command {
+        let config_path = config_path.context("Cannot locate the config file; pass --config")?;
+        let output = cli.output.unwrap_or(OutputFormat::Json);
+        return config::run(command, config, &config_path, cli.profile, output);
+    }
+
+    let profile = match &cli.profile {
+        Some(name) => Some(config.profile(name)?.clone()),
+        None => config.active_profile()?.map(|(_, profile)| profile.clone()),
+    };
+
+    // Command-line flags win over the profile.
+    let output = match (
+        cli.output,
+        profile.as_ref().and_then(|p| p.output.as_deref()),
+    ) {
+        (Some(output), _) => output,
+        (None, Some(output)) => OutputFormat::from_str(output, true)
+            .map_err(|_| anyhow::anyhow!("Unknown output format {} in profile", output))?,
+        (None, None) => OutputFormat::Json,
+    };
+
+    let mut client = KaiaScan::new(cli.network == Some(Network::Kairos))?;
+    if let Some(mut profile) = profile {
+        if cli.network.is_some() {
+            profile.base_url = None;
+        }
+        if cli.api_key.is_some() {
+            profile.api_key = None;
+            profile.api_key_env = None;
+        }
+        client = profile.apply(client)?;
+    }
+    if let Some(api_key) = cli.api_key {
+        client = client.with_api_key(api_key);
+    }
+
+    commands::run(&client, cli.command, output).await
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    // clap exits with status 2 on usage errors.
+    let cli = Cli::parse();
+
+    match run(cli).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("Error: {:#}", err);
+            ExitCode::from(exit_code(&err))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(status: u16) -> anyhow::Error {
+        ApiError::Status {
+            status,
+            body: String::new(),
+        }
+        .into()
+    }
+
+    #[test]
+    fn maps_api_errors_to_exit_codes() {
+        let request = reqwest::Client::new().get("not a url").build().unwrap_err();
+        assert_eq!(exit_code(&ApiError::Request(request).into()), EXIT_NETWORK);
+        assert_eq!(exit_code(&status(401)), EXIT_UNAUTHORIZED);
+        assert_eq!(exit_code(&status(403)), EXIT_UNAUTHORIZED);
+        assert_eq!(exit_code(&status(404)), EXIT_NOT_FOUND);
+        assert_eq!(exit_code(&status(429)), EXIT_RATE_LIMITED);
+        assert_eq!(exit_code(&status(500)), EXIT_API);
+        assert_eq!(exit_code(&status(400)), EXIT_API);
+
+        let decode = serde_json::from_str::<u64>("\"x\"").unwrap_err();
+        assert_eq!(exit_code(&ApiError::Decode(decode).into()), EXIT_DECODE);
+    }
+
+    #[test]
+    fn finds_api_errors_behind_context() {
+        let err = status(404).context("Failed to fetch block 1");
+        assert_eq!(exit_code(&err), EXIT_NOT_FOUND);
+        assert_eq!(exit_code(&anyhow::anyhow!("bad input")), EXIT_FAILURE);
+    }
+}
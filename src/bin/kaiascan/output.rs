@@ -0,0 +1,249 @@
+use anyhow::Result;
+use clap::ValueEnum;
+use serde::Serialize;
+use serde_json::Value;
+use std::io::{self, Write};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    Json,
+    Table,
+    Csv,
+}
+
+// A flattened row: column name and rendered cell, in first-seen column order.
+pub type Row = Vec<(String, String)>;
+
+pub fn print<T: Serialize>(value: &T, format: OutputFormat) -> Result<()> {
+    let value = serde_json::to_value(value)?;
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+
+    match format {
+        OutputFormat::Json => writeln!(out, "{}", serde_json::to_string_pretty(&value)?)?,
+        OutputFormat::Table => write_table(&mut out, &rows(&value))?,
+        OutputFormat::Csv => write_csv(&mut out, &rows(&value))?,
+    }
+
+    Ok(())
+}
+
+// Paged responses are rendered one row per result; anything else as a single row.
+pub fn rows(value: &Value) -> Vec<Row> {
+    let items = match value {
+        Value::Array(items) => items.as_slice(),
+        Value::Object(fields) => match fields.get("results") {
+            Some(Value::Array(results)) => results.as_slice(),
+            _ => std::slice::from_ref(value),
+        },
+        _ => std::slice::from_ref(value),
+    };
+
+    items
+        .iter()
+        .map(|item| {
+            let mut row = Vec::new();
+            flatten("", item, &mut row);
+            row
+        })
+        .collect()
+}
+
+fn flatten(prefix: &str, value: &Value, row: &mut Row) {
+    match value {
+        Value::Object(fields) if !fields.is_empty() => {
+            for (key, field) in fields {
+                let key = if prefix.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", prefix, key)
+                };
+                flatten(&key, field, row);
+            }
+        }
+        _ => {
+            let column = if prefix.is_empty() { "value" } else { prefix };
+            row.push((column.to_string(), cell(value)));
+        }
+    }
+}
+
+fn cell(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+pub fn columns(rows: &[Row]) -> Vec<String> {
+    let mut columns: Vec<String> = Vec::new();
+    for row in rows {
+        for (column, _) in row {
+            if !columns.contains(column) {
+                columns.push(column.clone());
+            }
+        }
+    }
+    columns
+}
+
+pub fn lookup<'a>(row: &'a Row, column: &str) -> &'a str {
+    row.iter()
+        .find(|(name, _)| name == column)
+        .map(|(_, cell)| cell.as_str())
+        .unwrap_or("")
+}
+
+fn write_table(out: &mut impl Write, rows: &[Row]) -> Result<()> {
+    // A single record reads better as field/value pairs.
+    if rows.len() == 1 {
+        let width = rows[0]
+            .iter()
+            .map(|(name, _)| name.len())
+            .max()
+            .unwrap_or(0);
+        for (name, cell) in &rows[0] {
+            writeln!(out, "{:width$}  {}", name, cell, width = width)?;
+        }
+        return Ok(());
+    }
+
+    let columns = columns(rows);
+    let widths: Vec<usize> = columns
+        .iter()
+        .map(|column| {
+            rows.iter()
+                .map(|row| lookup(row, column).len())
+                .max()
+                .unwrap_or(0)
+                .max(column.len())
+        })
+        .collect();
+
+    let header: Vec<String> = columns
+        .iter()
+        .zip(&widths)
+        .map(|(column, width)| format!("{:width$}", column.to_uppercase(), width = width))
+        .collect();
+    writeln!(out, "{}", header.join("  ").trim_end())?;
+
+    for row in rows {
+        let cells: Vec<String> = columns
+            .iter()
+            .zip(&widths)
+            .map(|(column, width)| format!("{:width$}", lookup(row, column), width = width))
+            .collect();
+        writeln!(out, "{}", cells.join("  ").trim_end())?;
+    }
+
+    Ok(())
+}
+
+fn write_csv(out: &mut impl Write, rows: &[Row]) -> Result<()> {
+    let columns = columns(rows);
+    let mut writer = csv::Writer::from_writer(out);
+
+    writer.write_record(&columns)?;
+    for row in rows {
+        writer.write_record(columns.iter().map(|column| lookup(row, column)))?;
+    }
+    writer.flush()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn row(cells: &[(&str, &str)]) -> Row {
+        cells
+            .iter()
+            .map(|(column, cell)| (column.to_string(), cell.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn flattens_nested_fields_with_dotted_columns() {
+        let value = json!({
+            "block_id": 7,
+            "hash": "0xabc",
+            "contract": { "address": "0xc", "type": null },
+            "tags": ["a", "b"],
+            "meta": {},
+        });
+
+        assert_eq!(
+            rows(&value),
+            vec![row(&[
+                ("block_id", "7"),
+                ("hash", "0xabc"),
+                ("contract.address", "0xc"),
+                ("contract.type", ""),
+                ("tags", r#"["a","b"]"#),
+                ("meta", "{}"),
+            ])]
+        );
+    }
+
+    #[test]
+    fn renders_one_row_per_result() {
+        let paged = json!({
+            "paging": { "last": true },
+            "results": [{ "id": 1 }, { "id": 2, "name": "two" }],
+        });
+        assert_eq!(
+            rows(&paged),
+            vec![row(&[("id", "1")]), row(&[("id", "2"), ("name", "two")])]
+        );
+
+        let list = json!([{ "id": 1 }, 5]);
+        assert_eq!(
+            rows(&list),
+            vec![row(&[("id", "1")]), row(&[("value", "5")])]
+        );
+        assert_eq!(rows(&json!("text")), vec![row(&[("value", "text")])]);
+    }
+
+    #[test]
+    fn collects_columns_in_first_seen_order() {
+        let rows = vec![
+            row(&[("id", "1"), ("name", "one")]),
+            row(&[("extra", "x"), ("id", "2")]),
+        ];
+        assert_eq!(columns(&rows), vec!["id", "name", "extra"]);
+        assert_eq!(lookup(&rows[1], "name"), "");
+    }
+
+    #[test]
+    fn writes_csv_with_a_header_and_blank_missing_cells() {
+        let rows = rows(&json!([
+            { "id": 1, "note": "a, \"quoted\" note" },
+            { "id": 2, "extra": true },
+        ]));
+        let mut out = Vec::new();
+        write_csv(&mut out, &rows).unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "id,note,extra\n1,\"a, \"\"quoted\"\" note\",\n2,,true\n"
+        );
+    }
+
+    #[test]
+    fn writes_tables() {
+        let mut out = Vec::new();
+        write_table(
+            &mut out,
+            &rows(&json!([{ "id": 1, "name": "one" }, { "id": 22 }])),
+        )
+        .unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "ID  NAME\n1   one\n22\n");
+
+        let mut out = Vec::new();
+        write_table(&mut out, &rows(&json!({ "id": 1, "name": "one" }))).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "id    1\nname  one\n");
+    }
+}
@@ -0,0 +1,67 @@
+mod app;
+mod view;
+
+use anyhow::Result;
+use app::{App, Update};
+use ratatui::crossterm::event::{self, Event, KeyEvent, KeyEventKind};
+use ratatui::DefaultTerminal;
+use rustsdk::KaiaScan;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+const REFRESH_INTERVAL: Duration = Duration::from_secs(10);
+const INPUT_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+// Terminal input is read on a plain thread, since crossterm's reader blocks.
+fn spawn_input_reader() -> mpsc::UnboundedReceiver<KeyEvent> {
+    let (keys, receiver) = mpsc::unbounded_channel();
+
+    std::thread::spawn(move || loop {
+        if keys.is_closed() {
+            break;
+        }
+        match event::poll(INPUT_POLL_INTERVAL) {
+            Ok(true) => match event::read() {
+                Ok(Event::Key(key)) if key.kind == KeyEventKind::Press => {
+                    if keys.send(key).is_err() {
+                        break;
+                    }
+                }
+                Ok(_) => {}
+                Err(_) => break,
+            },
+            Ok(false) => {}
+            Err(_) => break,
+        }
+    });
+
+    receiver
+}
+
+async fn run_app(terminal: &mut DefaultTerminal, client: KaiaScan) -> Result<()> {
+    let (updates, mut update_receiver) = mpsc::unbounded_channel::<Update>();
+    let mut keys = spawn_input_reader();
+    let mut refresh = tokio::time::interval(REFRESH_INTERVAL);
+
+    let mut app = App::new(client, updates);
+    app.start_block_feed();
+
+    while !app.quit {
+        terminal.draw(|frame| view::draw(frame, &mut app))?;
+
+        tokio::select! {
+            Some(update) = update_receiver.recv() => app.apply(update),
+            Some(key) = keys.recv() => app.handle_key(key),
+            _ = refresh.tick() => app.refresh(),
+        }
+    }
+
+    Ok(())
+}
+
+pub async fn run(client: KaiaScan) -> Result<()> {
+    let mut terminal = ratatui::try_init()?;
+    let result = run_app(&mut terminal, client).await;
+    ratatui::restore();
+    result
+}
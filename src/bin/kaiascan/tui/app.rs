@@ -0,0 +1,453 @@
+use crate::output::{self, Row};
+use anyhow::Result;
+use futures::StreamExt;
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::widgets::TableState;
+use rustsdk::{Address, KaiaScan, LatestBlock};
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use tokio::sync::mpsc::UnboundedSender;
+
+const MAX_BLOCKS: usize = 100;
+const INITIAL_BLOCKS: i64 = 20;
+const PAGE_SIZE: u32 = 100;
+
+// One tab of a screen and the SDK call that fills it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Source {
+    BlockTransactions(i64),
+    Transaction(String),
+    TransactionLogs(String),
+    TransactionInput(String),
+    TransactionTokenTransfers(String),
+    TransactionNftTransfers(String),
+    InternalTransactions(String),
+    TokenBalances(String),
+    Kip17Balances(String),
+    Kip37Balances(String),
+}
+
+impl Source {
+    pub fn title(&self) -> &'static str {
+        match self {
+            Source::BlockTransactions(_) => "Transactions",
+            Source::Transaction(_) => "Overview",
+            Source::TransactionLogs(_) => "Logs",
+            Source::TransactionInput(_) => "Input",
+            Source::TransactionTokenTransfers(_) => "Token transfers",
+            Source::TransactionNftTransfers(_) => "NFT transfers",
+            Source::InternalTransactions(_) => "Internal txs",
+            Source::TokenBalances(_) => "Tokens",
+            Source::Kip17Balances(_) => "KIP-17",
+            Source::Kip37Balances(_) => "KIP-37",
+        }
+    }
+
+    async fn fetch(&self, client: &KaiaScan) -> Result<Vec<Row>> {
+        let value = match self {
+            Source::BlockTransactions(block) => to_value(client.block_transactions(*block).await?),
+            Source::Transaction(hash) => to_value(client.get_transaction(hash).await?),
+            Source::TransactionLogs(hash) => to_value(
+                client
+                    .get_transaction_event_logs(hash, None, Some(PAGE_SIZE), None)
+                    .await?,
+            ),
+            Source::TransactionInput(hash) => {
+                to_value(client.get_transaction_input_data(hash).await?)
+            }
+            Source::TransactionTokenTransfers(hash) => to_value(
+                client
+                    .get_transaction_token_transfers(hash, None, Some(PAGE_SIZE))
+                    .await?,
+            ),
+            Source::TransactionNftTransfers(hash) => to_value(
+                client
+                    .get_transaction_nft_transfers(hash, None, Some(PAGE_SIZE))
+                    .await?,
+            ),
+            Source::InternalTransactions(hash) => to_value(
+                client
+                    .get_transaction_internal_transactions_page(hash, None, Some(PAGE_SIZE))
+                    .await?,
+            ),
+            Source::TokenBalances(address) => to_value(
+                client
+                    .get_account_token_balances(Address::new(address), None, Some(PAGE_SIZE))
+                    .await?,
+            ),
+            Source::Kip17Balances(address) => to_value(
+                client
+                    .get_account_kip17_nft_balances(Address::new(address), None, Some(PAGE_SIZE))
+                    .await?,
+            ),
+            Source::Kip37Balances(address) => to_value(
+                client
+                    .get_account_kip37_nft_balances(Address::new(address), None, Some(PAGE_SIZE))
+                    .await?,
+            ),
+        }?;
+
+        Ok(output::rows(&value))
+    }
+}
+
+fn to_value<T: Serialize>(value: T) -> Result<serde_json::Value> {
+    Ok(serde_json::to_value(value)?)
+}
+
+#[derive(Debug, Clone)]
+pub enum Screen {
+    Blocks,
+    Block(i64),
+    Transaction { hash: String, tab: usize },
+    Address { address: String, tab: usize },
+}
+
+impl Screen {
+    pub fn sources(&self) -> Vec<Source> {
+        match self {
+            Screen::Blocks => Vec::new(),
+            Screen::Block(block) => vec![Source::BlockTransactions(*block)],
+            Screen::Transaction { hash, .. } => vec![
+                Source::Transaction(hash.clone()),
+                Source::TransactionLogs(hash.clone()),
+                Source::TransactionInput(hash.clone()),
+                Source::TransactionTokenTransfers(hash.clone()),
+                Source::TransactionNftTransfers(hash.clone()),
+                Source::InternalTransactions(hash.clone()),
+            ],
+            Screen::Address { address, .. } => vec![
+                Source::TokenBalances(address.clone()),
+                Source::Kip17Balances(address.clone()),
+                Source::Kip37Balances(address.clone()),
+            ],
+        }
+    }
+
+    pub fn tab(&self) -> usize {
+        match self {
+            Screen::Transaction { tab, .. } | Screen::Address { tab, .. } => *tab,
+            _ => 0,
+        }
+    }
+
+    pub fn title(&self) -> String {
+        match self {
+            Screen::Blocks => "Latest blocks".to_string(),
+            Screen::Block(block) => format!("Block {}", block),
+            Screen::Transaction { hash, .. } => format!("Tx {}", hash),
+            Screen::Address { address, .. } => format!("Address {}", address),
+        }
+    }
+
+    fn source(&self) -> Option<Source> {
+        self.sources().into_iter().nth(self.tab())
+    }
+
+    fn cycle_tab(&mut self, forward: bool) {
+        let count = self.sources().len();
+        if let Screen::Transaction { tab, .. } | Screen::Address { tab, .. } = self {
+            *tab = if forward {
+                (*tab + 1) % count
+            } else {
+                (*tab + count - 1) % count
+            };
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Panel {
+    pub rows: Vec<Row>,
+    pub state: TableState,
+    pub loading: bool,
+    pub error: Option<String>,
+}
+
+impl Panel {
+    fn replace_rows(&mut self, rows: Vec<Row>) {
+        let selected = self.state.selected().unwrap_or(0);
+        self.state
+            .select((!rows.is_empty()).then(|| selected.min(rows.len() - 1)));
+        self.rows = rows;
+        self.error = None;
+    }
+
+    fn selected_row(&self) -> Option<&Row> {
+        self.state.selected().and_then(|index| self.rows.get(index))
+    }
+
+    fn move_selection(&mut self, delta: isize) {
+        if self.rows.is_empty() {
+            return;
+        }
+        let current = self.state.selected().unwrap_or(0) as isize;
+        let last = self.rows.len() as isize - 1;
+        self.state
+            .select(Some((current + delta).clamp(0, last) as usize));
+    }
+}
+
+pub enum Update {
+    Block(Result<LatestBlock>),
+    Panel(Source, Result<Vec<Row>>),
+}
+
+pub struct App {
+    client: KaiaScan,
+    updates: UnboundedSender<Update>,
+    pub stack: Vec<Screen>,
+    blocks: VecDeque<LatestBlock>,
+    pub blocks_panel: Panel,
+    pub panels: HashMap<Source, Panel>,
+    // Text typed after `/`, while the prompt is open.
+    pub prompt: Option<String>,
+    pub status: Option<String>,
+    pub quit: bool,
+}
+
+impl App {
+    pub fn new(client: KaiaScan, updates: UnboundedSender<Update>) -> Self {
+        Self {
+            client,
+            updates,
+            stack: vec![Screen::Blocks],
+            blocks: VecDeque::new(),
+            blocks_panel: Panel {
+                loading: true,
+                ..Panel::default()
+            },
+            panels: HashMap::new(),
+            prompt: None,
+            status: None,
+            quit: false,
+        }
+    }
+
+    pub fn screen(&self) -> &Screen {
+        self.stack.last().unwrap_or(&Screen::Blocks)
+    }
+
+    pub fn panel(&self) -> Option<&Panel> {
+        match self.screen().source() {
+            Some(source) => self.panels.get(&source),
+            None => Some(&self.blocks_panel),
+        }
+    }
+
+    fn panel_mut(&mut self) -> Option<&mut Panel> {
+        match self.screen().source() {
+            Some(source) => self.panels.get_mut(&source),
+            None => Some(&mut self.blocks_panel),
+        }
+    }
+
+    // Follows new blocks for as long as the UI runs, after seeding the list with the blocks
+    // just before the head.
+    pub fn start_block_feed(&self) {
+        let client = self.client.clone();
+        let updates = self.updates.clone();
+
+        tokio::spawn(async move {
+            let blocks = client.watch_blocks();
+            futures::pin_mut!(blocks);
+            let mut seeded = false;
+
+            while let Some(block) = blocks.next().await {
+                if let (false, Ok(latest)) = (seeded, &block) {
+                    seeded = true;
+                    let previous: Vec<i64> =
+                        ((latest.block_id - INITIAL_BLOCKS).max(0)..latest.block_id).collect();
+                    for block in client.get_blocks_batch(&previous).await {
+                        let _ = updates.send(Update::Block(block.map(LatestBlock::from)));
+                    }
+                }
+                if updates.send(Update::Block(block)).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+
+    // Loads the current tab in the background. Stale rows stay visible until it finishes.
+    pub fn refresh(&mut self) {
+        let Some(source) = self.screen().source() else {
+            return;
+        };
+        let panel = self.panels.entry(source.clone()).or_default();
+        if panel.loading {
+            return;
+        }
+        panel.loading = true;
+
+        let client = self.client.clone();
+        let updates = self.updates.clone();
+        tokio::spawn(async move {
+            let rows = source.fetch(&client).await;
+            let _ = updates.send(Update::Panel(source, rows));
+        });
+    }
+
+    fn refresh_if_empty(&mut self) {
+        let loaded = self
+            .screen()
+            .source()
+            .is_some_and(|source| self.panels.contains_key(&source));
+        if !loaded {
+            self.refresh();
+        }
+    }
+
+    pub fn apply(&mut self, update: Update) {
+        match update {
+            Update::Block(Ok(block)) => {
+                self.blocks.retain(|known| known.block_id != block.block_id);
+                let position = self
+                    .blocks
+                    .iter()
+                    .position(|known| known.block_id < block.block_id)
+                    .unwrap_or(self.blocks.len());
+                self.blocks.insert(position, block);
+                self.blocks.truncate(MAX_BLOCKS);
+
+                self.blocks_panel.loading = false;
+                match to_value(&self.blocks) {
+                    Ok(value) => self.blocks_panel.replace_rows(output::rows(&value)),
+                    Err(err) => self.blocks_panel.error = Some(format!("{:#}", err)),
+                }
+            }
+            Update::Block(Err(err)) => {
+                self.blocks_panel.loading = false;
+                self.blocks_panel.error = Some(format!("{:#}", err));
+            }
+            Update::Panel(source, rows) => {
+                let panel = self.panels.entry(source).or_default();
+                panel.loading = false;
+                match rows {
+                    Ok(rows) => panel.replace_rows(rows),
+                    Err(err) => panel.error = Some(format!("{:#}", err)),
+                }
+            }
+        }
+    }
+
+    fn push(&mut self, screen: Screen) {
+        self.stack.push(screen);
+        self.status = None;
+        self.refresh_if_empty();
+    }
+
+    fn selected(&self, column: &str) -> Option<String> {
+        let row = self.panel()?.selected_row()?;
+        let value = output::lookup(row, column);
+        (!value.is_empty()).then(|| value.to_string())
+    }
+
+    fn open_selected(&mut self) {
+        let next = match self.screen() {
+            Screen::Blocks => self
+                .selected("block_id")
+                .and_then(|block| block.parse().ok())
+                .map(Screen::Block),
+            Screen::Address { .. } => None,
+            Screen::Transaction { hash, .. } => self
+                .selected("transaction_hash")
+                .or_else(|| self.selected("transactionHash"))
+                .filter(|selected| selected != hash)
+                .map(|hash| Screen::Transaction { hash, tab: 0 }),
+            Screen::Block(_) => self
+                .selected("transaction_hash")
+                .map(|hash| Screen::Transaction { hash, tab: 0 }),
+        };
+
+        match next {
+            Some(screen) => self.push(screen),
+            None => self.status = Some("Nothing to open in this row".to_string()),
+        }
+    }
+
+    fn open_address(&mut self, column: &str) {
+        match self.selected(column) {
+            Some(address) => self.push(Screen::Address { address, tab: 0 }),
+            None => self.status = Some(format!("Row has no `{}` address", column)),
+        }
+    }
+
+    // Accepts a block number, a transaction hash or an address.
+    fn submit(&mut self, query: &str) {
+        let query = query.trim();
+        let screen = if !query.is_empty() && query.chars().all(|c| c.is_ascii_digit()) {
+            query.parse().ok().map(Screen::Block)
+        } else if query.starts_with("0x") && query.len() == 66 {
+            Some(Screen::Transaction {
+                hash: query.to_string(),
+                tab: 0,
+            })
+        } else if query.starts_with("0x") && query.len() == 42 {
+            Some(Screen::Address {
+                address: query.to_string(),
+                tab: 0,
+            })
+        } else {
+            None
+        };
+
+        match screen {
+            Some(screen) => self.push(screen),
+            None => self.status = Some(format!("Not a block, transaction or address: {}", query)),
+        }
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) {
+        if let Some(prompt) = &mut self.prompt {
+            match key.code {
+                KeyCode::Enter => {
+                    let query = std::mem::take(prompt);
+                    self.prompt = None;
+                    self.submit(&query);
+                }
+                KeyCode::Esc => self.prompt = None,
+                KeyCode::Backspace => {
+                    prompt.pop();
+                }
+                KeyCode::Char(c) => prompt.push(c),
+                _ => {}
+            }
+            return;
+        }
+
+        match key.code {
+            KeyCode::Char('q') => self.quit = true,
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => self.quit = true,
+            KeyCode::Esc | KeyCode::Backspace if self.stack.len() > 1 => {
+                self.stack.pop();
+                self.status = None;
+            }
+            KeyCode::Down | KeyCode::Char('j') => self.scroll(1),
+            KeyCode::Up | KeyCode::Char('k') => self.scroll(-1),
+            KeyCode::PageDown => self.scroll(20),
+            KeyCode::PageUp => self.scroll(-20),
+            KeyCode::Tab | KeyCode::Right | KeyCode::Char('l') => self.switch_tab(true),
+            KeyCode::BackTab | KeyCode::Left | KeyCode::Char('h') => self.switch_tab(false),
+            KeyCode::Enter => self.open_selected(),
+            KeyCode::Char('f') => self.open_address("from"),
+            KeyCode::Char('t') => self.open_address("to"),
+            KeyCode::Char('r') => self.refresh(),
+            KeyCode::Char('/') => self.prompt = Some(String::new()),
+            _ => {}
+        }
+    }
+
+    fn scroll(&mut self, delta: isize) {
+        if let Some(panel) = self.panel_mut() {
+            panel.move_selection(delta);
+        }
+    }
+
+    fn switch_tab(&mut self, forward: bool) {
+        if let Some(screen) = self.stack.last_mut() {
+            screen.cycle_tab(forward);
+        }
+        self.refresh_if_empty();
+    }
+}
@@ -0,0 +1,131 @@
+use super::app::{App, Panel, Screen};
+use crate::output;
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Cell, Paragraph, Row, Table, Tabs};
+use ratatui::Frame;
+
+const MAX_COLUMN_WIDTH: usize = 66;
+const HELP: &str =
+    "↑↓ select  ⏎ open  f/t from/to address  ⇥ tab  / search  r refresh  esc back  q quit";
+
+pub fn draw(frame: &mut Frame, app: &mut App) {
+    let [header, tabs, body, footer] = Layout::vertical([
+        Constraint::Length(1),
+        Constraint::Length(1),
+        Constraint::Min(0),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+
+    let breadcrumbs: Vec<String> = app.stack.iter().map(Screen::title).collect();
+    frame.render_widget(
+        Paragraph::new(breadcrumbs.join(" › ")).style(Style::new().add_modifier(Modifier::BOLD)),
+        header,
+    );
+
+    let screen = app.screen().clone();
+    let sources = screen.sources();
+    if sources.len() > 1 {
+        let titles = sources.iter().map(|source| source.title());
+        frame.render_widget(
+            Tabs::new(titles)
+                .select(screen.tab())
+                .highlight_style(Style::new().fg(Color::Yellow)),
+            tabs,
+        );
+    }
+
+    let title = sources
+        .get(screen.tab())
+        .map_or("Blocks", |source| source.title());
+    let panel = match sources.get(screen.tab()) {
+        Some(source) => app.panels.get_mut(source),
+        None => Some(&mut app.blocks_panel),
+    };
+    match panel {
+        Some(panel) => draw_panel(frame, body, title, panel),
+        None => frame.render_widget(Block::bordered().title(title), body),
+    }
+
+    let footer_line = match (&app.prompt, app.panel()) {
+        (Some(prompt), _) => Line::from(format!("/{}", prompt)),
+        (
+            None,
+            Some(Panel {
+                error: Some(error), ..
+            }),
+        ) => Line::from(Span::styled(error.clone(), Style::new().fg(Color::Red))),
+        _ => match &app.status {
+            Some(status) => Line::from(status.clone()),
+            None => Line::from(Span::styled(HELP, Style::new().fg(Color::DarkGray))),
+        },
+    };
+    frame.render_widget(Paragraph::new(footer_line), footer);
+}
+
+fn draw_panel(frame: &mut Frame, area: Rect, title: &str, panel: &mut Panel) {
+    let title = if panel.loading {
+        format!("{} (loading…)", title)
+    } else {
+        format!("{} ({})", title, panel.rows.len())
+    };
+    let block = Block::default().borders(Borders::ALL).title(title);
+
+    // A single record reads better as field/value pairs.
+    let (header, rows, widths): (Vec<String>, Vec<Vec<String>>, Vec<usize>) =
+        if panel.rows.len() == 1 {
+            let rows: Vec<Vec<String>> = panel.rows[0]
+                .iter()
+                .map(|(name, value)| vec![name.clone(), value.clone()])
+                .collect();
+            let width = rows.iter().map(|row| row[0].len()).max().unwrap_or(0);
+            (
+                vec!["FIELD".to_string(), "VALUE".to_string()],
+                rows,
+                vec![width, usize::MAX],
+            )
+        } else {
+            let columns = output::columns(&panel.rows);
+            let rows: Vec<Vec<String>> = panel
+                .rows
+                .iter()
+                .map(|row| {
+                    columns
+                        .iter()
+                        .map(|column| output::lookup(row, column).to_string())
+                        .collect()
+                })
+                .collect();
+            let widths = columns
+                .iter()
+                .enumerate()
+                .map(|(index, column)| {
+                    rows.iter()
+                        .map(|row| row[index].chars().count())
+                        .max()
+                        .unwrap_or(0)
+                        .max(column.len())
+                        .min(MAX_COLUMN_WIDTH)
+                })
+                .collect();
+            let header = columns.iter().map(|column| column.to_uppercase()).collect();
+            (header, rows, widths)
+        };
+
+    let widths = widths.into_iter().map(|width| match width {
+        usize::MAX => Constraint::Min(0),
+        width => Constraint::Length(width as u16),
+    });
+    let table = Table::new(
+        rows.into_iter()
+            .map(|row| Row::new(row.into_iter().map(Cell::from))),
+        widths,
+    )
+    .header(Row::new(header).style(Style::new().add_modifier(Modifier::BOLD)))
+    .block(block)
+    .row_highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+
+    frame.render_stateful_widget(table, area, &mut panel.state);
+}
@@ -0,0 +1,289 @@
+use crate::output::OutputFormat;
+use anyhow::{bail, Context, Result};
+use clap::Subcommand;
+use futures::{pin_mut, Stream, StreamExt};
+use rustsdk::{AccountEvent, AccountWatcher, Address, KaiaScan, LatestBlock, MemoryCursorStore};
+use serde::Serialize;
+use std::io::{self, ErrorKind, Write};
+use std::time::Duration;
+
+#[derive(Debug, Subcommand)]
+pub enum WatchCommand {
+    /// Print new blocks as they are produced
+    Blocks,
+    /// Print token receipts, NFT transfers, event logs and key updates of addresses
+    Address {
+        #[arg(required = true)]
+        addresses: Vec<String>,
+        /// Seconds between polls
+        #[arg(long, default_value_t = 5)]
+        interval: u64,
+    },
+}
+
+pub async fn run(client: &KaiaScan, command: WatchCommand, format: OutputFormat) -> Result<()> {
+    if format == OutputFormat::Csv {
+        bail!("watch supports json (newline-delimited) or table output");
+    }
+
+    let result = match command {
+        WatchCommand::Blocks => {
+            let header = format!(
+                "{:<12}  {:<25}  {:>4}  {}",
+                "BLOCK", "DATETIME", "TXS", "HASH"
+            );
+            print_header(format, &header)?;
+            tail(client.watch_blocks(), format, block_line).await
+        }
+        WatchCommand::Address {
+            addresses,
+            interval,
+        } => {
+            let watcher = address_watcher(client, addresses)
+                .await?
+                .with_poll_interval(Duration::from_secs(interval.max(1)));
+
+            let header = format!(
+                "{:<9}  {:<42}  {:>10}  {}",
+                "EVENT", "ADDRESS", "BLOCK", "DETAIL"
+            );
+            print_header(format, &header)?;
+            tail(watcher.into_stream(), format, event_line).await
+        }
+    };
+
+    // A closed pipe (e.g. `| head`) ends the watch like Ctrl-C does.
+    match result {
+        Err(err)
+            if err
+                .downcast_ref::<io::Error>()
+                .is_some_and(|err| err.kind() == ErrorKind::BrokenPipe) =>
+        {
+            Ok(())
+        }
+        result => result,
+    }
+}
+
+// Cursors are kept in memory only, so every run starts at the current head block instead
+// of printing each address's past events as new ones.
+async fn address_watcher(
+    client: &KaiaScan,
+    addresses: Vec<String>,
+) -> Result<AccountWatcher<'_, MemoryCursorStore>> {
+    let head = client
+        .get_latest_block()
+        .await
+        .context("Failed to fetch the latest block")?;
+    let head = u64::try_from(head.block_id).context("Invalid latest block number")?;
+
+    Ok(client
+        .watch_accounts(
+            addresses.into_iter().map(Address::new),
+            MemoryCursorStore::default(),
+        )
+        .starting_at_block(head))
+}
+
+fn print_header(format: OutputFormat, header: &str) -> Result<()> {
+    if format == OutputFormat::Table {
+        writeln!(io::stdout(), "{}", header)?;
+    }
+    Ok(())
+}
+
+// Prints items until Ctrl-C. Poll errors are reported and the watch keeps going, since the
+// library streams retry on their next poll.
+async fn tail<T: Serialize>(
+    stream: impl Stream<Item = Result<T>>,
+    format: OutputFormat,
+    line: fn(&T) -> String,
+) -> Result<()> {
+    pin_mut!(stream);
+    let ctrl_c = tokio::signal::ctrl_c();
+    pin_mut!(ctrl_c);
+
+    loop {
+        let item = tokio::select! {
+            _ = &mut ctrl_c => return Ok(()),
+            item = stream.next() => item,
+        };
+
+        match item {
+            Some(Ok(item)) => {
+                let mut out = io::stdout().lock();
+                match format {
+                    OutputFormat::Table => writeln!(out, "{}", line(&item))?,
+                    _ => writeln!(out, "{}", serde_json::to_string(&item)?)?,
+                }
+                out.flush()?;
+            }
+            Some(Err(err)) => eprintln!("Error: {:#}", err),
+            None => return Ok(()),
+        }
+    }
+}
+
+fn block_line(block: &LatestBlock) -> String {
+    format!(
+        "{:<12}  {:<25}  {:>4}  {}",
+        block.block_id, block.datetime, block.total_transaction_count, block.hash
+    )
+}
+
+fn event_line(event: &AccountEvent) -> String {
+    let (kind, address, block, detail) = match event {
+        AccountEvent::TokenReceived {
+            address,
+            contract,
+            amount,
+            balance,
+        } => (
+            "token",
+            address,
+            String::new(),
+            format!(
+                "+{} {} (balance {})",
+                amount, contract.contract_address, balance
+            ),
+        ),
+        AccountEvent::NftTransferred { address, transfer } => (
+            "nft",
+            address,
+            transfer.block_id.to_string(),
+            format!(
+                "{} #{} x{} {} -> {} {}",
+                transfer.contract.contract_address,
+                transfer.token_id,
+                transfer.token_count,
+                transfer.from,
+                transfer.to,
+                transfer.transaction_hash
+            ),
+        ),
+        AccountEvent::EventLogged { address, log } => (
+            "log",
+            address,
+            log.block_number.to_string(),
+            format!(
+                "{} {} {}",
+                log.contract_address,
+                log.topics.first().map(String::as_str).unwrap_or("-"),
+                log.transaction_hash
+            ),
+        ),
+        AccountEvent::KeyUpdated { address, key } => (
+            "key",
+            address,
+            String::new(),
+            format!("{} {} {}", key.key_type, key.public_key, key.created_at),
+        ),
+    };
+
+    format!(
+        "{:<9}  {:<42}  {:>10}  {}",
+        kind,
+        address.as_ref(),
+        block,
+        detail
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::Matcher;
+    use serde_json::json;
+
+    fn paging() -> serde_json::Value {
+        json!({ "current_page": 1, "last": true, "total_count": 0, "total_page": 1 })
+    }
+
+    async fn mock(
+        server: &mut mockito::Server,
+        path: &str,
+        body: serde_json::Value,
+    ) -> mockito::Mock {
+        server
+            .mock("GET", Matcher::Regex(path.to_string()))
+            .with_body(body.to_string())
+            .create_async()
+            .await
+    }
+
+    #[tokio::test]
+    async fn address_watch_starts_at_the_head_block() {
+        let mut server = mockito::Server::new_async().await;
+        let client = KaiaScan::new(false).unwrap().with_base_url(server.url());
+
+        let latest = json!({
+            "block_id": 500,
+            "datetime": "2024-01-01T00:00:00Z",
+            "hash": "0xhead",
+            "total_transaction_count": 0,
+            "block_reward": { "minted": "0", "total_fee": "0", "burnt_fee": "0" },
+        });
+        mock(&mut server, r"^/api/v1/blocks/latest$", latest).await;
+
+        let transfer = json!({
+            "contract": { "contract_address": "0xc", "contract_type": "kip17" },
+            "blockId": 500,
+            "transactionHash": "0xnew",
+            "feePayer": "",
+            "transactionIndex": 0,
+            "datetime": "2024-01-01T00:00:00Z",
+            "from": "0xf",
+            "to": "0xw",
+            "tokenId": "1",
+            "tokenCount": 1,
+        });
+        let transfers = mock(
+            &mut server,
+            r"^/api/v1/accounts/0xw/nft-transfers\?.*blockNumberStart=500",
+            json!({ "results": [transfer], "paging": paging(), "property": null }),
+        )
+        .await;
+        let logs = mock(
+            &mut server,
+            r"^/api/v1/accounts/0xw/event-logs\?.*blockNumberStart=500",
+            json!({
+                "results": [],
+                "pagings": { "total_count": 0, "current_page": 1, "last": true, "total_page": 1 },
+                "property": {},
+            }),
+        )
+        .await;
+        mock(
+            &mut server,
+            r"^/api/v1/accounts/0xw/token-balances",
+            json!({
+                "results": [],
+                "paging": { "totalCount": 0, "currentPage": 1, "last": true, "totalPage": 1 },
+                "property": null,
+            }),
+        )
+        .await;
+        mock(
+            &mut server,
+            r"^/api/v1/accounts/0xw/key-histories",
+            json!({ "paging": paging(), "results": [] }),
+        )
+        .await;
+
+        let watcher = address_watcher(&client, vec!["0xw".to_string()])
+            .await
+            .unwrap();
+        let stream = watcher.into_stream();
+        pin_mut!(stream);
+        let event = stream.next().await.unwrap().unwrap();
+
+        match event {
+            AccountEvent::NftTransferred { transfer, .. } => {
+                assert_eq!(transfer.transaction_hash, "0xnew")
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+        transfers.assert_async().await;
+        logs.assert_async().await;
+    }
+}
@@ -0,0 +1,238 @@
+use crate::{BlockListItem, BlocksListResponse, KaiaScan, Transaction};
+use anyhow::Result;
+use futures::stream::{self, Stream, StreamExt};
+use std::ops::RangeInclusive;
+
+const BLOCKS_MAX_PAGE_SIZE: i64 = 2000;
+const TRANSACTIONS_MAX_PAGE_SIZE: i32 = 2000;
+
+impl KaiaScan {
+    async fn get_blocks_between(
+        &self,
+        block_number_start: i64,
+        block_number_end: i64,
+        page: i32,
+    ) -> Result<BlocksListResponse> {
+        let url = format!(
+            "api/v1/blocks?blockNumberStart={}&blockNumberEnd={}&page={}&size={}",
+            block_number_start, block_number_end, page, BLOCKS_MAX_PAGE_SIZE
+        );
+        self.fetch_api(&url).await
+    }
+
+    // Fetches every page of one chunk and returns its blocks in ascending order.
+    async fn get_block_chunk(&self, chunk: RangeInclusive<i64>) -> Result<Vec<BlockListItem>> {
+        let mut blocks = Vec::new();
+        let mut page = 1;
+
+        loop {
+            let response = self
+                .get_blocks_between(*chunk.start(), *chunk.end(), page)
+                .await?;
+            blocks.extend(response.results);
+
+            if response.paging.last || page as i64 >= response.paging.total_page {
+                break;
+            }
+            page += 1;
+        }
+
+        blocks.retain(|block| chunk.contains(&block.block_id));
+        blocks.sort_by_key(|block| block.block_id);
+        blocks.dedup_by_key(|block| block.block_id);
+        Ok(blocks)
+    }
+
+    // Every transaction of a block, across all pages.
+    pub async fn block_transactions(&self, block_number: i64) -> Result<Vec<Transaction>> {
+        let mut transactions = Vec::new();
+        let mut page = 1;
+
+        loop {
+            let response = self
+                .get_transactions_of_block_page(
+                    block_number,
+                    Some(page),
+                    Some(TRANSACTIONS_MAX_PAGE_SIZE),
+                )
+                .await?;
+            transactions.extend(response.results);
+
+            if response.paging.last || page as i64 >= response.paging.total_page {
+                return Ok(transactions);
+            }
+            page += 1;
+        }
+    }
+
+    // Streams every block in `range` in ascending order. The range is split into chunks that
+    // fit in a single page and up to `batch_concurrency` chunks are fetched at once.
+    pub fn blocks_in_range(
+        &self,
+        range: RangeInclusive<i64>,
+    ) -> impl Stream<Item = Result<BlockListItem>> + '_ {
+        let chunks = block_chunks(range);
+
+        stream::iter(chunks)
+            .map(move |chunk| self.get_block_chunk(chunk))
+            .buffered(self.batch_concurrency)
+            .flat_map(|chunk| {
+                let items: Vec<Result<BlockListItem>> = match chunk {
+                    Ok(blocks) => blocks.into_iter().map(Ok).collect(),
+                    Err(err) => vec![Err(err)],
+                };
+                stream::iter(items)
+            })
+    }
+
+    // Continues a walk over `range` after an interruption. `checkpoint` is the last block
+    // that was fully processed, so streaming restarts at `checkpoint + 1`.
+    pub fn resume_blocks_in_range(
+        &self,
+        range: RangeInclusive<i64>,
+        checkpoint: i64,
+    ) -> impl Stream<Item = Result<BlockListItem>> + '_ {
+        let start = (*range.start()).max(checkpoint.saturating_add(1));
+        self.blocks_in_range(start..=*range.end())
+    }
+}
+
+fn block_chunks(range: RangeInclusive<i64>) -> Vec<RangeInclusive<i64>> {
+    let (start, end) = (*range.start(), *range.end());
+    let mut chunks = Vec::new();
+    let mut chunk_start = start;
+
+    while chunk_start <= end {
+        let chunk_end = chunk_start
+            .saturating_add(BLOCKS_MAX_PAGE_SIZE - 1)
+            .min(end);
+        chunks.push(chunk_start..=chunk_end);
+
+        if chunk_end == i64::MAX {
+            break;
+        }
+        chunk_start = chunk_end + 1;
+    }
+
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::TryStreamExt;
+    use serde_json::json;
+
+    fn block(block_id: i64) -> serde_json::Value {
+        json!({
+            "base_fee_per_gas": "25000000000",
+            "block_id": block_id,
+            "block_proposer": "0x0000000000000000000000000000000000000001",
+            "block_size": 1024,
+            "burnt_fees": "0",
+            "datetime": "2024-01-01T00:00:00Z",
+            "reward": "0",
+            "total_transaction_count": 0,
+        })
+    }
+
+    fn page(blocks: &[i64], current_page: i64, total_page: i64) -> String {
+        json!({
+            "paging": {
+                "current_page": current_page,
+                "last": current_page == total_page,
+                "total_count": blocks.len(),
+                "total_page": total_page,
+            },
+            "results": blocks.iter().map(|&id| block(id)).collect::<Vec<_>>(),
+        })
+        .to_string()
+    }
+
+    async fn mock_chunk(
+        server: &mut mockito::ServerGuard,
+        chunk: RangeInclusive<i64>,
+        pages: &[&[i64]],
+    ) -> Vec<mockito::Mock> {
+        let mut mocks = Vec::new();
+        for (index, blocks) in pages.iter().enumerate() {
+            let path = format!(
+                "/api/v1/blocks?blockNumberStart={}&blockNumberEnd={}&page={}&size=2000",
+                chunk.start(),
+                chunk.end(),
+                index + 1
+            );
+            let body = page(blocks, index as i64 + 1, pages.len() as i64);
+            mocks.push(
+                server
+                    .mock("GET", path.as_str())
+                    .with_body(body)
+                    .create_async()
+                    .await,
+            );
+        }
+        mocks
+    }
+
+    fn block_ids(blocks: Vec<BlockListItem>) -> Vec<i64> {
+        blocks.into_iter().map(|block| block.block_id).collect()
+    }
+
+    #[test]
+    fn splits_ranges_into_page_sized_chunks() {
+        assert_eq!(block_chunks(1..=2000), vec![1..=2000]);
+        assert_eq!(
+            block_chunks(1..=4500),
+            vec![1..=2000, 2001..=4000, 4001..=4500]
+        );
+        assert_eq!(block_chunks(10..=10), vec![10..=10]);
+        assert!(block_chunks(RangeInclusive::new(10, 9)).is_empty());
+        assert_eq!(
+            block_chunks(i64::MAX - 1..=i64::MAX),
+            vec![i64::MAX - 1..=i64::MAX]
+        );
+    }
+
+    #[tokio::test]
+    async fn streams_chunks_in_order_without_duplicates() {
+        let mut server = mockito::Server::new_async().await;
+        // Responses overlap at the chunk edges and arrive out of order.
+        mock_chunk(&mut server, 1..=2000, &[&[2000, 1, 2001]]).await;
+        mock_chunk(&mut server, 2001..=4000, &[&[4000, 2001, 2001]]).await;
+        mock_chunk(&mut server, 4001..=4500, &[&[4001, 4000], &[4500, 4501]]).await;
+        let client = KaiaScan::new(false)
+            .unwrap()
+            .with_base_url(server.url())
+            .with_batch_concurrency(3);
+
+        let blocks: Vec<BlockListItem> = client
+            .blocks_in_range(1..=4500)
+            .try_collect()
+            .await
+            .unwrap();
+
+        assert_eq!(block_ids(blocks), vec![1, 2000, 2001, 4000, 4001, 4500]);
+    }
+
+    #[tokio::test]
+    async fn resumes_after_the_checkpoint() {
+        let mut server = mockito::Server::new_async().await;
+        let skipped = server
+            .mock("GET", mockito::Matcher::Regex("blockNumberStart=1&".into()))
+            .expect(0)
+            .create_async()
+            .await;
+        mock_chunk(&mut server, 2001..=4000, &[&[2001, 4000]]).await;
+        mock_chunk(&mut server, 4001..=4500, &[&[4500]]).await;
+        let client = KaiaScan::new(false).unwrap().with_base_url(server.url());
+
+        let blocks: Vec<BlockListItem> = client
+            .resume_blocks_in_range(1..=4500, 2000)
+            .try_collect()
+            .await
+            .unwrap();
+
+        assert_eq!(block_ids(blocks), vec![2001, 4000, 4500]);
+        skipped.assert_async().await;
+    }
+}
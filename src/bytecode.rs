@@ -0,0 +1,321 @@
+use crate::abi::{self, decode_hex, Abi, AbiType, AbiValue};
+use crate::{Address, KaiaScan};
+use anyhow::{anyhow, bail, Context, Result};
+use serde_json::Value;
+use std::ops::Range;
+
+#[derive(Debug, Clone)]
+pub struct BuildArtifact {
+    pub creation_code: Vec<u8>,
+    pub abi: Option<Abi>,
+}
+
+impl BuildArtifact {
+    // Reads a Hardhat or Foundry artifact, or solc standard-JSON / combined-JSON output.
+    // `contract_name` selects the contract when the output holds several.
+    pub fn from_json(json: &str, contract_name: Option<&str>) -> Result<Self> {
+        let document: Value =
+            serde_json::from_str(json).context("Failed to parse artifact JSON")?;
+        let contract = select_contract(&document, contract_name)?;
+
+        let bytecode = contract
+            .get("bytecode")
+            .or_else(|| contract.pointer("/evm/bytecode"))
+            .or_else(|| contract.get("bin"))
+            .ok_or_else(|| anyhow!("Artifact has no creation bytecode"))?;
+        let bytecode = match bytecode {
+            Value::String(code) => code.as_str(),
+            Value::Object(fields) => fields
+                .get("object")
+                .and_then(Value::as_str)
+                .ok_or_else(|| anyhow!("Artifact bytecode has no `object` field"))?,
+            _ => bail!("Artifact bytecode is not a string"),
+        };
+
+        if bytecode.contains("__") {
+            bail!("Artifact bytecode has unlinked library placeholders");
+        }
+
+        let abi = match contract.get("abi") {
+            Some(Value::String(abi)) => Some(Abi::from_json(abi)?),
+            Some(abi @ Value::Array(_)) => Some(Abi::from_json(&abi.to_string())?),
+            _ => None,
+        };
+
+        Ok(Self {
+            creation_code: decode_hex(bytecode)?,
+            abi,
+        })
+    }
+}
+
+fn select_contract<'a>(document: &'a Value, contract_name: Option<&str>) -> Result<&'a Value> {
+    let Some(contracts) = document.get("contracts").and_then(Value::as_object) else {
+        return Ok(document);
+    };
+
+    // Standard JSON nests contracts per file; combined JSON keys them as `file:Name`.
+    let mut candidates: Vec<(String, &Value)> = Vec::new();
+    for (key, value) in contracts {
+        match value.as_object() {
+            Some(fields) if !fields.contains_key("bin") && !fields.contains_key("evm") => {
+                for (name, contract) in fields {
+                    candidates.push((name.clone(), contract));
+                }
+            }
+            _ => {
+                let name = key.rsplit(':').next().unwrap_or(key).to_string();
+                candidates.push((name, value));
+            }
+        }
+    }
+
+    match contract_name {
+        Some(contract_name) => candidates
+            .into_iter()
+            .find(|(name, _)| name == contract_name)
+            .map(|(_, contract)| contract)
+            .ok_or_else(|| anyhow!("Contract {} not found in artifact", contract_name)),
+        None if candidates.len() == 1 => Ok(candidates[0].1),
+        None => bail!("Artifact holds several contracts; pass a contract name"),
+    }
+}
+
+// The explorer returns creation code either as a bare string or inside an object.
+pub(crate) fn creation_code_hex(value: &Value) -> Option<String> {
+    match value {
+        Value::String(code) => Some(code.clone()),
+        Value::Object(fields) => ["creation_code", "creationCode", "bytecode", "code"]
+            .iter()
+            .find_map(|key| fields.get(*key))
+            .and_then(creation_code_hex),
+        _ => None,
+    }
+}
+
+// Returns the range of the trailing CBOR metadata solc appends to bytecode. The last two
+// bytes hold the big-endian length of the CBOR map that precedes them.
+pub fn metadata_range(code: &[u8]) -> Option<Range<usize>> {
+    let length_at = code.len().checked_sub(2)?;
+    let length = u16::from_be_bytes([code[length_at], code[length_at + 1]]) as usize;
+    let start = length_at.checked_sub(length)?;
+
+    // CBOR maps start with major type 5.
+    if length == 0 || code[start] & 0xe0 != 0xa0 {
+        return None;
+    }
+
+    Some(start..code.len())
+}
+
+pub fn strip_metadata(code: &[u8]) -> &[u8] {
+    match metadata_range(code) {
+        Some(range) => &code[..range.start],
+        None => code,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BytecodeMatch {
+    Exact,
+    // Only the metadata hash differs, e.g. different source paths or comments.
+    MetadataOnly,
+    // Byte ranges refer to offsets in the creation code, excluding constructor arguments.
+    Mismatch {
+        ranges: Vec<Range<usize>>,
+        local_length: usize,
+        onchain_length: usize,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct BytecodeComparison {
+    pub result: BytecodeMatch,
+    pub constructor_arguments: Vec<u8>,
+    // Present when the artifact ABI declares a constructor and the arguments decode.
+    pub decoded_constructor_arguments: Option<Vec<(String, AbiValue)>>,
+}
+
+fn differing_ranges(local: &[u8], onchain: &[u8]) -> Vec<Range<usize>> {
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+    let length = local.len().max(onchain.len());
+
+    for offset in 0..length {
+        if local.get(offset) == onchain.get(offset) {
+            continue;
+        }
+        match ranges.last_mut() {
+            Some(range) if range.end == offset => range.end = offset + 1,
+            _ => ranges.push(offset..offset + 1),
+        }
+    }
+
+    ranges
+}
+
+// Compares on-chain creation code with a local build. The on-chain code is the compiled
+// creation code followed by the ABI-encoded constructor arguments.
+pub fn compare_creation_code(onchain: &[u8], artifact: &BuildArtifact) -> BytecodeComparison {
+    let local = artifact.creation_code.as_slice();
+    let code_length = local.len().min(onchain.len());
+    let (onchain_code, constructor_arguments) = onchain.split_at(code_length);
+
+    let result = if onchain_code == local {
+        BytecodeMatch::Exact
+    } else {
+        let ranges = differing_ranges(local, onchain_code);
+        let metadata = metadata_range(local);
+        let metadata_only = onchain_code.len() == local.len()
+            && metadata.is_some_and(|metadata| {
+                ranges
+                    .iter()
+                    .all(|range| range.start >= metadata.start && range.end <= metadata.end)
+            });
+
+        if metadata_only {
+            BytecodeMatch::MetadataOnly
+        } else {
+            BytecodeMatch::Mismatch {
+                ranges,
+                local_length: local.len(),
+                onchain_length: onchain.len(),
+            }
+        }
+    };
+
+    let decoded_constructor_arguments = artifact
+        .abi
+        .as_ref()
+        .and_then(|abi| abi.constructor.as_ref())
+        .and_then(|inputs| {
+            let types: Vec<AbiType> = inputs.iter().map(|input| input.kind.clone()).collect();
+            let values = abi::decode(&types, constructor_arguments).ok()?;
+            Some(
+                inputs
+                    .iter()
+                    .map(|input| input.name.clone())
+                    .zip(values)
+                    .collect(),
+            )
+        });
+
+    BytecodeComparison {
+        result,
+        constructor_arguments: constructor_arguments.to_vec(),
+        decoded_constructor_arguments,
+    }
+}
+
+impl KaiaScan {
+    pub async fn verify_creation_code(
+        &self,
+        contract_address: Address,
+        artifact: &BuildArtifact,
+    ) -> Result<BytecodeComparison> {
+        let response = self
+            .get_contract_creation_code(contract_address.clone())
+            .await?;
+        let code = creation_code_hex(&response).ok_or_else(|| {
+            anyhow!(
+                "No creation code returned for {}",
+                contract_address.as_ref()
+            )
+        })?;
+
+        Ok(compare_creation_code(&decode_hex(&code)?, artifact))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abi::encode_hex;
+    use primitive_types::U256;
+
+    // Runtime body, a 3-byte CBOR map and its big-endian length.
+    fn code_with_metadata(hash: u8) -> Vec<u8> {
+        vec![0x60, 0x80, 0x60, 0x40, 0xa1, 0x01, hash, 0x00, 0x03]
+    }
+
+    fn artifact(creation_code: Vec<u8>) -> BuildArtifact {
+        BuildArtifact {
+            creation_code,
+            abi: Some(Abi::from_human_readable(["constructor(uint256 supply)"]).unwrap()),
+        }
+    }
+
+    #[test]
+    fn strips_trailing_cbor_metadata() {
+        let code = code_with_metadata(0x02);
+        assert_eq!(metadata_range(&code), Some(4..9));
+        assert_eq!(strip_metadata(&code), &[0x60, 0x80, 0x60, 0x40]);
+
+        // The length points at a byte that does not start a CBOR map.
+        let code = [0x60, 0x80, 0x60, 0x40, 0x00, 0x03];
+        assert_eq!(metadata_range(&code), None);
+        assert_eq!(strip_metadata(&code), &code);
+        assert_eq!(metadata_range(&[0x00]), None);
+        assert_eq!(metadata_range(&[0x00, 0xff]), None);
+    }
+
+    #[test]
+    fn compares_creation_code_and_decodes_constructor_arguments() {
+        let mut onchain = code_with_metadata(0x02);
+        let mut supply = [0u8; 32];
+        supply[31] = 100;
+        onchain.extend(supply);
+
+        let exact = compare_creation_code(&onchain, &artifact(code_with_metadata(0x02)));
+        assert_eq!(exact.result, BytecodeMatch::Exact);
+        assert_eq!(exact.constructor_arguments, supply);
+        assert_eq!(
+            exact.decoded_constructor_arguments,
+            Some(vec![(
+                "supply".to_string(),
+                AbiValue::Uint(U256::from(100))
+            )])
+        );
+
+        let metadata = compare_creation_code(&onchain, &artifact(code_with_metadata(0x03)));
+        assert_eq!(metadata.result, BytecodeMatch::MetadataOnly);
+
+        let mut changed = code_with_metadata(0x02);
+        changed[1] = 0x81;
+        changed[3] = 0x41;
+        let mismatch = compare_creation_code(&onchain, &artifact(changed));
+        assert_eq!(
+            mismatch.result,
+            BytecodeMatch::Mismatch {
+                ranges: vec![1..2, 3..4],
+                local_length: 9,
+                onchain_length: 41,
+            }
+        );
+    }
+
+    #[test]
+    fn reads_build_artifacts() {
+        let code = encode_hex(&code_with_metadata(0x02));
+
+        let hardhat = format!(
+            r#"{{"contractName": "Token", "abi": [], "bytecode": "{}"}}"#,
+            code
+        );
+        let parsed = BuildArtifact::from_json(&hardhat, None).unwrap();
+        assert_eq!(parsed.creation_code, code_with_metadata(0x02));
+
+        let standard_json = format!(
+            r#"{{"contracts": {{"Token.sol": {{
+                "Token": {{"abi": [], "evm": {{"bytecode": {{"object": "{}"}}}}}},
+                "Lib": {{"abi": [], "evm": {{"bytecode": {{"object": "0x00"}}}}}}
+            }}}}}}"#,
+            code.trim_start_matches("0x")
+        );
+        assert!(BuildArtifact::from_json(&standard_json, None).is_err());
+        let token = BuildArtifact::from_json(&standard_json, Some("Token")).unwrap();
+        assert_eq!(token.creation_code, code_with_metadata(0x02));
+
+        let unlinked = r#"{"bytecode": "0x6080__$abc$__"}"#;
+        assert!(BuildArtifact::from_json(unlinked, None).is_err());
+    }
+}
@@ -0,0 +1,261 @@
+use crate::abi::{self, decode_hex, encode_hex, Abi, AbiFunction, AbiValue};
+use crate::standards;
+use crate::{KaiaScan, TransactionInputData};
+use anyhow::{anyhow, bail, Result};
+use std::collections::HashMap;
+
+// Common method signatures used when no ABI is available for the called contract, on top
+// of the KIP-7/17/37 methods.
+const BUILTIN_SIGNATURES: &[&str] = &[
+    "increaseAllowance(address,uint256)",
+    "decreaseAllowance(address,uint256)",
+    "mint(address,uint256)",
+    "burn(uint256)",
+    "burnFrom(address,uint256)",
+    "deposit()",
+    "withdraw(uint256)",
+    "multicall(bytes[])",
+    "multicall(uint256,bytes[])",
+    "execute(bytes,bytes[],uint256)",
+    "swapExactTokensForTokens(uint256,uint256,address[],address,uint256)",
+    "swapTokensForExactTokens(uint256,uint256,address[],address,uint256)",
+    "swapExactETHForTokens(uint256,address[],address,uint256)",
+    "swapExactTokensForETH(uint256,uint256,address[],address,uint256)",
+    "swapExactKLAYForTokens(uint256,address[],address,uint256)",
+    "swapExactTokensForKLAY(uint256,uint256,address[],address,uint256)",
+    "addLiquidity(address,address,uint256,uint256,uint256,uint256,address,uint256)",
+    "removeLiquidity(address,address,uint256,uint256,uint256,address,uint256)",
+    "stake(uint256)",
+    "unstake(uint256)",
+    "claim()",
+    "harvest(uint256)",
+    "upgradeTo(address)",
+    "upgradeToAndCall(address,bytes)",
+    "transferOwnership(address)",
+    "renounceOwnership()",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodedCall {
+    pub name: String,
+    pub signature: String,
+    pub params: Vec<(String, AbiValue)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CalldataDecoding {
+    pub selector: [u8; 4],
+    // Every known signature with this selector whose parameters decode from the calldata.
+    // More than one entry means the selector collides.
+    pub candidates: Vec<DecodedCall>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SelectorDatabase {
+    functions: HashMap<[u8; 4], Vec<AbiFunction>>,
+}
+
+impl SelectorDatabase {
+    pub fn builtin() -> Self {
+        let mut database = Self::default();
+        for standard in standards::standard_methods() {
+            database.register(standard.abi.clone());
+        }
+        for signature in BUILTIN_SIGNATURES {
+            database
+                .register_signature(signature)
+                .expect("built-in signatures are valid");
+        }
+        database
+    }
+
+    pub fn register(&mut self, function: AbiFunction) {
+        let candidates = self.functions.entry(function.selector()).or_default();
+        if !candidates
+            .iter()
+            .any(|known| known.signature() == function.signature())
+        {
+            candidates.push(function);
+        }
+    }
+
+    pub fn register_abi(&mut self, abi: &Abi) {
+        for function in &abi.functions {
+            self.register(function.clone());
+        }
+    }
+
+    pub fn register_signature(&mut self, signature: &str) -> Result<()> {
+        self.register(AbiFunction::parse(signature)?);
+        Ok(())
+    }
+
+    pub fn get(&self, selector: &[u8; 4]) -> &[AbiFunction] {
+        self.functions
+            .get(selector)
+            .map(Vec::as_slice)
+            .unwrap_or(&[])
+    }
+
+    pub fn decode(&self, calldata: &[u8]) -> Result<CalldataDecoding> {
+        let selector = selector_of(calldata)?;
+        let functions = self.get(&selector);
+
+        if functions.is_empty() {
+            bail!("Unknown function selector {}", encode_hex(&selector));
+        }
+
+        let candidates: Vec<DecodedCall> = functions
+            .iter()
+            .filter_map(|function| decode_call(function, calldata).ok())
+            .collect();
+
+        if candidates.is_empty() {
+            let signatures: Vec<String> = functions.iter().map(AbiFunction::signature).collect();
+            bail!(
+                "Calldata for selector {} does not decode as any of: {}",
+                encode_hex(&selector),
+                signatures.join(", ")
+            );
+        }
+
+        Ok(CalldataDecoding {
+            selector,
+            candidates,
+        })
+    }
+}
+
+fn selector_of(calldata: &[u8]) -> Result<[u8; 4]> {
+    calldata
+        .get(..4)
+        .map(|selector| [selector[0], selector[1], selector[2], selector[3]])
+        .ok_or_else(|| anyhow!("Calldata is shorter than a 4-byte selector"))
+}
+
+// Decodes calldata against a single function, checking that the selector matches.
+pub fn decode_call(function: &AbiFunction, calldata: &[u8]) -> Result<DecodedCall> {
+    let selector = selector_of(calldata)?;
+    if selector != function.selector() {
+        bail!(
+            "Selector {} does not match {}",
+            encode_hex(&selector),
+            function.signature()
+        );
+    }
+
+    let types: Vec<_> = function
+        .inputs
+        .iter()
+        .map(|input| input.kind.clone())
+        .collect();
+    let values = abi::decode(&types, &calldata[4..])?;
+
+    Ok(DecodedCall {
+        name: function.name.clone(),
+        signature: function.signature(),
+        params: function
+            .inputs
+            .iter()
+            .map(|input| input.name.clone())
+            .zip(values)
+            .collect(),
+    })
+}
+
+impl TransactionInputData {
+    pub fn calldata(&self) -> Result<Vec<u8>> {
+        decode_hex(&self.original_value)
+    }
+
+    pub fn decode_with_abi(&self, abi: &Abi) -> Result<CalldataDecoding> {
+        let mut database = SelectorDatabase::default();
+        database.register_abi(abi);
+        database.decode(&self.calldata()?)
+    }
+
+    pub fn decode_with_selectors(&self, database: &SelectorDatabase) -> Result<CalldataDecoding> {
+        database.decode(&self.calldata()?)
+    }
+}
+
+impl KaiaScan {
+    // Decodes input data with the ABIs registered on the client and the built-in selectors.
+    pub fn decode_input_data(&self, input: &TransactionInputData) -> Result<CalldataDecoding> {
+        input.decode_with_selectors(&self.selectors.read().unwrap())
+    }
+
+    pub fn register_function_signature(&self, signature: &str) -> Result<()> {
+        self.selectors
+            .write()
+            .unwrap()
+            .register_signature(signature)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use primitive_types::U256;
+
+    // transfer(0x1111…11, 1000)
+    fn transfer_calldata() -> Vec<u8> {
+        let mut calldata = decode_hex("0xa9059cbb").unwrap();
+        calldata.extend([0u8; 12]);
+        calldata.extend([0x11u8; 20]);
+        let mut amount = [0u8; 32];
+        amount[30..].copy_from_slice(&1000u16.to_be_bytes());
+        calldata.extend(amount);
+        calldata
+    }
+
+    #[test]
+    fn decodes_builtin_selectors() {
+        let decoding = SelectorDatabase::builtin()
+            .decode(&transfer_calldata())
+            .unwrap();
+
+        assert_eq!(encode_hex(&decoding.selector), "0xa9059cbb");
+        assert_eq!(decoding.candidates.len(), 1);
+        let call = &decoding.candidates[0];
+        assert_eq!(call.signature, "transfer(address,uint256)");
+        assert_eq!(
+            call.params[0].1,
+            AbiValue::Address(format!("0x{}", "11".repeat(20)))
+        );
+        assert_eq!(call.params[1].1, AbiValue::Uint(U256::from(1000)));
+    }
+
+    #[test]
+    fn decodes_with_registered_abi() {
+        let input = TransactionInputData {
+            original_value: encode_hex(&transfer_calldata()),
+            decoded_value: None,
+            utf8_value: None,
+        };
+        let abi =
+            Abi::from_human_readable(["function transfer(address recipient, uint256 amount)"])
+                .unwrap();
+
+        let decoding = input.decode_with_abi(&abi).unwrap();
+        assert_eq!(decoding.candidates[0].params[0].0, "recipient");
+        assert_eq!(decoding.candidates[0].params[1].0, "amount");
+    }
+
+    #[test]
+    fn rejects_unknown_short_and_malformed_calldata() {
+        let database = SelectorDatabase::builtin();
+        assert!(database.decode(&[0xde, 0xad]).is_err());
+        assert!(database.decode(&[0xde, 0xad, 0xbe, 0xef]).is_err());
+
+        // Known selector, but the arguments are cut short.
+        let calldata = transfer_calldata();
+        assert!(database.decode(&calldata[..40]).is_err());
+    }
+
+    #[test]
+    fn checks_the_selector_against_the_function() {
+        let approve = AbiFunction::parse("approve(address,uint256)").unwrap();
+        assert!(decode_call(&approve, &transfer_calldata()).is_err());
+    }
+}
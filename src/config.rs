@@ -0,0 +1,290 @@
+use crate::KaiaScan;
+use anyhow::{anyhow, bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+const CONFIG_PATH_ENV: &str = "KAIASCAN_CONFIG";
+
+// Contents of `~/.config/kaiascan/config.toml`:
+//
+//     active = "kairos"
+//
+//     [profiles.kairos]
+//     base_url = "https://kairos-oapi.kaiascan.io/"
+//     api_key_env = "KAIROS_API_KEY"
+//     timeout_secs = 30
+//     rate_limit = 5
+//     output = "table"
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Config {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub active: Option<String>,
+    #[serde(default)]
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Profile {
+    // Mainnet when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<String>,
+    // Name of an environment variable holding the API key, so the key stays out of the file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key_env: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+    // Requests per second.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rate_limit: Option<u32>,
+    // Default output format of the CLI.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<String>,
+}
+
+impl Config {
+    // `$KAIASCAN_CONFIG`, else `$XDG_CONFIG_HOME/kaiascan/config.toml`, else
+    // `~/.config/kaiascan/config.toml`.
+    pub fn default_path() -> Option<PathBuf> {
+        if let Some(path) = env::var_os(CONFIG_PATH_ENV).filter(|path| !path.is_empty()) {
+            return Some(PathBuf::from(path));
+        }
+
+        let config_dir = env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+
+        Some(config_dir.join("kaiascan").join("config.toml"))
+    }
+
+    // A missing file yields an empty config.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        toml::from_str(&text).with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    pub fn load_default() -> Result<Self> {
+        match Self::default_path() {
+            Some(path) => Self::load(path),
+            None => Ok(Self::default()),
+        }
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+
+        let text = toml::to_string_pretty(self).context("Failed to serialize config")?;
+        fs::write(path, text).with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    pub fn profile(&self, name: &str) -> Result<&Profile> {
+        self.profiles
+            .get(name)
+            .ok_or_else(|| anyhow!("Profile {} is not defined", name))
+    }
+
+    pub fn active_profile(&self) -> Result<Option<(&str, &Profile)>> {
+        match &self.active {
+            Some(name) => Ok(Some((name.as_str(), self.profile(name)?))),
+            None => Ok(None),
+        }
+    }
+
+    pub fn set_active(&mut self, name: &str) -> Result<()> {
+        self.profile(name)?;
+        self.active = Some(name.to_string());
+        Ok(())
+    }
+}
+
+impl Profile {
+    // An inline `api_key` wins over `api_key_env`.
+    pub fn resolve_api_key(&self) -> Result<Option<String>> {
+        if let Some(api_key) = &self.api_key {
+            return Ok(Some(api_key.clone()));
+        }
+
+        match &self.api_key_env {
+            Some(name) => match env::var(name) {
+                Ok(api_key) => Ok(Some(api_key)),
+                Err(_) => bail!("Environment variable {} is not set", name),
+            },
+            None => Ok(None),
+        }
+    }
+
+    // Applies the profile's settings on top of an existing client.
+    pub fn apply(&self, mut client: KaiaScan) -> Result<KaiaScan> {
+        if let Some(base_url) = &self.base_url {
+            client = client.with_base_url(base_url.as_str());
+        }
+        if let Some(api_key) = self.resolve_api_key()? {
+            client = client.with_api_key(api_key);
+        }
+        if let Some(timeout_secs) = self.timeout_secs {
+            client = client.with_timeout(Duration::from_secs(timeout_secs))?;
+        }
+        if let Some(rate_limit) = self.rate_limit {
+            client = client.with_rate_limit(rate_limit);
+        }
+        Ok(client)
+    }
+
+    pub fn client(&self) -> Result<KaiaScan> {
+        self.apply(KaiaScan::new(false)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        env::temp_dir().join(format!(
+            "rustsdk-config-{}-{}.toml",
+            name,
+            std::process::id()
+        ))
+    }
+
+    fn config() -> Config {
+        toml::from_str(
+            r#"
+            active = "kairos"
+
+            [profiles.kairos]
+            base_url = "https://kairos-oapi.kaiascan.io/"
+            api_key = "inline"
+            timeout_secs = 30
+            rate_limit = 5
+            output = "table"
+
+            [profiles.mainnet]
+            "#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn loads_missing_files_as_empty_config() {
+        let config = Config::load(temp_path("missing")).unwrap();
+        assert!(config.active.is_none());
+        assert!(config.profiles.is_empty());
+    }
+
+    #[test]
+    fn saves_and_loads_profiles() {
+        let path = temp_path("roundtrip");
+        config().save(&path).unwrap();
+        let loaded = Config::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.active.as_deref(), Some("kairos"));
+        let kairos = loaded.profile("kairos").unwrap();
+        assert_eq!(kairos.timeout_secs, Some(30));
+        assert_eq!(kairos.rate_limit, Some(5));
+        assert_eq!(kairos.output.as_deref(), Some("table"));
+        assert!(loaded.profile("mainnet").unwrap().base_url.is_none());
+    }
+
+    #[test]
+    fn rejects_unparsable_files() {
+        let path = temp_path("invalid");
+        fs::write(&path, "active = [").unwrap();
+        let result = Config::load(&path);
+        fs::remove_file(&path).unwrap();
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn selects_profiles() {
+        let mut config = config();
+        let (name, profile) = config.active_profile().unwrap().unwrap();
+        assert_eq!(name, "kairos");
+        assert_eq!(profile.rate_limit, Some(5));
+
+        assert!(config.profile("testnet").is_err());
+        assert!(config.set_active("testnet").is_err());
+        assert_eq!(config.active.as_deref(), Some("kairos"));
+
+        config.set_active("mainnet").unwrap();
+        assert_eq!(config.active_profile().unwrap().unwrap().0, "mainnet");
+
+        config.active = Some("removed".to_string());
+        assert!(config.active_profile().is_err());
+        config.active = None;
+        assert!(config.active_profile().unwrap().is_none());
+    }
+
+    #[test]
+    fn inline_api_key_wins_over_environment() {
+        let name = format!("RUSTSDK_TEST_API_KEY_{}", std::process::id());
+        env::set_var(&name, "from-env");
+
+        let mut profile = Profile {
+            api_key: Some("inline".to_string()),
+            api_key_env: Some(name.clone()),
+            ..Profile::default()
+        };
+        assert_eq!(
+            profile.resolve_api_key().unwrap().as_deref(),
+            Some("inline")
+        );
+
+        profile.api_key = None;
+        assert_eq!(
+            profile.resolve_api_key().unwrap().as_deref(),
+            Some("from-env")
+        );
+
+        env::remove_var(&name);
+        assert!(profile.resolve_api_key().is_err());
+
+        profile.api_key_env = None;
+        assert_eq!(profile.resolve_api_key().unwrap(), None);
+    }
+
+    #[test]
+    fn applies_profile_settings_to_the_client() {
+        let profile = config().profile("kairos").unwrap().clone();
+        let client = profile.apply(KaiaScan::new(false).unwrap()).unwrap();
+
+        assert_eq!(client.base_url, "https://kairos-oapi.kaiascan.io/");
+        assert_eq!(client.api_key, "inline");
+        assert_eq!(
+            client
+                .rate_limiter
+                .as_ref()
+                .map(|limiter| limiter.requests_per_second()),
+            Some(5)
+        );
+
+        // An empty profile leaves the client as it was.
+        let client = Profile::default()
+            .apply(
+                KaiaScan::new(false)
+                    .unwrap()
+                    .with_base_url("http://localhost:1"),
+            )
+            .unwrap();
+        assert_eq!(client.base_url, "http://localhost:1/");
+        assert!(client.rate_limiter.is_none());
+    }
+}
@@ -0,0 +1,338 @@
+use crate::abi::Abi;
+use crate::ContractSourceCode;
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceFile {
+    pub path: String,
+    pub content: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OptimizationSettings {
+    pub enabled: bool,
+    pub runs: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct StandardJsonInput {
+    sources: BTreeMap<String, StandardJsonSource>,
+    #[serde(default)]
+    settings: StandardJsonSettings,
+}
+
+#[derive(Deserialize)]
+struct StandardJsonSource {
+    #[serde(default)]
+    content: String,
+}
+
+// Etherscan-style multi-file sources: a bare map of path to `{ "content": ... }` without the
+// surrounding `sources` key or any settings.
+#[derive(Deserialize)]
+struct BareJsonSource {
+    content: String,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum MultiFileSource {
+    StandardJson(StandardJsonInput),
+    SourceMap(BTreeMap<String, BareJsonSource>),
+}
+
+#[derive(Deserialize, Default)]
+struct StandardJsonSettings {
+    #[serde(default)]
+    optimizer: Option<StandardJsonOptimizer>,
+    #[serde(default, rename = "evmVersion")]
+    evm_version: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct StandardJsonOptimizer {
+    #[serde(default)]
+    enabled: bool,
+    #[serde(default)]
+    runs: Option<u64>,
+}
+
+impl ContractSourceCode {
+    // Parses `source_code` as solc standard-JSON input, or as a bare Etherscan-style map of
+    // sources, which then has no settings. Explorers sometimes wrap the JSON in an extra
+    // pair of braces, which is stripped first.
+    fn standard_json_input(&self) -> Option<StandardJsonInput> {
+        let source = self.source_code.trim();
+        let source = source
+            .strip_prefix("{{")
+            .and_then(|s| s.strip_suffix("}}"))
+            .map(|inner| format!("{{{}}}", inner))
+            .unwrap_or_else(|| source.to_string());
+
+        match serde_json::from_str(&source).ok()? {
+            MultiFileSource::StandardJson(input) => Some(input),
+            MultiFileSource::SourceMap(sources) if !sources.is_empty() => Some(StandardJsonInput {
+                sources: sources
+                    .into_iter()
+                    .map(|(path, source)| {
+                        let source = StandardJsonSource {
+                            content: source.content,
+                        };
+                        (path, source)
+                    })
+                    .collect(),
+                settings: StandardJsonSettings::default(),
+            }),
+            MultiFileSource::SourceMap(_) => None,
+        }
+    }
+
+    pub fn is_multi_file(&self) -> bool {
+        self.standard_json_input().is_some()
+    }
+
+    pub fn sources(&self) -> Vec<SourceFile> {
+        match self.standard_json_input() {
+            Some(input) => input
+                .sources
+                .into_iter()
+                .map(|(path, source)| SourceFile {
+                    path,
+                    content: source.content,
+                })
+                .collect(),
+            None => vec![SourceFile {
+                path: format!(
+                    "{}.sol",
+                    self.contract_name.as_deref().unwrap_or("Contract")
+                ),
+                content: self.source_code.clone(),
+            }],
+        }
+    }
+
+    // Prefers the explorer's fields and falls back to the standard-JSON settings.
+    pub fn optimization(&self) -> Option<OptimizationSettings> {
+        if let Some(enabled) = self.optimizer_enabled {
+            return Some(OptimizationSettings {
+                enabled,
+                runs: self.optimizer_runs,
+            });
+        }
+
+        self.standard_json_input()
+            .and_then(|input| input.settings.optimizer)
+            .map(|optimizer| OptimizationSettings {
+                enabled: optimizer.enabled,
+                runs: optimizer.runs,
+            })
+    }
+
+    pub fn resolved_evm_version(&self) -> Option<String> {
+        self.evm_version.clone().or_else(|| {
+            self.standard_json_input()
+                .and_then(|input| input.settings.evm_version)
+        })
+    }
+
+    pub fn parsed_abi(&self) -> Result<Option<Abi>> {
+        match self.abi.as_deref().map(str::trim) {
+            Some(abi) if !abi.is_empty() => Abi::from_json(abi).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    // Writes every source file under `dir`, keeping the paths from the standard-JSON input,
+    // and returns the written paths.
+    pub fn write_sources(&self, dir: impl AsRef<Path>) -> Result<Vec<PathBuf>> {
+        let dir = dir.as_ref();
+        let mut written = Vec::new();
+
+        for source in self.sources() {
+            let path = dir.join(safe_relative_path(&source.path)?);
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)
+                    .with_context(|| format!("Failed to create {}", parent.display()))?;
+            }
+            fs::write(&path, &source.content)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            written.push(path);
+        }
+
+        Ok(written)
+    }
+}
+
+// Keeps source paths inside the output directory.
+fn safe_relative_path(path: &str) -> Result<PathBuf> {
+    let mut relative = PathBuf::new();
+
+    for component in Path::new(path).components() {
+        match component {
+            Component::Normal(part) => relative.push(part),
+            Component::CurDir => {}
+            _ => bail!(
+                "Refusing to write source outside the output directory: {}",
+                path
+            ),
+        }
+    }
+
+    if relative.as_os_str().is_empty() {
+        bail!("Empty source path");
+    }
+
+    Ok(relative)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn contract(source_code: &str) -> ContractSourceCode {
+        serde_json::from_value(json!({
+            "contract_address": "0x0000000000000000000000000000000000000001",
+            "source_code": source_code,
+            "contract_name": "Token",
+        }))
+        .unwrap()
+    }
+
+    fn standard_json() -> String {
+        json!({
+            "language": "Solidity",
+            "sources": {
+                "contracts/Token.sol": { "content": "contract Token {}" },
+                "@openzeppelin/ERC20.sol": { "content": "contract ERC20 {}" },
+            },
+            "settings": {
+                "optimizer": { "enabled": true, "runs": 200 },
+                "evmVersion": "london",
+            },
+        })
+        .to_string()
+    }
+
+    fn paths(sources: &[SourceFile]) -> Vec<&str> {
+        sources.iter().map(|source| source.path.as_str()).collect()
+    }
+
+    #[test]
+    fn single_file_source_is_named_after_the_contract() {
+        let source = contract("pragma solidity ^0.8.0;\ncontract Token {}");
+
+        assert!(!source.is_multi_file());
+        assert_eq!(
+            source.sources(),
+            vec![SourceFile {
+                path: "Token.sol".to_string(),
+                content: "pragma solidity ^0.8.0;\ncontract Token {}".to_string(),
+            }]
+        );
+        assert_eq!(source.optimization(), None);
+    }
+
+    #[test]
+    fn reads_standard_json_sources_and_settings() {
+        let source = contract(&standard_json());
+
+        assert!(source.is_multi_file());
+        let sources = source.sources();
+        assert_eq!(
+            paths(&sources),
+            vec!["@openzeppelin/ERC20.sol", "contracts/Token.sol"]
+        );
+        assert_eq!(sources[1].content, "contract Token {}");
+        assert_eq!(source.resolved_evm_version().as_deref(), Some("london"));
+    }
+
+    #[test]
+    fn unwraps_double_braced_standard_json() {
+        let wrapped = format!("{{{}}}", standard_json());
+        let source = contract(&format!("  {}\n", wrapped));
+
+        assert!(source.is_multi_file());
+        assert_eq!(source.sources().len(), 2);
+    }
+
+    #[test]
+    fn reads_bare_source_maps() {
+        let map = json!({
+            "Token.sol": { "content": "contract Token {}" },
+            "lib/Math.sol": { "content": "library Math {}" },
+        });
+        let source = contract(&map.to_string());
+
+        assert!(source.is_multi_file());
+        assert_eq!(paths(&source.sources()), vec!["Token.sol", "lib/Math.sol"]);
+        assert_eq!(source.optimization(), None);
+
+        // JSON that is neither form stays a single file.
+        let source = contract(r#"{"Token.sol": "contract Token {}"}"#);
+        assert!(!source.is_multi_file());
+    }
+
+    #[test]
+    fn optimization_prefers_explorer_fields() {
+        let mut source = contract(&standard_json());
+        assert_eq!(
+            source.optimization(),
+            Some(OptimizationSettings {
+                enabled: true,
+                runs: Some(200),
+            })
+        );
+
+        source.optimizer_enabled = Some(false);
+        source.optimizer_runs = None;
+        assert_eq!(
+            source.optimization(),
+            Some(OptimizationSettings {
+                enabled: false,
+                runs: None,
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_paths_outside_the_output_directory() {
+        assert_eq!(
+            safe_relative_path("./contracts/Token.sol").unwrap(),
+            PathBuf::from("contracts/Token.sol")
+        );
+        assert!(safe_relative_path("../Token.sol").is_err());
+        assert!(safe_relative_path("contracts/../../Token.sol").is_err());
+        assert!(safe_relative_path("/etc/Token.sol").is_err());
+        assert!(safe_relative_path(".").is_err());
+    }
+
+    #[test]
+    fn writes_sources_under_the_directory() {
+        let dir = std::env::temp_dir().join(format!("rustsdk-sources-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+
+        let written = contract(&standard_json()).write_sources(&dir).unwrap();
+        assert_eq!(
+            written,
+            vec![
+                dir.join("@openzeppelin/ERC20.sol"),
+                dir.join("contracts/Token.sol"),
+            ]
+        );
+        assert_eq!(
+            fs::read_to_string(dir.join("contracts/Token.sol")).unwrap(),
+            "contract Token {}"
+        );
+
+        let escaping = json!({ "sources": { "../escape.sol": { "content": "" } } });
+        assert!(contract(&escaping.to_string()).write_sources(&dir).is_err());
+        assert!(!dir.join("../escape.sol").exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
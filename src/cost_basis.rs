@@ -0,0 +1,942 @@
+use crate::datetime::unix_timestamp;
+#[cfg(feature = "csv")]
+use crate::portfolio::format_base_units;
+use crate::portfolio::{parse_base_units, wei_to_kaia, KAIA_DECIMALS};
+use crate::{
+    Address, Asset, FeeDelegation, KaiaScan, NftTransfer, PriceSource, TokenTransfer, Transaction,
+    TransferPage,
+};
+use anyhow::{bail, Result};
+use futures::stream::{self, StreamExt, TryStreamExt};
+use primitive_types::U256;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+use std::ops::RangeInclusive;
+
+const ZERO_ADDRESS: &str = "0x0000000000000000000000000000000000000000";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CostBasisMethod {
+    Fifo,
+    Lifo,
+    // Every disposal takes the pooled average cost of the open position.
+    Average,
+}
+
+// Quantities are integers in the token's base units; `decimals` scales them for display
+// (NFTs have 0). Prices and values are USD and `None` when the price source had no price.
+
+// An incoming transfer. `remaining` is what is left after the disposals in the report.
+#[derive(Debug, Clone, Serialize)]
+pub struct Acquisition {
+    pub contract_address: String,
+    pub token_id: Option<String>,
+    pub symbol: Option<String>,
+    pub decimals: u32,
+    pub datetime: String,
+    pub transaction_hash: String,
+    pub quantity: u128,
+    pub remaining: u128,
+    pub unit_price: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Disposal {
+    pub contract_address: String,
+    pub token_id: Option<String>,
+    pub symbol: Option<String>,
+    pub decimals: u32,
+    pub datetime: String,
+    pub transaction_hash: String,
+    pub quantity: u128,
+    pub unit_price: Option<f64>,
+    pub proceeds: Option<f64>,
+    // `None` when one of the lots the disposal was matched against is unpriced.
+    pub cost_basis: Option<f64>,
+    // `None` when the proceeds or the cost basis are unknown.
+    pub realized_gain: Option<f64>,
+    // Quantity sent without a matching acquisition in the report, e.g. bought before the
+    // block range. It is given a cost basis of zero.
+    pub unmatched_quantity: u128,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OpenPosition {
+    pub contract_address: String,
+    pub token_id: Option<String>,
+    pub symbol: Option<String>,
+    pub decimals: u32,
+    pub quantity: u128,
+    pub cost_basis: Option<f64>,
+    pub market_value: Option<f64>,
+    pub unrealized_gain: Option<f64>,
+}
+
+// A transaction fee paid by the reported address, either for its own transaction or as
+// the fee payer of a delegated one.
+#[derive(Debug, Clone, Serialize)]
+pub struct FeePayment {
+    pub transaction_hash: String,
+    pub datetime: String,
+    pub fee: f64,
+    pub usd_value: Option<f64>,
+    // Sender of a delegated transaction whose fee this address covered.
+    pub sponsored_for: Option<String>,
+    // The fee was split between sender and fee payer with a ratio the transaction listing
+    // does not reveal. `fee` is the whole transaction fee and is left out of `fees_usd`.
+    pub partial: bool,
+}
+
+// A token left out of the report because its decimals could not be looked up.
+#[derive(Debug, Clone, Serialize)]
+pub struct SkippedToken {
+    pub contract_address: String,
+    pub transfers: usize,
+    pub error: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CostBasisReport {
+    pub address: Address,
+    pub method: CostBasisMethod,
+    pub acquisitions: Vec<Acquisition>,
+    pub disposals: Vec<Disposal>,
+    pub open_positions: Vec<OpenPosition>,
+    pub fees: Vec<FeePayment>,
+    pub skipped_tokens: Vec<SkippedToken>,
+    // Totals over the priced disposals, positions and fees only.
+    pub realized_gain: f64,
+    pub unrealized_gain: f64,
+    // Fees of partially delegated transactions are not included.
+    pub fees_usd: f64,
+    // Transfers and fees left unpriced because the price source had no price.
+    pub missing_prices: usize,
+    // Hashes of transactions whose fee could not be parsed; their fees are left out.
+    pub unparsed_fees: Vec<String>,
+}
+
+#[cfg(feature = "csv")]
+#[derive(Debug, Serialize)]
+struct CsvRow<'a> {
+    kind: &'a str,
+    contract_address: &'a str,
+    token_id: Option<&'a str>,
+    symbol: Option<&'a str>,
+    datetime: Option<&'a str>,
+    transaction_hash: Option<&'a str>,
+    quantity: Option<String>,
+    unit_price: Option<f64>,
+    proceeds: Option<f64>,
+    cost_basis: Option<f64>,
+    gain: Option<f64>,
+    fee: Option<f64>,
+    fee_usd: Option<f64>,
+    sponsored_for: Option<&'a str>,
+    partial: Option<bool>,
+}
+
+#[cfg(feature = "csv")]
+impl<'a> CsvRow<'a> {
+    fn new(kind: &'a str, contract_address: &'a str) -> Self {
+        Self {
+            kind,
+            contract_address,
+            token_id: None,
+            symbol: None,
+            datetime: None,
+            transaction_hash: None,
+            quantity: None,
+            unit_price: None,
+            proceeds: None,
+            cost_basis: None,
+            gain: None,
+            fee: None,
+            fee_usd: None,
+            sponsored_for: None,
+            partial: None,
+        }
+    }
+}
+
+impl CostBasisReport {
+    // Writes acquisitions, disposals, open positions and fees as one CSV table, told apart
+    // by the `kind` column.
+    #[cfg(feature = "csv")]
+    pub fn write_csv<W: std::io::Write>(&self, writer: W) -> Result<()> {
+        let mut writer = csv::Writer::from_writer(writer);
+
+        for lot in &self.acquisitions {
+            writer.serialize(CsvRow {
+                token_id: lot.token_id.as_deref(),
+                symbol: lot.symbol.as_deref(),
+                datetime: Some(&lot.datetime),
+                transaction_hash: Some(&lot.transaction_hash),
+                quantity: Some(format_base_units(lot.quantity, lot.decimals)),
+                unit_price: lot.unit_price,
+                cost_basis: lot
+                    .unit_price
+                    .map(|price| price * units(lot.quantity, lot.decimals)),
+                ..CsvRow::new("acquisition", &lot.contract_address)
+            })?;
+        }
+        for disposal in &self.disposals {
+            writer.serialize(CsvRow {
+                token_id: disposal.token_id.as_deref(),
+                symbol: disposal.symbol.as_deref(),
+                datetime: Some(&disposal.datetime),
+                transaction_hash: Some(&disposal.transaction_hash),
+                quantity: Some(format_base_units(disposal.quantity, disposal.decimals)),
+                unit_price: disposal.unit_price,
+                proceeds: disposal.proceeds,
+                cost_basis: disposal.cost_basis,
+                gain: disposal.realized_gain,
+                ..CsvRow::new("disposal", &disposal.contract_address)
+            })?;
+        }
+        for position in &self.open_positions {
+            writer.serialize(CsvRow {
+                token_id: position.token_id.as_deref(),
+                symbol: position.symbol.as_deref(),
+                quantity: Some(format_base_units(position.quantity, position.decimals)),
+                proceeds: position.market_value,
+                cost_basis: position.cost_basis,
+                gain: position.unrealized_gain,
+                ..CsvRow::new("open", &position.contract_address)
+            })?;
+        }
+        for fee in &self.fees {
+            writer.serialize(CsvRow {
+                datetime: Some(&fee.datetime),
+                transaction_hash: Some(&fee.transaction_hash),
+                fee: Some(fee.fee),
+                fee_usd: fee.usd_value,
+                sponsored_for: fee.sponsored_for.as_deref(),
+                partial: Some(fee.partial),
+                ..CsvRow::new("fee", "")
+            })?;
+        }
+
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+// A token or NFT movement in or out of the reported address.
+struct Movement {
+    contract_address: String,
+    token_id: Option<String>,
+    symbol: Option<String>,
+    decimals: u32,
+    block_id: u64,
+    transaction_index: u32,
+    datetime: String,
+    transaction_hash: String,
+    quantity: u128,
+    incoming: bool,
+}
+
+struct OpenLot {
+    acquisition: usize,
+    remaining: u128,
+    unit_cost: Option<f64>,
+}
+
+// Open lots of one token, or of one NFT token id.
+struct Position {
+    symbol: Option<String>,
+    decimals: u32,
+    lots: VecDeque<OpenLot>,
+}
+
+// Converts base units to whole tokens, for valuation only.
+fn units(quantity: u128, decimals: u32) -> f64 {
+    quantity as f64 / 10f64.powi(decimals as i32)
+}
+
+// The value of `quantity` base units at `unit_price` per whole token.
+fn value(quantity: u128, decimals: u32, unit_price: Option<f64>) -> Option<f64> {
+    if quantity == 0 {
+        return Some(0.0);
+    }
+    unit_price.map(|price| price * units(quantity, decimals))
+}
+
+fn add(total: Option<f64>, value: Option<f64>) -> Option<f64> {
+    total.zip(value).map(|(total, value)| total + value)
+}
+
+// `numerator * taken / held`, rounded down, without overflowing.
+fn pro_rata(numerator: u128, taken: u128, held: u128) -> u128 {
+    (U256::from(numerator) * U256::from(taken) / U256::from(held)).as_u128()
+}
+
+fn same_address(left: &str, right: &str) -> bool {
+    left.eq_ignore_ascii_case(right)
+}
+
+// The account that pays the fee: the fee payer of a delegated transaction, otherwise the
+// sender.
+fn fee_payer(transaction: &Transaction) -> &str {
+    let fee_payer = transaction.fee_payer.trim();
+    if fee_payer.is_empty() || same_address(fee_payer, ZERO_ADDRESS) {
+        &transaction.from
+    } else {
+        fee_payer
+    }
+}
+
+// Takes `quantity` out of `lots` following `method` and returns the cost of what was taken
+// (`None` if an unpriced lot was used) and the quantity that no lot covered.
+fn consume(
+    lots: &mut VecDeque<OpenLot>,
+    quantity: u128,
+    decimals: u32,
+    method: CostBasisMethod,
+    acquisitions: &mut [Acquisition],
+) -> (Option<f64>, u128) {
+    let mut cost = Some(0.0);
+    let mut left = quantity;
+
+    if method == CostBasisMethod::Average {
+        // Each lot gives up its share of the pool. Shares are cumulative, so rounding never
+        // takes more or less than `taken` in total.
+        let held: u128 = lots.iter().map(|lot| lot.remaining).sum();
+        let taken = quantity.min(held);
+        let mut before = 0;
+        for lot in lots.iter_mut() {
+            if taken == 0 {
+                break;
+            }
+            let through = before + lot.remaining;
+            let share = pro_rata(through, taken, held) - pro_rata(before, taken, held);
+            before = through;
+
+            cost = add(cost, value(share, decimals, lot.unit_cost));
+            lot.remaining -= share;
+            acquisitions[lot.acquisition].remaining = lot.remaining;
+        }
+        left -= taken;
+    } else {
+        while left > 0 {
+            let lot = match method {
+                CostBasisMethod::Lifo => lots.back_mut(),
+                _ => lots.front_mut(),
+            };
+            let Some(lot) = lot else { break };
+
+            let taken = lot.remaining.min(left);
+            cost = add(cost, value(taken, decimals, lot.unit_cost));
+            lot.remaining -= taken;
+            left -= taken;
+            acquisitions[lot.acquisition].remaining = lot.remaining;
+
+            if lot.remaining == 0 {
+                match method {
+                    CostBasisMethod::Lifo => lots.pop_back(),
+                    _ => lots.pop_front(),
+                };
+            }
+        }
+    }
+
+    lots.retain(|lot| lot.remaining > 0);
+    (cost, left)
+}
+
+// Lots and disposals in movement order, and the positions left open per token or NFT.
+struct Ledger {
+    acquisitions: Vec<Acquisition>,
+    disposals: Vec<Disposal>,
+    positions: BTreeMap<(String, Option<String>), Position>,
+}
+
+fn match_lots(
+    movements: Vec<Movement>,
+    unit_prices: Vec<Option<f64>>,
+    method: CostBasisMethod,
+) -> Ledger {
+    let mut acquisitions = Vec::new();
+    let mut disposals = Vec::new();
+    let mut positions: BTreeMap<(String, Option<String>), Position> = BTreeMap::new();
+
+    for (movement, unit_price) in movements.into_iter().zip(unit_prices) {
+        let key = (movement.contract_address.clone(), movement.token_id.clone());
+        let Position { lots, .. } = positions.entry(key).or_insert_with(|| Position {
+            symbol: movement.symbol.clone(),
+            decimals: movement.decimals,
+            lots: VecDeque::new(),
+        });
+
+        if movement.incoming {
+            lots.push_back(OpenLot {
+                acquisition: acquisitions.len(),
+                remaining: movement.quantity,
+                unit_cost: unit_price,
+            });
+            acquisitions.push(Acquisition {
+                contract_address: movement.contract_address,
+                token_id: movement.token_id,
+                symbol: movement.symbol,
+                decimals: movement.decimals,
+                datetime: movement.datetime,
+                transaction_hash: movement.transaction_hash,
+                quantity: movement.quantity,
+                remaining: movement.quantity,
+                unit_price,
+            });
+        } else {
+            let (cost_basis, unmatched_quantity) = consume(
+                lots,
+                movement.quantity,
+                movement.decimals,
+                method,
+                &mut acquisitions,
+            );
+            let proceeds = value(movement.quantity, movement.decimals, unit_price);
+            disposals.push(Disposal {
+                contract_address: movement.contract_address,
+                token_id: movement.token_id,
+                symbol: movement.symbol,
+                decimals: movement.decimals,
+                datetime: movement.datetime,
+                transaction_hash: movement.transaction_hash,
+                quantity: movement.quantity,
+                unit_price,
+                proceeds,
+                cost_basis,
+                realized_gain: proceeds
+                    .zip(cost_basis)
+                    .map(|(proceeds, cost)| proceeds - cost),
+                unmatched_quantity,
+            });
+        }
+    }
+
+    Ledger {
+        acquisitions,
+        disposals,
+        positions,
+    }
+}
+
+impl KaiaScan {
+    // Builds tax lots from the token and NFT transfers of `address` within `block_range`
+    // and values them through `prices` at each transfer's time. NFT lots are keyed by
+    // token id and priced per item with the collection's price. Tokens whose decimals
+    // cannot be looked up are left out and listed in `skipped_tokens`. Fees are collected
+    // for the transactions in the report that `address` paid for and for every transaction
+    // in the range it sponsored as fee payer, which walks every block of the range.
+    pub async fn cost_basis_report<P: PriceSource + Sync>(
+        &self,
+        address: Address,
+        block_range: RangeInclusive<i64>,
+        method: CostBasisMethod,
+        prices: &P,
+    ) -> Result<CostBasisReport> {
+        let account = address.as_ref();
+        let (Ok(start), Ok(end)) = (
+            u64::try_from(*block_range.start()),
+            u64::try_from(*block_range.end()),
+        ) else {
+            bail!("Block numbers must not be negative: {:?}", block_range);
+        };
+        let transfer_range = start..=end;
+
+        let token_pages: Vec<TransferPage<TokenTransfer>> = self
+            .account_token_transfer_pages(address.clone(), transfer_range.clone(), 1)
+            .try_collect()
+            .await?;
+        let nft_pages: Vec<TransferPage<NftTransfer>> = self
+            .account_nft_transfer_pages(address.clone(), transfer_range, 1)
+            .try_collect()
+            .await?;
+        let token_transfers: Vec<TokenTransfer> = token_pages
+            .into_iter()
+            .flat_map(|page| page.results)
+            .collect();
+        let nft_transfers = nft_pages.into_iter().flat_map(|page| page.results);
+
+        let token_addresses: Vec<Address> = token_transfers
+            .iter()
+            .map(|transfer| transfer.contract.contract_address.to_ascii_lowercase())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .map(Address::new)
+            .collect();
+        let mut token_infos: HashMap<String, (Option<String>, u32)> = HashMap::new();
+        let mut skipped_tokens: BTreeMap<String, SkippedToken> = BTreeMap::new();
+        for (token, info) in token_addresses
+            .iter()
+            .zip(self.get_token_infos_batch(&token_addresses).await)
+        {
+            let contract_address = token.as_ref().to_string();
+            let decimals = info.and_then(|info| {
+                let decimals = u32::try_from(info.decimal)
+                    .map_err(|_| anyhow::anyhow!("Invalid decimals {}", info.decimal))?;
+                Ok((Some(info.symbol), decimals))
+            });
+            match decimals {
+                Ok(info) => {
+                    token_infos.insert(contract_address, info);
+                }
+                Err(err) => {
+                    skipped_tokens.insert(
+                        contract_address.clone(),
+                        SkippedToken {
+                            contract_address,
+                            transfers: 0,
+                            error: format!("{:#}", err),
+                        },
+                    );
+                }
+            }
+        }
+
+        let mut movements = Vec::new();
+        for transfer in token_transfers {
+            let incoming = same_address(&transfer.to, account);
+            if incoming == same_address(&transfer.from, account) {
+                continue;
+            }
+            let contract_address = transfer.contract.contract_address.to_ascii_lowercase();
+            let Some((symbol, decimals)) = token_infos.get(&contract_address).cloned() else {
+                if let Some(skipped) = skipped_tokens.get_mut(&contract_address) {
+                    skipped.transfers += 1;
+                }
+                continue;
+            };
+            movements.push(Movement {
+                quantity: transfer.amount as u128,
+                contract_address,
+                token_id: None,
+                symbol,
+                decimals,
+                block_id: transfer.block_id,
+                transaction_index: transfer.transaction_index,
+                datetime: transfer.datetime,
+                transaction_hash: transfer.transaction_hash,
+                incoming,
+            });
+        }
+        for transfer in nft_transfers {
+            let incoming = same_address(&transfer.to, account);
+            if incoming == same_address(&transfer.from, account) {
+                continue;
+            }
+            movements.push(Movement {
+                contract_address: transfer.contract.contract_address.to_ascii_lowercase(),
+                token_id: Some(transfer.token_id),
+                symbol: None,
+                decimals: 0,
+                block_id: transfer.block_id,
+                transaction_index: transfer.transaction_index,
+                datetime: transfer.datetime,
+                transaction_hash: transfer.transaction_hash,
+                quantity: transfer.token_count as u128,
+                incoming,
+            });
+        }
+        // Within a transaction, acquisitions come before disposals.
+        movements.sort_by_key(|movement| {
+            (
+                movement.block_id,
+                movement.transaction_index,
+                !movement.incoming,
+            )
+        });
+
+        let unit_prices: Vec<Option<f64>> = stream::iter(&movements)
+            .map(|movement| async move {
+                match unix_timestamp(&movement.datetime) {
+                    Some(timestamp) => {
+                        let asset = Asset::token(&movement.contract_address);
+                        prices.price_at(&asset, timestamp).await
+                    }
+                    None => Ok(None),
+                }
+            })
+            .buffered(self.batch_concurrency)
+            .try_collect()
+            .await?;
+        let mut missing_prices = unit_prices.iter().filter(|price| price.is_none()).count();
+
+        let Ledger {
+            acquisitions,
+            disposals,
+            positions,
+        } = match_lots(movements, unit_prices, method);
+
+        let mut open_positions = Vec::new();
+        for ((contract_address, token_id), position) in positions {
+            let Position {
+                symbol,
+                decimals,
+                lots,
+            } = position;
+            let quantity: u128 = lots.iter().map(|lot| lot.remaining).sum();
+            if quantity == 0 {
+                continue;
+            }
+            let cost_basis = lots.iter().try_fold(0.0, |total, lot| {
+                value(lot.remaining, decimals, lot.unit_cost).map(|value| total + value)
+            });
+            let spot = prices.spot_price(&Asset::token(&contract_address)).await?;
+            let market_value = value(quantity, decimals, spot);
+            open_positions.push(OpenPosition {
+                contract_address,
+                token_id,
+                symbol,
+                decimals,
+                quantity,
+                cost_basis,
+                market_value,
+                unrealized_gain: market_value
+                    .zip(cost_basis)
+                    .map(|(value, cost)| value - cost),
+            });
+        }
+
+        let report_hashes: BTreeSet<&str> = acquisitions
+            .iter()
+            .map(|lot| lot.transaction_hash.as_str())
+            .chain(
+                disposals
+                    .iter()
+                    .map(|disposal| disposal.transaction_hash.as_str()),
+            )
+            .collect();
+
+        let blocks = self
+            .blocks_in_range(block_range)
+            .try_filter(|block| futures::future::ready(block.total_transaction_count > 0))
+            .map_ok(|block| self.block_transactions(block.block_id))
+            .try_buffered(self.batch_concurrency);
+        futures::pin_mut!(blocks);
+
+        let mut fees = Vec::new();
+        let mut unparsed_fees = Vec::new();
+        while let Some(transactions) = blocks.try_next().await? {
+            for transaction in transactions {
+                // With a fee ratio both the sender and the fee payer pay a share.
+                let partial = transaction.tx_type().fee_delegation == FeeDelegation::Partial;
+                let sponsored = !same_address(&transaction.from, account);
+                let paid =
+                    same_address(fee_payer(&transaction), account) || (partial && !sponsored);
+                if !paid
+                    || !(sponsored || report_hashes.contains(transaction.transaction_hash.as_str()))
+                {
+                    continue;
+                }
+                let Some(fee_wei) = parse_base_units(&transaction.transaction_fee, KAIA_DECIMALS)
+                else {
+                    unparsed_fees.push(transaction.transaction_hash);
+                    continue;
+                };
+                let fee = wei_to_kaia(fee_wei);
+                let price = match unix_timestamp(&transaction.datetime) {
+                    Some(timestamp) => prices.price_at(&Asset::Kaia, timestamp).await?,
+                    None => None,
+                };
+                if price.is_none() {
+                    missing_prices += 1;
+                }
+                fees.push(FeePayment {
+                    usd_value: price.map(|price| price * fee),
+                    sponsored_for: sponsored.then(|| transaction.from.clone()),
+                    partial,
+                    transaction_hash: transaction.transaction_hash,
+                    datetime: transaction.datetime,
+                    fee,
+                });
+            }
+        }
+
+        Ok(CostBasisReport {
+            realized_gain: disposals
+                .iter()
+                .filter_map(|disposal| disposal.realized_gain)
+                .sum(),
+            unrealized_gain: open_positions
+                .iter()
+                .filter_map(|position| position.unrealized_gain)
+                .sum(),
+            fees_usd: fees
+                .iter()
+                .filter(|fee| !fee.partial)
+                .filter_map(|fee| fee.usd_value)
+                .sum(),
+            address,
+            method,
+            acquisitions,
+            disposals,
+            open_positions,
+            fees,
+            skipped_tokens: skipped_tokens.into_values().collect(),
+            missing_prices,
+            unparsed_fees,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::StaticPrices;
+    use mockito::Matcher;
+    use serde_json::json;
+
+    const ACCOUNT: &str = "0xaaaa";
+
+    fn movement(quantity: u128, incoming: bool, block_id: u64) -> Movement {
+        Movement {
+            contract_address: "0xtoken".to_string(),
+            token_id: None,
+            symbol: Some("TKN".to_string()),
+            decimals: 6,
+            block_id,
+            transaction_index: 0,
+            datetime: "2024-01-01T00:00:00Z".to_string(),
+            transaction_hash: format!("0x{}", block_id),
+            quantity,
+            incoming,
+        }
+    }
+
+    // Buys 1 at $1 and 3 at $2, then sells 2 at $3.
+    fn trades() -> (Vec<Movement>, Vec<Option<f64>>) {
+        (
+            vec![
+                movement(1_000_000, true, 1),
+                movement(3_000_000, true, 2),
+                movement(2_000_000, false, 3),
+            ],
+            vec![Some(1.0), Some(2.0), Some(3.0)],
+        )
+    }
+
+    #[test]
+    fn matches_lots_first_in_first_out() {
+        let (movements, prices) = trades();
+        let ledger = match_lots(movements, prices, CostBasisMethod::Fifo);
+
+        assert_eq!(ledger.disposals[0].cost_basis, Some(3.0));
+        assert_eq!(ledger.disposals[0].realized_gain, Some(3.0));
+        let remaining: Vec<u128> = ledger
+            .acquisitions
+            .iter()
+            .map(|lot| lot.remaining)
+            .collect();
+        assert_eq!(remaining, [0, 2_000_000]);
+    }
+
+    #[test]
+    fn matches_lots_last_in_first_out() {
+        let (movements, prices) = trades();
+        let ledger = match_lots(movements, prices, CostBasisMethod::Lifo);
+
+        assert_eq!(ledger.disposals[0].cost_basis, Some(4.0));
+        assert_eq!(ledger.disposals[0].realized_gain, Some(2.0));
+        let remaining: Vec<u128> = ledger
+            .acquisitions
+            .iter()
+            .map(|lot| lot.remaining)
+            .collect();
+        assert_eq!(remaining, [1_000_000, 1_000_000]);
+    }
+
+    #[test]
+    fn average_cost_takes_exact_shares_of_every_lot() {
+        let (movements, prices) = trades();
+        let ledger = match_lots(movements, prices, CostBasisMethod::Average);
+
+        assert_eq!(ledger.disposals[0].cost_basis, Some(3.5));
+        let remaining: Vec<u128> = ledger
+            .acquisitions
+            .iter()
+            .map(|lot| lot.remaining)
+            .collect();
+        assert_eq!(remaining, [500_000, 1_500_000]);
+
+        // Three base units out of lots of 1 and 2 cannot split evenly; no unit is lost.
+        let movements = vec![
+            movement(1, true, 1),
+            movement(2, true, 2),
+            movement(2, false, 3),
+        ];
+        let ledger = match_lots(movements, vec![Some(1.0); 3], CostBasisMethod::Average);
+        let remaining: u128 = ledger.acquisitions.iter().map(|lot| lot.remaining).sum();
+        assert_eq!(remaining, 1);
+        assert_eq!(ledger.disposals[0].unmatched_quantity, 0);
+    }
+
+    #[test]
+    fn unpriced_lots_are_not_realized_gains() {
+        let (movements, _) = trades();
+        let ledger = match_lots(
+            movements,
+            vec![None, Some(2.0), Some(3.0)],
+            CostBasisMethod::Fifo,
+        );
+
+        let disposal = &ledger.disposals[0];
+        assert_eq!(disposal.proceeds, Some(6.0));
+        assert_eq!(disposal.cost_basis, None);
+        assert_eq!(disposal.realized_gain, None);
+
+        // More sold than held: the rest is unmatched with a zero cost basis.
+        let movements = vec![movement(1, true, 1), movement(3, false, 2)];
+        let ledger = match_lots(movements, vec![Some(1.0), None], CostBasisMethod::Lifo);
+        assert_eq!(ledger.disposals[0].unmatched_quantity, 2);
+        assert_eq!(ledger.disposals[0].proceeds, None);
+    }
+
+    fn page(results: serde_json::Value) -> String {
+        json!({
+            "paging": { "current_page": 1, "last": true, "total_count": 1, "total_page": 1 },
+            "results": results,
+        })
+        .to_string()
+    }
+
+    fn token_transfer(contract: &str, block_id: u64, amount: u64) -> serde_json::Value {
+        json!({
+            "contract": { "contract_address": contract, "contract_type": "kip7" },
+            "blockId": block_id,
+            "transactionHash": format!("0x{}", block_id),
+            "feePayer": "",
+            "transactionIndex": 0,
+            "datetime": "2024-01-01T00:00:00Z",
+            "from": "0xfeed",
+            "to": ACCOUNT,
+            "amount": amount,
+        })
+    }
+
+    fn transaction(hash: &str, from: &str, fee_payer: &str) -> serde_json::Value {
+        json!({
+            "amount": "0",
+            "block_id": 2,
+            "datetime": "2024-01-01T00:00:00Z",
+            "effective_gas_price": "25",
+            "fee_payer": fee_payer,
+            "from": from,
+            "method_id": "",
+            "status": { "status": "Success" },
+            "to": "0xcontract",
+            "transaction_fee": "0.000525",
+            "transaction_hash": hash,
+            "transaction_index": 0,
+            "transaction_type": "TxTypeFeeDelegatedSmartContractExecution",
+        })
+    }
+
+    #[tokio::test]
+    async fn skips_unknown_tokens_and_collects_sponsored_fees() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock(
+                "GET",
+                Matcher::Regex(r"^/api/v1/accounts/0xaaaa/token-transfers".into()),
+            )
+            .with_body(page(json!([
+                token_transfer("0xgood", 1, 1_500_000),
+                token_transfer("0xbad", 1, 7),
+            ])))
+            .create_async()
+            .await;
+        server
+            .mock(
+                "GET",
+                Matcher::Regex(r"^/api/v1/accounts/0xaaaa/nft-transfers".into()),
+            )
+            .with_body(page(json!([])))
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/api/v1/tokens/0xgood")
+            .with_body(
+                json!({
+                    "contract_type": "kip7", "name": "Good", "symbol": "GOOD", "icon": "",
+                    "decimal": 6, "total_supply": "0", "total_transfers": 0,
+                    "official_site": "", "burn_amount": "0", "total_burns": 0,
+                })
+                .to_string(),
+            )
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/api/v1/tokens/0xbad")
+            .with_status(404)
+            .create_async()
+            .await;
+        server
+            .mock(
+                "GET",
+                Matcher::Regex(r"^/api/v1/blocks\?blockNumberStart=1".into()),
+            )
+            .with_body(page(json!([
+                { "base_fee_per_gas": "25", "block_id": 1, "block_proposer": "", "block_size": 1,
+                  "burnt_fees": "0", "datetime": "2024-01-01T00:00:00Z", "reward": "0",
+                  "total_transaction_count": 0 },
+                { "base_fee_per_gas": "25", "block_id": 2, "block_proposer": "", "block_size": 1,
+                  "burnt_fees": "0", "datetime": "2024-01-01T00:00:00Z", "reward": "0",
+                  "total_transaction_count": 2 },
+            ])))
+            .create_async()
+            .await;
+        let mut unparsable_fee = transaction("0xbadfee", "0xuser", ACCOUNT);
+        unparsable_fee["transaction_fee"] = json!("n/a");
+        server
+            .mock(
+                "GET",
+                Matcher::Regex(r"^/api/v1/blocks/2/transactions".into()),
+            )
+            .with_body(page(json!([
+                transaction("0xsponsored", "0xuser", ACCOUNT),
+                transaction("0xother", "0xuser", "0xsomeone"),
+                unparsable_fee,
+            ])))
+            .create_async()
+            .await;
+
+        let client = KaiaScan::new(false).unwrap().with_base_url(server.url());
+        let prices = StaticPrices::new()
+            .with_spot_price(Asset::token("0xgood"), 2.0)
+            .with_spot_price(Asset::Kaia, 0.2);
+        let report = client
+            .cost_basis_report(Address::new(ACCOUNT), 1..=2, CostBasisMethod::Fifo, &prices)
+            .await
+            .unwrap();
+
+        assert_eq!(report.acquisitions.len(), 1);
+        assert_eq!(report.acquisitions[0].quantity, 1_500_000);
+        assert_eq!(report.acquisitions[0].decimals, 6);
+        assert_eq!(report.open_positions[0].market_value, Some(3.0));
+
+        assert_eq!(report.skipped_tokens.len(), 1);
+        assert_eq!(report.skipped_tokens[0].contract_address, "0xbad");
+        assert_eq!(report.skipped_tokens[0].transfers, 1);
+
+        assert_eq!(report.fees.len(), 1);
+        assert_eq!(report.fees[0].transaction_hash, "0xsponsored");
+        assert_eq!(report.fees[0].sponsored_for.as_deref(), Some("0xuser"));
+        assert_eq!(report.fees[0].fee, 0.000525);
+        assert_eq!(report.unparsed_fees, ["0xbadfee"]);
+    }
+
+    #[tokio::test]
+    async fn rejects_negative_block_ranges() {
+        let client = KaiaScan::new(false).unwrap();
+        let prices = StaticPrices::new();
+        let result = client
+            .cost_basis_report(
+                Address::new(ACCOUNT),
+                -1..=2,
+                CostBasisMethod::Fifo,
+                &prices,
+            )
+            .await;
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,55 @@
+// Datetime helpers shared by the reports and the Arrow export. Crate-internal, so the chrono
+// types used here stay out of the public API.
+
+use chrono::{DateTime, NaiveDateTime};
+
+// Converts an API datetime (RFC 3339, or zone-less and taken as UTC) to Unix seconds.
+pub(crate) fn unix_timestamp(datetime: &str) -> Option<i64> {
+    unix_timestamp_micros(datetime).map(|micros| micros.div_euclid(1_000_000))
+}
+
+// Like `unix_timestamp`, in microseconds.
+pub(crate) fn unix_timestamp_micros(datetime: &str) -> Option<i64> {
+    let datetime = datetime.trim();
+
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(datetime) {
+        return Some(datetime.timestamp_micros());
+    }
+    ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(datetime, format).ok())
+        .map(|datetime| datetime.and_utc().timestamp_micros())
+}
+
+// The UTC day of a Unix timestamp as "YYYY-MM-DD".
+pub(crate) fn utc_date(timestamp: i64) -> Option<String> {
+    DateTime::from_timestamp(timestamp, 0).map(|datetime| datetime.format("%Y-%m-%d").to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_api_datetimes() {
+        assert_eq!(unix_timestamp("2024-01-01T00:00:00Z"), Some(1_704_067_200));
+        assert_eq!(
+            unix_timestamp("2024-01-01T09:00:00+09:00"),
+            Some(1_704_067_200)
+        );
+        assert_eq!(unix_timestamp(" 2024-01-01 00:00:00 "), Some(1_704_067_200));
+        assert_eq!(
+            unix_timestamp_micros("2024-01-01T00:00:00.25"),
+            Some(1_704_067_200_250_000)
+        );
+        assert_eq!(unix_timestamp("1969-12-31T23:59:59.5Z"), Some(-1));
+        assert_eq!(unix_timestamp("2024-01-01"), None);
+        assert_eq!(unix_timestamp(""), None);
+    }
+
+    #[test]
+    fn formats_utc_dates() {
+        assert_eq!(utc_date(1_704_067_199).as_deref(), Some("2023-12-31"));
+        assert_eq!(utc_date(1_704_067_200).as_deref(), Some("2024-01-01"));
+    }
+}
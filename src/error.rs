@@ -0,0 +1,48 @@
+use std::fmt;
+
+// Failures of a single API call. `fetch_api` wraps these in `anyhow::Error`, so callers
+// can classify them with `err.downcast_ref::<ApiError>()`; the underlying error is the
+// `source()`.
+#[derive(Debug)]
+pub enum ApiError {
+    // The request could not be sent or the response body could not be read.
+    Request(reqwest::Error),
+    // The server answered with a non-success status.
+    Status { status: u16, body: String },
+    // The response body did not match the expected shape.
+    Decode(serde_json::Error),
+}
+
+impl ApiError {
+    // Whether `err` was caused by a request for something the API does not know (HTTP 404).
+    pub fn is_not_found(err: &anyhow::Error) -> bool {
+        err.chain().any(|cause| {
+            matches!(
+                cause.downcast_ref::<ApiError>(),
+                Some(ApiError::Status { status: 404, .. })
+            )
+        })
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ApiError::Request(_) => write!(f, "Failed to make HTTP request"),
+            ApiError::Status { status, body } => {
+                write!(f, "API request failed with status {}: {}", status, body)
+            }
+            ApiError::Decode(_) => write!(f, "Failed to parse API response JSON"),
+        }
+    }
+}
+
+impl std::error::Error for ApiError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ApiError::Request(err) => Some(err),
+            ApiError::Status { .. } => None,
+            ApiError::Decode(err) => Some(err),
+        }
+    }
+}
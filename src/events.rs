@@ -0,0 +1,252 @@
+use crate::abi::{self, decode_hex, Abi, AbiEvent, AbiType, AbiValue};
+use crate::standards;
+use crate::{EventLogEntry, EventLogResult, KaiaScan};
+use anyhow::{anyhow, bail, Result};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodedEvent {
+    pub name: String,
+    pub signature: String,
+    pub params: Vec<(String, AbiValue)>,
+}
+
+// Events indexed by topic0. Several events can share a topic0 and differ only in which
+// parameters are indexed (ERC-20 and ERC-721 `Transfer`), so each entry keeps a list.
+#[derive(Debug, Clone, Default)]
+pub struct EventRegistry {
+    events: HashMap<[u8; 32], Vec<AbiEvent>>,
+}
+
+impl EventRegistry {
+    // A registry preloaded with the KIP-7/17/37 events.
+    pub fn standard() -> Self {
+        let mut registry = Self::default();
+        for standard in standards::standard_events() {
+            registry.register(standard.abi.clone());
+        }
+        registry
+    }
+
+    pub fn register(&mut self, event: AbiEvent) {
+        // Anonymous events have no topic0 to look them up by.
+        if event.anonymous {
+            return;
+        }
+
+        let candidates = self.events.entry(event.topic0()).or_default();
+        if !candidates.contains(&event) {
+            candidates.push(event);
+        }
+    }
+
+    pub fn register_abi(&mut self, abi: &Abi) {
+        for event in &abi.events {
+            self.register(event.clone());
+        }
+    }
+
+    pub fn get(&self, topic0: &[u8; 32]) -> &[AbiEvent] {
+        self.events.get(topic0).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    pub fn decode(&self, log: &EventLogResult) -> Result<DecodedEvent> {
+        let topic0 = log
+            .topics
+            .first()
+            .ok_or_else(|| anyhow!("Event log {} has no topics", log.log_index))?;
+        let topic0: [u8; 32] = decode_hex(topic0)?
+            .try_into()
+            .map_err(|_| anyhow!("Invalid topic0: {}", topic0))?;
+
+        let candidates = self.get(&topic0);
+        let event = candidates
+            .iter()
+            .find(|event| event.topic_count() == log.topics.len())
+            .or_else(|| candidates.first())
+            .ok_or_else(|| anyhow!("No registered ABI for event topic {}", log.topics[0]))?;
+
+        decode_log(event, &log.topics, &log.data)
+    }
+}
+
+// Decodes raw topics and data against a known event. Indexed parameters are read from the
+// topics; indexed parameters of dynamic types only expose their hash.
+pub fn decode_log(event: &AbiEvent, topics: &[String], data: &str) -> Result<DecodedEvent> {
+    if topics.len() != event.topic_count() {
+        bail!(
+            "Event {} expects {} topics but the log has {}",
+            event.signature(),
+            event.topic_count(),
+            topics.len()
+        );
+    }
+
+    let mut indexed_topics = topics.iter().skip(usize::from(!event.anonymous));
+    let data_types: Vec<AbiType> = event
+        .inputs
+        .iter()
+        .filter(|input| !input.indexed)
+        .map(|input| input.kind.clone())
+        .collect();
+    let mut data_values = abi::decode(&data_types, &decode_hex(data)?)?.into_iter();
+
+    let mut params = Vec::with_capacity(event.inputs.len());
+    for input in &event.inputs {
+        let value = if input.indexed {
+            let topic = decode_hex(indexed_topics.next().expect("topic count checked above"))?;
+            if topic.len() != 32 {
+                bail!("Topic for {} is not 32 bytes", input.name);
+            }
+
+            if input.kind.is_dynamic()
+                || matches!(input.kind, AbiType::Tuple(_) | AbiType::FixedArray(..))
+            {
+                let mut hash = [0u8; 32];
+                hash.copy_from_slice(&topic);
+                AbiValue::Hashed(hash)
+            } else {
+                abi::decode_word(&input.kind, &topic)?
+            }
+        } else {
+            data_values.next().expect("one value per data type")
+        };
+
+        params.push((input.name.clone(), value));
+    }
+
+    Ok(DecodedEvent {
+        name: event.name.clone(),
+        signature: event.signature(),
+        params,
+    })
+}
+
+impl KaiaScan {
+    // Registers every event and function of a JSON ABI or a solc/Hardhat/Foundry artifact.
+    pub fn register_abi_json(&self, json: &str) -> Result<()> {
+        let abi = Abi::from_json(json)?;
+        self.events.write().unwrap().register_abi(&abi);
+        self.selectors.write().unwrap().register_abi(&abi);
+        Ok(())
+    }
+
+    // Registers a human-readable event such as
+    // `event Transfer(address indexed from, address indexed to, uint256 value)`.
+    pub fn register_event_signature(&self, signature: &str) -> Result<()> {
+        let event = AbiEvent::parse(signature)?;
+        self.events.write().unwrap().register(event);
+        Ok(())
+    }
+
+    pub fn decode_event_log(&self, log: &EventLogResult) -> Result<DecodedEvent> {
+        self.events.read().unwrap().decode(log)
+    }
+
+    pub fn decode_event_logs(&self, entry: &EventLogEntry) -> Vec<Result<DecodedEvent>> {
+        let registry = self.events.read().unwrap();
+        entry
+            .results
+            .iter()
+            .map(|log| registry.decode(log))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abi::{encode_hex, keccak256};
+    use primitive_types::U256;
+
+    const TRANSFER_TOPIC: &str =
+        "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef";
+
+    fn address_topic(byte: u8) -> String {
+        let mut word = [0u8; 32];
+        word[12..].fill(byte);
+        encode_hex(&word)
+    }
+
+    fn log(topics: Vec<String>, data: &str) -> EventLogResult {
+        EventLogResult {
+            log_index: 0,
+            contract_address: "0xc".to_string(),
+            log_type: String::new(),
+            topics,
+            data: data.to_string(),
+            items: Vec::new(),
+            block_number: 1,
+            transaction_hash: "0xabc".to_string(),
+            estimated_event_log: false,
+        }
+    }
+
+    #[test]
+    fn tells_token_and_nft_transfers_apart_by_topic_count() {
+        let registry = EventRegistry::standard();
+        let amount = encode_hex(&{
+            let mut word = [0u8; 32];
+            word[31] = 7;
+            word
+        });
+
+        let token = registry
+            .decode(&log(
+                vec![
+                    TRANSFER_TOPIC.to_string(),
+                    address_topic(0x11),
+                    address_topic(0x22),
+                ],
+                &amount,
+            ))
+            .unwrap();
+        assert_eq!(token.params[2].1, AbiValue::Uint(U256::from(7)));
+
+        let nft = registry
+            .decode(&log(
+                vec![
+                    TRANSFER_TOPIC.to_string(),
+                    address_topic(0x11),
+                    address_topic(0x22),
+                    amount.clone(),
+                ],
+                "0x",
+            ))
+            .unwrap();
+        assert_eq!(
+            nft.params[0].1,
+            AbiValue::Address(format!("0x{}", "11".repeat(20)))
+        );
+        assert_eq!(nft.params[2].1, AbiValue::Uint(U256::from(7)));
+    }
+
+    #[test]
+    fn hashes_indexed_dynamic_params() {
+        let event = AbiEvent::parse("event Named(string indexed name, uint256 value)").unwrap();
+        let mut registry = EventRegistry::default();
+        registry.register(event.clone());
+
+        let name_hash = encode_hex(&keccak256(b"kaia"));
+        let decoded = registry
+            .decode(&log(
+                vec![encode_hex(&event.topic0()), name_hash.clone()],
+                &encode_hex(&[0u8; 32]),
+            ))
+            .unwrap();
+
+        assert_eq!(decoded.params[0].1.to_string(), name_hash);
+        assert_eq!(decoded.params[1].1, AbiValue::Uint(U256::zero()));
+    }
+
+    #[test]
+    fn rejects_unknown_topics_and_topic_count_mismatches() {
+        let registry = EventRegistry::standard();
+        assert!(registry
+            .decode(&log(vec![address_topic(0x33)], "0x"))
+            .is_err());
+
+        let transfer = &registry.get(&decode_hex(TRANSFER_TOPIC).unwrap().try_into().unwrap())[0];
+        assert!(decode_log(transfer, &[TRANSFER_TOPIC.to_string()], "0x").is_err());
+    }
+}
@@ -0,0 +1,716 @@
+// Arrow and Parquet export of explorer data, behind the `arrow` feature.
+//
+// Every record type has a fixed schema. KAIA amounts are decimal128(38, 18), raw token
+// amounts decimal128(38, 0), values whose unit the API does not pin down (gas prices) stay
+// strings, and `datetime` fields become timestamp[us, UTC].
+
+use crate::datetime::unix_timestamp_micros;
+use crate::{BlockListItem, EventLogResult, NftTransfer, TokenTransfer, Transaction};
+use anyhow::{anyhow, bail, Context, Result};
+use arrow::array::{
+    ArrayRef, BooleanArray, Decimal128Array, Int64Array, ListBuilder, StringArray, StringBuilder,
+    TimestampMicrosecondArray, UInt32Array,
+};
+use arrow::compute::take_record_batch;
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef, TimeUnit};
+use arrow::record_batch::RecordBatch;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+const DECIMAL_PRECISION: u8 = 38;
+const KAIA_SCALE: i8 = 18;
+const TIMEZONE: &str = "UTC";
+
+// A record type that converts to Arrow. `TABLE` names its directory in a Parquet export.
+pub trait ArrowRecord: Sized {
+    const TABLE: &'static str;
+
+    fn schema() -> SchemaRef;
+    fn record_batch(rows: &[Self]) -> Result<RecordBatch>;
+    fn block_number(&self) -> i64;
+}
+
+fn timestamp_type() -> DataType {
+    DataType::Timestamp(TimeUnit::Microsecond, Some(TIMEZONE.into()))
+}
+
+fn decimal_type(scale: i8) -> DataType {
+    DataType::Decimal128(DECIMAL_PRECISION, scale)
+}
+
+fn topics_type() -> DataType {
+    DataType::List(Arc::new(Field::new("item", DataType::Utf8, true)))
+}
+
+// Parses a plain decimal string such as "1.25" into an integer scaled by 10^scale.
+fn parse_decimal(value: &str, scale: i8) -> Result<Option<i128>> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Ok(None);
+    }
+
+    let (negative, digits) = match value.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, value.strip_prefix('+').unwrap_or(value)),
+    };
+    let (integer, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+
+    let valid = |part: &str| part.chars().all(|c| c.is_ascii_digit());
+    if (integer.is_empty() && fraction.is_empty()) || !valid(integer) || !valid(fraction) {
+        bail!("Cannot parse decimal {}", value);
+    }
+    if fraction.len() > scale as usize && fraction[scale as usize..].chars().any(|c| c != '0') {
+        bail!(
+            "Decimal {} has more than {} fractional digits",
+            value,
+            scale
+        );
+    }
+
+    let overflow = || anyhow!("Decimal {} does not fit in decimal128", value);
+    let mut scaled: i128 = 0;
+    let fraction_digits = fraction.chars().chain(std::iter::repeat('0'));
+    for digit in integer.chars().chain(fraction_digits.take(scale as usize)) {
+        scaled = scaled
+            .checked_mul(10)
+            .and_then(|scaled| scaled.checked_add(digit.to_digit(10).unwrap_or(0) as i128))
+            .ok_or_else(overflow)?;
+    }
+    if scaled >= 10_i128.pow(DECIMAL_PRECISION as u32) {
+        return Err(overflow());
+    }
+
+    Ok(Some(if negative { -scaled } else { scaled }))
+}
+
+fn strings<'a>(values: impl Iterator<Item = &'a str>) -> ArrayRef {
+    Arc::new(StringArray::from_iter_values(values))
+}
+
+fn optional_strings<'a>(values: impl Iterator<Item = &'a str>) -> ArrayRef {
+    Arc::new(
+        values
+            .map(|value| (!value.is_empty()).then_some(value))
+            .collect::<StringArray>(),
+    )
+}
+
+fn timestamps<'a>(values: impl Iterator<Item = &'a str>) -> Result<ArrayRef> {
+    // Empty datetimes become nulls.
+    let values = values
+        .map(str::trim)
+        .map(|value| match value {
+            "" => Ok(None),
+            value => unix_timestamp_micros(value)
+                .map(Some)
+                .ok_or_else(|| anyhow!("Cannot parse timestamp {}", value)),
+        })
+        .collect::<Result<Vec<Option<i64>>>>()?;
+    Ok(Arc::new(
+        TimestampMicrosecondArray::from(values).with_timezone(TIMEZONE),
+    ))
+}
+
+fn decimals<'a>(
+    values: impl Iterator<Item = &'a str>,
+    scale: i8,
+    column: &str,
+) -> Result<ArrayRef> {
+    let values = values
+        .map(|value| parse_decimal(value, scale))
+        .collect::<Result<Vec<Option<i128>>>>()
+        .with_context(|| format!("Invalid {} value", column))?;
+    Ok(Arc::new(
+        Decimal128Array::from(values).with_precision_and_scale(DECIMAL_PRECISION, scale)?,
+    ))
+}
+
+fn raw_amounts(values: impl Iterator<Item = u64>) -> Result<ArrayRef> {
+    let values: Vec<i128> = values.map(i128::from).collect();
+    Ok(Arc::new(
+        Decimal128Array::from(values).with_precision_and_scale(DECIMAL_PRECISION, 0)?,
+    ))
+}
+
+fn int64s(values: impl Iterator<Item = i64>) -> ArrayRef {
+    Arc::new(Int64Array::from_iter_values(values))
+}
+
+impl ArrowRecord for Transaction {
+    const TABLE: &'static str = "transactions";
+
+    fn schema() -> SchemaRef {
+        Arc::new(Schema::new(vec![
+            Field::new("block_id", DataType::Int64, false),
+            Field::new("transaction_hash", DataType::Utf8, false),
+            Field::new("transaction_index", DataType::Int64, false),
+            Field::new("datetime", timestamp_type(), true),
+            Field::new("from", DataType::Utf8, false),
+            Field::new("to", DataType::Utf8, true),
+            Field::new("fee_payer", DataType::Utf8, true),
+            Field::new("amount", decimal_type(KAIA_SCALE), true),
+            Field::new("transaction_fee", decimal_type(KAIA_SCALE), true),
+            Field::new("effective_gas_price", DataType::Utf8, true),
+            Field::new("method_id", DataType::Utf8, true),
+            Field::new("transaction_type", DataType::Utf8, false),
+            Field::new("status", DataType::Utf8, false),
+        ]))
+    }
+
+    fn record_batch(rows: &[Self]) -> Result<RecordBatch> {
+        let columns = vec![
+            int64s(rows.iter().map(|tx| tx.block_id)),
+            strings(rows.iter().map(|tx| tx.transaction_hash.as_str())),
+            int64s(rows.iter().map(|tx| tx.transaction_index)),
+            timestamps(rows.iter().map(|tx| tx.datetime.as_str()))?,
+            strings(rows.iter().map(|tx| tx.from.as_str())),
+            optional_strings(rows.iter().map(|tx| tx.to.as_str())),
+            optional_strings(rows.iter().map(|tx| tx.fee_payer.as_str())),
+            decimals(
+                rows.iter().map(|tx| tx.amount.as_str()),
+                KAIA_SCALE,
+                "amount",
+            )?,
+            decimals(
+                rows.iter().map(|tx| tx.transaction_fee.as_str()),
+                KAIA_SCALE,
+                "transaction_fee",
+            )?,
+            optional_strings(rows.iter().map(|tx| tx.effective_gas_price.as_str())),
+            optional_strings(rows.iter().map(|tx| tx.method_id.as_str())),
+            strings(rows.iter().map(|tx| tx.transaction_type.as_str())),
+            strings(rows.iter().map(|tx| tx.status.status.as_str())),
+        ];
+        Ok(RecordBatch::try_new(Self::schema(), columns)?)
+    }
+
+    fn block_number(&self) -> i64 {
+        self.block_id
+    }
+}
+
+impl ArrowRecord for BlockListItem {
+    const TABLE: &'static str = "blocks";
+
+    fn schema() -> SchemaRef {
+        Arc::new(Schema::new(vec![
+            Field::new("block_id", DataType::Int64, false),
+            Field::new("datetime", timestamp_type(), true),
+            Field::new("block_proposer", DataType::Utf8, true),
+            Field::new("block_size", DataType::Int64, false),
+            Field::new("total_transaction_count", DataType::Int64, false),
+            Field::new("base_fee_per_gas", DataType::Utf8, true),
+            Field::new("burnt_fees", decimal_type(KAIA_SCALE), true),
+            Field::new("reward", decimal_type(KAIA_SCALE), true),
+        ]))
+    }
+
+    fn record_batch(rows: &[Self]) -> Result<RecordBatch> {
+        let columns = vec![
+            int64s(rows.iter().map(|block| block.block_id)),
+            timestamps(rows.iter().map(|block| block.datetime.as_str()))?,
+            optional_strings(rows.iter().map(|block| block.block_proposer.as_str())),
+            int64s(rows.iter().map(|block| block.block_size)),
+            int64s(rows.iter().map(|block| block.total_transaction_count)),
+            optional_strings(rows.iter().map(|block| block.base_fee_per_gas.as_str())),
+            decimals(
+                rows.iter().map(|block| block.burnt_fees.as_str()),
+                KAIA_SCALE,
+                "burnt_fees",
+            )?,
+            decimals(
+                rows.iter().map(|block| block.reward.as_str()),
+                KAIA_SCALE,
+                "reward",
+            )?,
+        ];
+        Ok(RecordBatch::try_new(Self::schema(), columns)?)
+    }
+
+    fn block_number(&self) -> i64 {
+        self.block_id
+    }
+}
+
+impl ArrowRecord for TokenTransfer {
+    const TABLE: &'static str = "token_transfers";
+
+    fn schema() -> SchemaRef {
+        Arc::new(Schema::new(vec![
+            Field::new("block_id", DataType::Int64, false),
+            Field::new("transaction_hash", DataType::Utf8, false),
+            Field::new("transaction_index", DataType::UInt32, false),
+            Field::new("datetime", timestamp_type(), true),
+            Field::new("contract_address", DataType::Utf8, false),
+            Field::new("contract_type", DataType::Utf8, false),
+            Field::new("from", DataType::Utf8, false),
+            Field::new("to", DataType::Utf8, false),
+            Field::new("fee_payer", DataType::Utf8, true),
+            // Raw units; the token's decimals are not part of the transfer.
+            Field::new("amount", decimal_type(0), false),
+        ]))
+    }
+
+    fn record_batch(rows: &[Self]) -> Result<RecordBatch> {
+        let columns = vec![
+            int64s(rows.iter().map(|transfer| transfer.block_id as i64)),
+            strings(
+                rows.iter()
+                    .map(|transfer| transfer.transaction_hash.as_str()),
+            ),
+            Arc::new(UInt32Array::from_iter_values(
+                rows.iter().map(|transfer| transfer.transaction_index),
+            )) as ArrayRef,
+            timestamps(rows.iter().map(|transfer| transfer.datetime.as_str()))?,
+            strings(
+                rows.iter()
+                    .map(|transfer| transfer.contract.contract_address.as_str()),
+            ),
+            strings(
+                rows.iter()
+                    .map(|transfer| transfer.contract.contract_type.as_str()),
+            ),
+            strings(rows.iter().map(|transfer| transfer.from.as_str())),
+            strings(rows.iter().map(|transfer| transfer.to.as_str())),
+            optional_strings(rows.iter().map(|transfer| transfer.fee_payer.as_str())),
+            raw_amounts(rows.iter().map(|transfer| transfer.amount))?,
+        ];
+        Ok(RecordBatch::try_new(Self::schema(), columns)?)
+    }
+
+    fn block_number(&self) -> i64 {
+        self.block_id as i64
+    }
+}
+
+impl ArrowRecord for NftTransfer {
+    const TABLE: &'static str = "nft_transfers";
+
+    fn schema() -> SchemaRef {
+        Arc::new(Schema::new(vec![
+            Field::new("block_id", DataType::Int64, false),
+            Field::new("transaction_hash", DataType::Utf8, false),
+            Field::new("transaction_index", DataType::UInt32, false),
+            Field::new("datetime", timestamp_type(), true),
+            Field::new("contract_address", DataType::Utf8, false),
+            Field::new("contract_type", DataType::Utf8, false),
+            Field::new("from", DataType::Utf8, false),
+            Field::new("to", DataType::Utf8, false),
+            Field::new("fee_payer", DataType::Utf8, true),
+            // Token ids can exceed 128 bits, so they stay strings.
+            Field::new("token_id", DataType::Utf8, false),
+            Field::new("token_count", decimal_type(0), false),
+        ]))
+    }
+
+    fn record_batch(rows: &[Self]) -> Result<RecordBatch> {
+        let columns = vec![
+            int64s(rows.iter().map(|transfer| transfer.block_id as i64)),
+            strings(
+                rows.iter()
+                    .map(|transfer| transfer.transaction_hash.as_str()),
+            ),
+            Arc::new(UInt32Array::from_iter_values(
+                rows.iter().map(|transfer| transfer.transaction_index),
+            )) as ArrayRef,
+            timestamps(rows.iter().map(|transfer| transfer.datetime.as_str()))?,
+            strings(
+                rows.iter()
+                    .map(|transfer| transfer.contract.contract_address.as_str()),
+            ),
+            strings(
+                rows.iter()
+                    .map(|transfer| transfer.contract.contract_type.as_str()),
+            ),
+            strings(rows.iter().map(|transfer| transfer.from.as_str())),
+            strings(rows.iter().map(|transfer| transfer.to.as_str())),
+            optional_strings(rows.iter().map(|transfer| transfer.fee_payer.as_str())),
+            strings(rows.iter().map(|transfer| transfer.token_id.as_str())),
+            raw_amounts(rows.iter().map(|transfer| transfer.token_count))?,
+        ];
+        Ok(RecordBatch::try_new(Self::schema(), columns)?)
+    }
+
+    fn block_number(&self) -> i64 {
+        self.block_id as i64
+    }
+}
+
+impl ArrowRecord for EventLogResult {
+    const TABLE: &'static str = "event_logs";
+
+    fn schema() -> SchemaRef {
+        Arc::new(Schema::new(vec![
+            Field::new("block_number", DataType::Int64, false),
+            Field::new("transaction_hash", DataType::Utf8, false),
+            Field::new("log_index", DataType::UInt32, false),
+            Field::new("contract_address", DataType::Utf8, false),
+            Field::new("log_type", DataType::Utf8, true),
+            Field::new("topics", topics_type(), false),
+            Field::new("data", DataType::Utf8, false),
+            // Explorer-decoded parameters as a JSON array of {name, value}.
+            Field::new("items", DataType::Utf8, true),
+            Field::new("estimated_event_log", DataType::Boolean, false),
+        ]))
+    }
+
+    fn record_batch(rows: &[Self]) -> Result<RecordBatch> {
+        let mut topics = ListBuilder::new(StringBuilder::new());
+        for log in rows {
+            for topic in &log.topics {
+                topics.values().append_value(topic);
+            }
+            topics.append(true);
+        }
+
+        let items = rows
+            .iter()
+            .map(|log| {
+                if log.items.is_empty() {
+                    Ok(None)
+                } else {
+                    serde_json::to_string(&log.items).map(Some)
+                }
+            })
+            .collect::<serde_json::Result<Vec<Option<String>>>>()?;
+
+        let columns = vec![
+            int64s(rows.iter().map(|log| log.block_number as i64)),
+            strings(rows.iter().map(|log| log.transaction_hash.as_str())),
+            Arc::new(UInt32Array::from_iter_values(
+                rows.iter().map(|log| log.log_index),
+            )) as ArrayRef,
+            strings(rows.iter().map(|log| log.contract_address.as_str())),
+            optional_strings(rows.iter().map(|log| log.log_type.as_str())),
+            Arc::new(topics.finish()) as ArrayRef,
+            strings(rows.iter().map(|log| log.data.as_str())),
+            Arc::new(StringArray::from(items)) as ArrayRef,
+            Arc::new(BooleanArray::from_iter(
+                rows.iter().map(|log| Some(log.estimated_event_log)),
+            )) as ArrayRef,
+        ];
+        Ok(RecordBatch::try_new(Self::schema(), columns)?)
+    }
+
+    fn block_number(&self) -> i64 {
+        self.block_number as i64
+    }
+}
+
+const DEFAULT_MAX_OPEN_WRITERS: usize = 16;
+const DEFAULT_ROW_GROUP_SIZE: usize = 65_536;
+
+struct PartitionFile {
+    path: PathBuf,
+    writer: ArrowWriter<File>,
+    last_write: u64,
+}
+
+// Writes records to `<dir>/<table>/blocks_<start>_<end>_<part>.parquet`, one or more part
+// files per block range of `blocks_per_partition` blocks. At most `max_open_writers` files
+// are open at once; when another partition is needed the least recently written one is
+// closed, and rows that arrive for it later go to its next part file. Row groups are
+// flushed every `row_group_size` rows, so memory stays bounded. Existing files are never
+// replaced: part numbers continue after the parts already on disk.
+//
+// `finish` closes the open files and reports close errors. A writer dropped without it,
+// e.g. on an early return, still closes its files so each has a valid footer, but close
+// errors are then ignored.
+pub struct PartitionedParquetWriter<T> {
+    dir: PathBuf,
+    blocks_per_partition: i64,
+    max_open_writers: usize,
+    properties: WriterProperties,
+    writers: BTreeMap<i64, PartitionFile>,
+    next_part: BTreeMap<i64, u32>,
+    written: Vec<(i64, u32, PathBuf)>,
+    writes: u64,
+    _record: PhantomData<T>,
+}
+
+impl<T: ArrowRecord> PartitionedParquetWriter<T> {
+    pub fn new(dir: impl AsRef<Path>, blocks_per_partition: u64) -> Result<Self> {
+        if blocks_per_partition == 0 {
+            bail!("Partition size must be at least one block");
+        }
+
+        let dir = dir.as_ref().join(T::TABLE);
+        fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+
+        Ok(Self {
+            dir,
+            blocks_per_partition: blocks_per_partition.min(i64::MAX as u64) as i64,
+            max_open_writers: DEFAULT_MAX_OPEN_WRITERS,
+            properties: Self::properties(DEFAULT_ROW_GROUP_SIZE),
+            writers: BTreeMap::new(),
+            next_part: BTreeMap::new(),
+            written: Vec::new(),
+            writes: 0,
+            _record: PhantomData,
+        })
+    }
+
+    fn properties(row_group_size: usize) -> WriterProperties {
+        WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .set_max_row_group_size(row_group_size)
+            .build()
+    }
+
+    pub fn with_max_open_writers(mut self, max_open_writers: usize) -> Self {
+        self.max_open_writers = max_open_writers.max(1);
+        self
+    }
+
+    pub fn with_row_group_size(mut self, row_group_size: usize) -> Self {
+        self.properties = Self::properties(row_group_size.max(1));
+        self
+    }
+
+    pub fn partition_range(&self, block_number: i64) -> (i64, i64) {
+        let start = block_number.div_euclid(self.blocks_per_partition) * self.blocks_per_partition;
+        (start, start.saturating_add(self.blocks_per_partition - 1))
+    }
+
+    pub fn write(&mut self, rows: &[T]) -> Result<()> {
+        let mut partitions: BTreeMap<i64, Vec<usize>> = BTreeMap::new();
+        for (index, row) in rows.iter().enumerate() {
+            let (start, _) = self.partition_range(row.block_number());
+            partitions.entry(start).or_default().push(index);
+        }
+
+        let batch = T::record_batch(rows)?;
+
+        for (start, indices) in partitions {
+            let indices = UInt32Array::from_iter_values(indices.into_iter().map(|i| i as u32));
+            let partition = take_record_batch(&batch, &indices)?;
+
+            if !self.writers.contains_key(&start) {
+                if self.writers.len() >= self.max_open_writers {
+                    self.close_least_recent()?;
+                }
+                let file = self.create_part(start)?;
+                self.writers.insert(start, file);
+            }
+
+            self.writes += 1;
+            let file = self
+                .writers
+                .get_mut(&start)
+                .expect("partition writer was just opened");
+            file.last_write = self.writes;
+            file.writer
+                .write(&partition)
+                .with_context(|| format!("Failed to write {}", file.path.display()))?;
+        }
+
+        Ok(())
+    }
+
+    // Creates the next part file of a partition, skipping part numbers already on disk.
+    fn create_part(&mut self, start: i64) -> Result<PartitionFile> {
+        let end = start.saturating_add(self.blocks_per_partition - 1);
+        let part = self.next_part.entry(start).or_insert(0);
+
+        loop {
+            let path = self.dir.join(format!(
+                "blocks_{:012}_{:012}_{:04}.parquet",
+                start, end, part
+            ));
+            match File::create_new(&path) {
+                Ok(file) => {
+                    let writer =
+                        ArrowWriter::try_new(file, T::schema(), Some(self.properties.clone()))?;
+                    self.written.push((start, *part, path.clone()));
+                    *part += 1;
+                    return Ok(PartitionFile {
+                        path,
+                        writer,
+                        last_write: 0,
+                    });
+                }
+                Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => *part += 1,
+                Err(err) => {
+                    return Err(err).with_context(|| format!("Failed to create {}", path.display()))
+                }
+            }
+        }
+    }
+
+    fn close_least_recent(&mut self) -> Result<()> {
+        let Some(start) = self
+            .writers
+            .iter()
+            .min_by_key(|(_, file)| file.last_write)
+            .map(|(start, _)| *start)
+        else {
+            return Ok(());
+        };
+
+        if let Some(file) = self.writers.remove(&start) {
+            file.writer
+                .close()
+                .with_context(|| format!("Failed to finish {}", file.path.display()))?;
+        }
+        Ok(())
+    }
+
+    // Closes the open partition files and returns the paths of every file written, in
+    // block and part order.
+    pub fn finish(mut self) -> Result<Vec<PathBuf>> {
+        for (_, file) in std::mem::take(&mut self.writers) {
+            file.writer
+                .close()
+                .with_context(|| format!("Failed to finish {}", file.path.display()))?;
+        }
+
+        let mut written = std::mem::take(&mut self.written);
+        written.sort_by_key(|(start, part, _)| (*start, *part));
+        Ok(written.into_iter().map(|(_, _, path)| path).collect())
+    }
+}
+
+impl<T> Drop for PartitionedParquetWriter<T> {
+    fn drop(&mut self) {
+        for (_, file) in std::mem::take(&mut self.writers) {
+            let _ = file.writer.close();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::Array;
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+    use serde_json::json;
+
+    fn block(block_id: i64) -> BlockListItem {
+        serde_json::from_value(json!({
+            "base_fee_per_gas": "25000000000",
+            "block_id": block_id,
+            "block_proposer": "0x1",
+            "block_size": 1000,
+            "burnt_fees": "0.000105",
+            "datetime": "2024-01-01T00:00:00.000Z",
+            "reward": "9.6",
+            "total_transaction_count": 1,
+        }))
+        .unwrap()
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("rustsdk-export-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn row_groups(path: &Path) -> Vec<i64> {
+        let reader = SerializedFileReader::new(File::open(path).unwrap()).unwrap();
+        reader
+            .metadata()
+            .row_groups()
+            .iter()
+            .map(|group| group.num_rows())
+            .collect()
+    }
+
+    #[test]
+    fn converts_amounts_to_decimals() {
+        assert_eq!(
+            parse_decimal("9.6", KAIA_SCALE).unwrap(),
+            Some(9_600_000_000_000_000_000)
+        );
+        assert_eq!(parse_decimal("", KAIA_SCALE).unwrap(), None);
+        assert!(parse_decimal("1.0000000000000000001", KAIA_SCALE).is_err());
+
+        let batch = BlockListItem::record_batch(&[block(1), block(2)]).unwrap();
+        assert_eq!(batch.num_rows(), 2);
+        assert_eq!(batch.schema(), BlockListItem::schema());
+    }
+
+    #[test]
+    fn caps_open_writers_and_continues_in_part_files() {
+        let dir = temp_dir("parts");
+        let mut writer = PartitionedParquetWriter::<BlockListItem>::new(&dir, 10)
+            .unwrap()
+            .with_max_open_writers(1)
+            .with_row_group_size(2);
+
+        writer.write(&[block(1), block(2), block(3)]).unwrap();
+        writer.write(&[block(15)]).unwrap();
+        writer.write(&[block(4)]).unwrap();
+        let paths = writer.finish().unwrap();
+
+        let names: Vec<_> = paths
+            .iter()
+            .map(|path| path.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "blocks_000000000000_000000000009_0000.parquet",
+                "blocks_000000000000_000000000009_0001.parquet",
+                "blocks_000000000010_000000000019_0000.parquet",
+            ]
+        );
+        assert_eq!(row_groups(&paths[0]), [2, 1]);
+        assert_eq!(row_groups(&paths[1]), [1]);
+
+        // A second run adds part files instead of replacing the first run's files.
+        let mut writer = PartitionedParquetWriter::<BlockListItem>::new(&dir, 10).unwrap();
+        writer.write(&[block(5)]).unwrap();
+        let paths = writer.finish().unwrap();
+        assert_eq!(
+            paths[0].file_name().unwrap(),
+            "blocks_000000000000_000000000009_0002.parquet"
+        );
+        assert_eq!(
+            row_groups(&dir.join("blocks/blocks_000000000000_000000000009_0000.parquet")),
+            [2, 1]
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn closes_files_when_dropped_without_finish() {
+        let dir = temp_dir("dropped");
+        let mut writer = PartitionedParquetWriter::<BlockListItem>::new(&dir, 10).unwrap();
+        writer.write(&[block(1), block(12)]).unwrap();
+        drop(writer);
+
+        assert_eq!(
+            row_groups(&dir.join("blocks/blocks_000000000000_000000000009_0000.parquet")),
+            [1]
+        );
+        assert_eq!(
+            row_groups(&dir.join("blocks/blocks_000000000010_000000000019_0000.parquet")),
+            [1]
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn converts_datetimes_to_timestamps() {
+        let array =
+            timestamps(["2024-01-01T00:00:00.5Z", " ", "2024-01-01 00:00:01"].into_iter()).unwrap();
+        let array = array
+            .as_any()
+            .downcast_ref::<TimestampMicrosecondArray>()
+            .unwrap();
+
+        assert_eq!(array.value(0), 1_704_067_200_500_000);
+        assert!(array.is_null(1));
+        assert_eq!(array.value(2), 1_704_067_201_000_000);
+        assert!(timestamps(["yesterday"].into_iter()).is_err());
+    }
+}
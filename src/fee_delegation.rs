@@ -0,0 +1,320 @@
+use crate::datetime::{unix_timestamp, utc_date};
+use crate::portfolio::{parse_base_units, wei_to_kaia, KAIA_DECIMALS};
+use crate::{Address, FeeDelegation, KaiaScan, Transaction};
+use anyhow::Result;
+use futures::stream::TryStreamExt;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::ops::RangeInclusive;
+
+// Gas of the cheapest transaction; a fee implying less gas than this is inconsistent.
+const INTRINSIC_GAS: u128 = 21_000;
+// KaiaScan reports fees in KAIA and gas prices in Gwei (9 decimals). Fees are summed in wei
+// and converted to KAIA for the report.
+const GWEI_DECIMALS: u32 = 9;
+
+// Fees of partially delegated ("with ratio") transactions are kept apart: the listing has
+// no fee ratio, so only the full transaction fee is known, not the fee payer's share.
+#[derive(Debug, Clone, Serialize)]
+pub struct SponsoredSender {
+    pub address: String,
+    pub transactions: usize,
+    pub fees: f64,
+    pub partial_fees: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DailySponsorship {
+    pub date: String,
+    pub transactions: usize,
+    pub fees: f64,
+    pub partial_fees: f64,
+}
+
+// A sponsored transaction whose fee does not match its gas price: the fee in wei is not a
+// whole amount of gas of at least 21000 at `effective_gas_price`, or does not parse.
+#[derive(Debug, Clone, Serialize)]
+pub struct FeeMismatch {
+    pub transaction_hash: String,
+    pub transaction_fee: String,
+    pub effective_gas_price: String,
+    pub implied_gas: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FeeDelegationReport {
+    pub fee_payer: Address,
+    pub start_block: i64,
+    pub end_block: i64,
+    // Fully and partially sponsored transactions.
+    pub sponsored_transactions: usize,
+    // Fees in KAIA of fully delegated transactions, which the fee payer paid in full.
+    pub total_fees: f64,
+    // Partially delegated ("with ratio") transactions and their full transaction fees in
+    // KAIA. The fee payer paid a share of these fees that the listing does not reveal.
+    pub partially_sponsored_transactions: usize,
+    pub partial_fees: f64,
+    pub by_type: BTreeMap<String, usize>,
+    // Senders ordered by the fees paid for them, highest first.
+    pub top_senders: Vec<SponsoredSender>,
+    pub daily: Vec<DailySponsorship>,
+    pub fee_mismatches: Vec<FeeMismatch>,
+}
+
+// Running totals in wei: fully delegated fees and full fees of partially delegated ones.
+#[derive(Default)]
+struct Totals {
+    transactions: usize,
+    partial_transactions: usize,
+    fees: u128,
+    partial_fees: u128,
+}
+
+impl Totals {
+    fn add(&mut self, fee: u128, partial: bool) {
+        self.transactions += 1;
+        if partial {
+            self.partial_transactions += 1;
+            self.partial_fees += fee;
+        } else {
+            self.fees += fee;
+        }
+    }
+}
+
+fn check_fee(transaction: &Transaction, fee: Option<u128>) -> Option<FeeMismatch> {
+    let gas_price = parse_base_units(&transaction.effective_gas_price, GWEI_DECIMALS)
+        .filter(|price| *price > 0);
+
+    let consistent = match (fee, gas_price) {
+        (Some(fee), Some(price)) => fee % price == 0 && fee / price >= INTRINSIC_GAS,
+        (Some(fee), None) => fee == 0,
+        (None, _) => false,
+    };
+    if consistent {
+        return None;
+    }
+
+    Some(FeeMismatch {
+        transaction_hash: transaction.transaction_hash.clone(),
+        transaction_fee: transaction.transaction_fee.clone(),
+        effective_gas_price: transaction.effective_gas_price.clone(),
+        implied_gas: fee
+            .zip(gas_price)
+            .map(|(fee, price)| fee as f64 / price as f64),
+    })
+}
+
+impl KaiaScan {
+    // Summarizes the transactions in `block_range` whose fee `fee_payer` paid on behalf of
+    // another sender. Blocks are walked with `blocks_in_range`, and the transactions of
+    // non-empty blocks are fetched up to `batch_concurrency` blocks at a time.
+    pub async fn fee_delegation_report(
+        &self,
+        fee_payer: Address,
+        block_range: RangeInclusive<i64>,
+    ) -> Result<FeeDelegationReport> {
+        let (start_block, end_block) = (*block_range.start(), *block_range.end());
+        let payer = fee_payer.as_ref();
+
+        let mut report = FeeDelegationReport {
+            fee_payer: fee_payer.clone(),
+            start_block,
+            end_block,
+            sponsored_transactions: 0,
+            total_fees: 0.0,
+            partially_sponsored_transactions: 0,
+            partial_fees: 0.0,
+            by_type: BTreeMap::new(),
+            top_senders: Vec::new(),
+            daily: Vec::new(),
+            fee_mismatches: Vec::new(),
+        };
+        let mut totals = Totals::default();
+        let mut senders: HashMap<String, (String, Totals)> = HashMap::new();
+        let mut daily: BTreeMap<String, Totals> = BTreeMap::new();
+
+        let blocks = self
+            .blocks_in_range(block_range)
+            .try_filter(|block| futures::future::ready(block.total_transaction_count > 0))
+            .map_ok(|block| self.block_transactions(block.block_id))
+            .try_buffered(self.batch_concurrency);
+        futures::pin_mut!(blocks);
+
+        while let Some(transactions) = blocks.try_next().await? {
+            for transaction in transactions {
+                if !transaction.fee_payer.eq_ignore_ascii_case(payer)
+                    || transaction.from.eq_ignore_ascii_case(payer)
+                {
+                    continue;
+                }
+
+                let tx_type = transaction.tx_type();
+                let partial = tx_type.fee_delegation == FeeDelegation::Partial;
+                let parsed_fee = parse_base_units(&transaction.transaction_fee, KAIA_DECIMALS);
+                let fee = parsed_fee.unwrap_or(0);
+
+                totals.add(fee, partial);
+                *report.by_type.entry(tx_type.to_string()).or_default() += 1;
+                senders
+                    .entry(transaction.from.to_ascii_lowercase())
+                    .or_insert_with(|| (transaction.from.clone(), Totals::default()))
+                    .1
+                    .add(fee, partial);
+
+                let date = unix_timestamp(&transaction.datetime)
+                    .and_then(utc_date)
+                    .unwrap_or_else(|| transaction.datetime.clone());
+                daily.entry(date).or_default().add(fee, partial);
+
+                if let Some(mismatch) = check_fee(&transaction, parsed_fee) {
+                    report.fee_mismatches.push(mismatch);
+                }
+            }
+        }
+
+        report.sponsored_transactions = totals.transactions;
+        report.total_fees = wei_to_kaia(totals.fees);
+        report.partial_fees = wei_to_kaia(totals.partial_fees);
+        report.partially_sponsored_transactions = totals.partial_transactions;
+
+        let mut top_senders: Vec<(String, Totals)> = senders.into_values().collect();
+        top_senders.sort_by(|(left_address, left), (right_address, right)| {
+            (right.fees + right.partial_fees)
+                .cmp(&(left.fees + left.partial_fees))
+                .then_with(|| right.transactions.cmp(&left.transactions))
+                .then_with(|| left_address.cmp(right_address))
+        });
+        report.top_senders = top_senders
+            .into_iter()
+            .map(|(address, totals)| SponsoredSender {
+                address,
+                transactions: totals.transactions,
+                fees: wei_to_kaia(totals.fees),
+                partial_fees: wei_to_kaia(totals.partial_fees),
+            })
+            .collect();
+        report.daily = daily
+            .into_iter()
+            .map(|(date, totals)| DailySponsorship {
+                date,
+                transactions: totals.transactions,
+                fees: wei_to_kaia(totals.fees),
+                partial_fees: wei_to_kaia(totals.partial_fees),
+            })
+            .collect();
+
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const PAYER: &str = "0xpayer";
+
+    fn transaction(
+        hash: &str,
+        from: &str,
+        tx_type: &str,
+        fee: &str,
+        gas_price: &str,
+    ) -> Transaction {
+        serde_json::from_value(json!({
+            "amount": "0",
+            "block_id": 7,
+            "datetime": "2024-01-02T03:04:05Z",
+            "effective_gas_price": gas_price,
+            "fee_payer": PAYER,
+            "from": from,
+            "method_id": "",
+            "status": { "status": "Success" },
+            "to": "0xcontract",
+            "transaction_fee": fee,
+            "transaction_hash": hash,
+            "transaction_index": 0,
+            "transaction_type": tx_type,
+        }))
+        .unwrap()
+    }
+
+    fn paging() -> serde_json::Value {
+        json!({ "current_page": 1, "last": true, "total_count": 1, "total_page": 1 })
+    }
+
+    #[test]
+    fn checks_fees_in_exact_wei() {
+        let check = |fee: &str, gas_price: &str| {
+            let transaction = transaction("0x1", "0xa", "0x09", fee, gas_price);
+            check_fee(
+                &transaction,
+                parse_base_units(&transaction.transaction_fee, KAIA_DECIMALS),
+            )
+        };
+
+        // 21000 gas at 25 Gwei, and 123457 gas at 27.5 Gwei.
+        assert!(check("0.000525", "25").is_none());
+        assert!(check("0.0033950675", "27.5").is_none());
+        // One wei off is a whole-gas mismatch, which a relative tolerance would miss.
+        let mismatch = check("0.000525000000000001", "25").unwrap();
+        assert!(mismatch.implied_gas.unwrap() > 20_999.0);
+        assert!(check("0.0000025", "25").is_some());
+        assert!(check("0", "").is_none());
+        assert!(check("abc", "25").unwrap().implied_gas.is_none());
+    }
+
+    #[tokio::test]
+    async fn reports_partially_delegated_fees_separately() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock(
+                "GET",
+                "/api/v1/blocks?blockNumberStart=7&blockNumberEnd=7&page=1&size=2000",
+            )
+            .with_body(
+                json!({
+                    "paging": paging(),
+                    "results": [{
+                        "base_fee_per_gas": "25", "block_id": 7, "block_proposer": "",
+                        "block_size": 1, "burnt_fees": "0", "datetime": "2024-01-02T03:04:05Z",
+                        "reward": "0", "total_transaction_count": 3,
+                    }],
+                })
+                .to_string(),
+            )
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/api/v1/blocks/7/transactions?page=1&size=2000")
+            .with_body(
+                json!({
+                    "paging": paging(),
+                    "results": [
+                        transaction("0x1", "0xa", "FeeDelegatedValueTransfer", "0.000525", "25"),
+                        transaction("0x2", "0xa", "0x0a", "0.00105", "25"),
+                        transaction("0x3", PAYER, "FeeDelegatedValueTransfer", "0.000525", "25"),
+                    ],
+                })
+                .to_string(),
+            )
+            .create_async()
+            .await;
+
+        let client = KaiaScan::new(false).unwrap().with_base_url(server.url());
+        let report = client
+            .fee_delegation_report(Address::new(PAYER), 7..=7)
+            .await
+            .unwrap();
+
+        assert_eq!(report.sponsored_transactions, 2);
+        assert_eq!(report.partially_sponsored_transactions, 1);
+        assert_eq!(report.total_fees, 0.000525);
+        assert_eq!(report.partial_fees, 0.00105);
+        assert_eq!(report.top_senders[0].fees, 0.000525);
+        assert_eq!(report.top_senders[0].partial_fees, 0.00105);
+        assert_eq!(report.daily[0].date, "2024-01-02");
+        assert!(report.fee_mismatches.is_empty());
+    }
+}
@@ -0,0 +1,501 @@
+use crate::{
+    BlockRewardsResponse, BurnSummary, InternalTransaction, InternalTransactionsResponse, KaiaScan,
+    Transaction,
+};
+use anyhow::{Context, Result};
+use futures::stream::{self, StreamExt};
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+const BLOCK_PAGE_SIZE: i32 = 2000;
+
+// Persists the last block the indexer fully processed. `save` is only called after every
+// handler accepted the block, so a restarted indexer continues with the next one.
+//
+// Delivery is at least once: a block whose handlers ran but whose checkpoint was not saved
+// (a crash, a failed save) is handed to every handler again after a restart. Handlers get
+// the block number with each call so they can write idempotently, e.g. upsert by block.
+pub trait CheckpointStore {
+    fn load(&mut self) -> Result<Option<i64>>;
+    fn save(&mut self, block_number: i64) -> Result<()>;
+}
+
+#[derive(Debug, Default)]
+pub struct MemoryCheckpointStore {
+    block_number: Option<i64>,
+}
+
+impl CheckpointStore for MemoryCheckpointStore {
+    fn load(&mut self) -> Result<Option<i64>> {
+        Ok(self.block_number)
+    }
+
+    fn save(&mut self, block_number: i64) -> Result<()> {
+        self.block_number = Some(block_number);
+        Ok(())
+    }
+}
+
+// Keeps the checkpoint as a block number in a text file. Writes go through a temporary file
+// and a rename, so a crash never leaves a partial checkpoint behind.
+#[derive(Debug)]
+pub struct FileCheckpointStore {
+    path: PathBuf,
+}
+
+impl FileCheckpointStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl CheckpointStore for FileCheckpointStore {
+    fn load(&mut self) -> Result<Option<i64>> {
+        match fs::read_to_string(&self.path) {
+            Ok(contents) => {
+                let block_number = contents
+                    .trim()
+                    .parse()
+                    .with_context(|| format!("Invalid checkpoint in {}", self.path.display()))?;
+                Ok(Some(block_number))
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err)
+                .with_context(|| format!("Failed to read checkpoint {}", self.path.display())),
+        }
+    }
+
+    fn save(&mut self, block_number: i64) -> Result<()> {
+        let mut temporary = self.path.clone().into_os_string();
+        temporary.push(".tmp");
+        let temporary = PathBuf::from(temporary);
+
+        fs::write(&temporary, format!("{}\n", block_number))
+            .with_context(|| format!("Failed to write checkpoint {}", temporary.display()))?;
+        fs::rename(&temporary, &self.path)
+            .with_context(|| format!("Failed to write checkpoint {}", self.path.display()))
+    }
+}
+
+// One handler trait per data kind. The indexer only fetches the kinds that have a handler.
+pub trait TransactionHandler {
+    fn handle_transactions(
+        &mut self,
+        block_number: i64,
+        transactions: &[Transaction],
+    ) -> Result<()>;
+}
+
+pub trait InternalTransactionHandler {
+    fn handle_internal_transactions(
+        &mut self,
+        block_number: i64,
+        internal_transactions: &[InternalTransaction],
+    ) -> Result<()>;
+}
+
+pub trait RewardHandler {
+    fn handle_rewards(&mut self, block_number: i64, rewards: &BlockRewardsResponse) -> Result<()>;
+}
+
+pub trait BurnHandler {
+    fn handle_burns(&mut self, block_number: i64, burns: &BurnSummary) -> Result<()>;
+}
+
+impl<F> TransactionHandler for F
+where
+    F: FnMut(i64, &[Transaction]) -> Result<()>,
+{
+    fn handle_transactions(
+        &mut self,
+        block_number: i64,
+        transactions: &[Transaction],
+    ) -> Result<()> {
+        self(block_number, transactions)
+    }
+}
+
+impl<F> InternalTransactionHandler for F
+where
+    F: FnMut(i64, &[InternalTransaction]) -> Result<()>,
+{
+    fn handle_internal_transactions(
+        &mut self,
+        block_number: i64,
+        internal_transactions: &[InternalTransaction],
+    ) -> Result<()> {
+        self(block_number, internal_transactions)
+    }
+}
+
+impl<F> RewardHandler for F
+where
+    F: FnMut(i64, &BlockRewardsResponse) -> Result<()>,
+{
+    fn handle_rewards(&mut self, block_number: i64, rewards: &BlockRewardsResponse) -> Result<()> {
+        self(block_number, rewards)
+    }
+}
+
+impl<F> BurnHandler for F
+where
+    F: FnMut(i64, &BurnSummary) -> Result<()>,
+{
+    fn handle_burns(&mut self, block_number: i64, burns: &BurnSummary) -> Result<()> {
+        self(block_number, burns)
+    }
+}
+
+// Data fetched for one block. Kinds without a handler are left out.
+struct BlockData {
+    block_number: i64,
+    transactions: Option<Vec<Transaction>>,
+    internal_transactions: Option<Vec<InternalTransaction>>,
+    rewards: Option<BlockRewardsResponse>,
+    burns: Option<BurnSummary>,
+}
+
+impl KaiaScan {
+    // Every internal transaction of a block, across all pages.
+    pub async fn block_internal_transactions(
+        &self,
+        block_number: i64,
+    ) -> Result<Vec<InternalTransaction>> {
+        let mut internal_transactions = Vec::new();
+        let mut page = 1;
+
+        loop {
+            let response: InternalTransactionsResponse = self
+                .get_internal_transactions_of_block(block_number, Some(page), Some(BLOCK_PAGE_SIZE))
+                .await?;
+            internal_transactions.extend(response.results);
+
+            if response.paging.last || page as i64 >= response.paging.total_page {
+                return Ok(internal_transactions);
+            }
+            page += 1;
+        }
+    }
+
+    // Creates an indexer that starts at `start_block`, or after the checkpoint in
+    // `checkpoints` if one was saved by an earlier run.
+    pub fn indexer<'a, C: CheckpointStore>(
+        &'a self,
+        start_block: i64,
+        checkpoints: C,
+    ) -> Indexer<'a, C> {
+        Indexer {
+            client: self,
+            start_block,
+            checkpoints,
+            concurrency: self.batch_concurrency,
+            poll_interval: None,
+            transaction_handlers: Vec::new(),
+            internal_transaction_handlers: Vec::new(),
+            reward_handlers: Vec::new(),
+            burn_handlers: Vec::new(),
+            accepted: None,
+        }
+    }
+}
+
+// Backfills blocks from a start block to the chain head and then follows new blocks. Up to
+// `concurrency` blocks are fetched at once, but handlers see blocks strictly in order and
+// the checkpoint advances only after all of them accepted a block. When a handler fails,
+// the block is retried from that handler on the next `backfill`; see `CheckpointStore` for
+// what happens across restarts.
+pub struct Indexer<'a, C> {
+    client: &'a KaiaScan,
+    start_block: i64,
+    checkpoints: C,
+    concurrency: usize,
+    poll_interval: Option<Duration>,
+    transaction_handlers: Vec<Box<dyn TransactionHandler + Send + 'a>>,
+    internal_transaction_handlers: Vec<Box<dyn InternalTransactionHandler + Send + 'a>>,
+    reward_handlers: Vec<Box<dyn RewardHandler + Send + 'a>>,
+    burn_handlers: Vec<Box<dyn BurnHandler + Send + 'a>>,
+    // Block being committed and how many of its handlers accepted it so far.
+    accepted: Option<(i64, usize)>,
+}
+
+impl<'a, C: CheckpointStore> Indexer<'a, C> {
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    // Overrides the head poll interval, which otherwise follows the network's block time.
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = Some(poll_interval);
+        self
+    }
+
+    pub fn with_transaction_handler(
+        mut self,
+        handler: impl TransactionHandler + Send + 'a,
+    ) -> Self {
+        self.transaction_handlers.push(Box::new(handler));
+        self
+    }
+
+    pub fn with_internal_transaction_handler(
+        mut self,
+        handler: impl InternalTransactionHandler + Send + 'a,
+    ) -> Self {
+        self.internal_transaction_handlers.push(Box::new(handler));
+        self
+    }
+
+    pub fn with_reward_handler(mut self, handler: impl RewardHandler + Send + 'a) -> Self {
+        self.reward_handlers.push(Box::new(handler));
+        self
+    }
+
+    pub fn with_burn_handler(mut self, handler: impl BurnHandler + Send + 'a) -> Self {
+        self.burn_handlers.push(Box::new(handler));
+        self
+    }
+
+    pub fn checkpoints(&mut self) -> &mut C {
+        &mut self.checkpoints
+    }
+
+    // The first block that has not been processed yet.
+    pub fn next_block(&mut self) -> Result<i64> {
+        Ok(match self.checkpoints.load()? {
+            Some(checkpoint) => self.start_block.max(checkpoint.saturating_add(1)),
+            None => self.start_block,
+        })
+    }
+
+    // Processes every unprocessed block up to and including `end` and returns the number
+    // of blocks processed. Stops at the first failed fetch, handler or checkpoint save; the
+    // failed block is retried on the next call.
+    pub async fn backfill(&mut self, end: i64) -> Result<u64> {
+        let start = self.next_block()?;
+        if start > end {
+            return Ok(0);
+        }
+
+        let client = self.client;
+        let kinds = (
+            !self.transaction_handlers.is_empty(),
+            !self.internal_transaction_handlers.is_empty(),
+            !self.reward_handlers.is_empty(),
+            !self.burn_handlers.is_empty(),
+        );
+        let blocks = stream::iter(start..=end)
+            .map(|block_number| fetch_block(client, block_number, kinds))
+            .buffered(self.concurrency);
+        futures::pin_mut!(blocks);
+
+        let mut processed = 0;
+        while let Some(block) = blocks.next().await {
+            // Handlers and the checkpoint save run without an await in between, so dropping
+            // the future cannot separate a processed block from its checkpoint.
+            self.commit(block?)?;
+            processed += 1;
+        }
+
+        Ok(processed)
+    }
+
+    // Backfills to the current head and then keeps following new blocks. Only returns on
+    // error; drop the future to stop. A restarted indexer resumes after the checkpoint.
+    pub async fn run(&mut self) -> Result<()> {
+        loop {
+            let head = self.client.get_latest_block().await?.block_id;
+            let processed = self.backfill(head).await?;
+
+            if processed == 0 {
+                let poll_interval = match self.poll_interval {
+                    Some(poll_interval) => poll_interval,
+                    None => self.client.block_poll_interval().await,
+                };
+                tokio::time::sleep(poll_interval).await;
+            }
+        }
+    }
+
+    fn commit(&mut self, block: BlockData) -> Result<()> {
+        let block_number = block.block_number;
+        let Self {
+            checkpoints,
+            transaction_handlers,
+            internal_transaction_handlers,
+            reward_handlers,
+            burn_handlers,
+            accepted,
+            ..
+        } = self;
+
+        // Handlers that accepted this block before another handler failed are not called again
+        // when the block is retried by this indexer.
+        let skip = match *accepted {
+            Some((number, count)) if number == block_number => count,
+            _ => 0,
+        };
+        let mut position = 0;
+        let mut deliver = |handle: &mut dyn FnMut() -> Result<()>| -> Result<()> {
+            position += 1;
+            if position > skip {
+                handle()?;
+                *accepted = Some((block_number, position));
+            }
+            Ok(())
+        };
+
+        if let Some(transactions) = &block.transactions {
+            for handler in transaction_handlers {
+                deliver(&mut || handler.handle_transactions(block_number, transactions))?;
+            }
+        }
+        if let Some(internal_transactions) = &block.internal_transactions {
+            for handler in internal_transaction_handlers {
+                deliver(&mut || {
+                    handler.handle_internal_transactions(block_number, internal_transactions)
+                })?;
+            }
+        }
+        if let Some(rewards) = &block.rewards {
+            for handler in reward_handlers {
+                deliver(&mut || handler.handle_rewards(block_number, rewards))?;
+            }
+        }
+        if let Some(burns) = &block.burns {
+            for handler in burn_handlers {
+                deliver(&mut || handler.handle_burns(block_number, burns))?;
+            }
+        }
+
+        checkpoints.save(block_number)?;
+        *accepted = None;
+        Ok(())
+    }
+}
+
+async fn fetch_block(
+    client: &KaiaScan,
+    block_number: i64,
+    (transactions, internal_transactions, rewards, burns): (bool, bool, bool, bool),
+) -> Result<BlockData> {
+    let (transactions, internal_transactions, rewards, burns) = futures::try_join!(
+        async {
+            if transactions {
+                client.block_transactions(block_number).await.map(Some)
+            } else {
+                Ok(None)
+            }
+        },
+        async {
+            if internal_transactions {
+                client
+                    .block_internal_transactions(block_number)
+                    .await
+                    .map(Some)
+            } else {
+                Ok(None)
+            }
+        },
+        async {
+            if rewards {
+                client.get_block_rewards(block_number).await.map(Some)
+            } else {
+                Ok(None)
+            }
+        },
+        async {
+            if burns {
+                client.get_block_burns(block_number).await.map(Some)
+            } else {
+                Ok(None)
+            }
+        },
+    )
+    .with_context(|| format!("Failed to fetch block {}", block_number))?;
+
+    Ok(BlockData {
+        block_number,
+        transactions,
+        internal_transactions,
+        rewards,
+        burns,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::bail;
+    use serde_json::json;
+    use std::sync::{Arc, Mutex};
+
+    async fn mock_rewards(server: &mut mockito::Server, block_number: i64) {
+        server
+            .mock(
+                "GET",
+                format!("/api/v1/blocks/{}/rewards", block_number).as_str(),
+            )
+            .with_body(
+                json!({
+                    "burnt_fee": "0",
+                    "distributions": [],
+                    "minted": "9.6",
+                    "recipients": [],
+                    "total_fee": "0",
+                })
+                .to_string(),
+            )
+            .create_async()
+            .await;
+    }
+
+    #[tokio::test]
+    async fn retries_a_failed_block_from_the_failed_handler() {
+        let mut server = mockito::Server::new_async().await;
+        mock_rewards(&mut server, 5).await;
+        mock_rewards(&mut server, 6).await;
+        let client = KaiaScan::new(false).unwrap().with_base_url(server.url());
+
+        let first = Arc::new(Mutex::new(Vec::new()));
+        let second = Arc::new(Mutex::new(Vec::new()));
+        let (first_calls, second_calls) = (first.clone(), second.clone());
+        let mut failures = 1;
+
+        let mut indexer = client
+            .indexer(5, MemoryCheckpointStore::default())
+            .with_concurrency(1)
+            .with_reward_handler(move |block: i64, _: &BlockRewardsResponse| {
+                first_calls.lock().unwrap().push(block);
+                Ok(())
+            })
+            .with_reward_handler(move |block: i64, _: &BlockRewardsResponse| {
+                if block == 6 && failures > 0 {
+                    failures -= 1;
+                    bail!("database unavailable");
+                }
+                second_calls.lock().unwrap().push(block);
+                Ok(())
+            });
+
+        assert!(indexer.backfill(6).await.is_err());
+        assert_eq!(indexer.checkpoints().load().unwrap(), Some(5));
+
+        assert_eq!(indexer.backfill(6).await.unwrap(), 1);
+        assert_eq!(indexer.checkpoints().load().unwrap(), Some(6));
+        assert_eq!(*first.lock().unwrap(), [5, 6]);
+        assert_eq!(*second.lock().unwrap(), [5, 6]);
+    }
+
+    #[test]
+    fn file_checkpoints_survive_a_reload() {
+        let path = std::env::temp_dir().join(format!("rustsdk-checkpoint-{}", std::process::id()));
+        let mut store = FileCheckpointStore::new(&path);
+        assert_eq!(store.load().unwrap(), None);
+
+        store.save(42).unwrap();
+        assert_eq!(FileCheckpointStore::new(&path).load().unwrap(), Some(42));
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -1,21 +1,98 @@
 use anyhow::{Context, Result};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
+use std::sync::{Arc, RwLock};
 use std::time::Duration;
 
+pub mod abi;
+mod account_watch;
+mod batch;
+mod block_range;
+mod bytecode;
+mod calldata;
+#[cfg(feature = "config")]
+mod config;
+mod contract_source;
+mod cost_basis;
+mod datetime;
+mod error;
+mod events;
+#[cfg(feature = "arrow")]
+pub mod export;
+mod fee_delegation;
+mod indexer;
+mod portfolio;
+mod prices;
+mod proxy;
+mod rate_limit;
+mod signature;
+#[cfg(any(feature = "sqlite", feature = "postgres"))]
+pub mod sink;
+mod standards;
+mod transfers;
+mod tx_type;
+mod validator_rewards;
+mod wait;
+mod watch;
+
+pub use abi::{keccak256, Abi, AbiEvent, AbiFunction, AbiParam, AbiType, AbiValue};
+pub use account_watch::{
+    AccountCursor, AccountEvent, AccountWatcher, CursorStore, FeedCursor, MemoryCursorStore,
+};
+pub use bytecode::{
+    compare_creation_code, metadata_range, strip_metadata, BuildArtifact, BytecodeComparison,
+    BytecodeMatch,
+};
+pub use calldata::{decode_call, CalldataDecoding, DecodedCall, SelectorDatabase};
+#[cfg(feature = "config")]
+pub use config::{Config, Profile};
+pub use contract_source::{OptimizationSettings, SourceFile};
+pub use cost_basis::{
+    Acquisition, CostBasisMethod, CostBasisReport, Disposal, FeePayment, OpenPosition, SkippedToken,
+};
+pub use error::ApiError;
+pub use events::{decode_log, DecodedEvent, EventRegistry};
+pub use fee_delegation::{DailySponsorship, FeeDelegationReport, FeeMismatch, SponsoredSender};
+pub use indexer::{
+    BurnHandler, CheckpointStore, FileCheckpointStore, Indexer, InternalTransactionHandler,
+    MemoryCheckpointStore, RewardHandler, TransactionHandler,
+};
+pub use portfolio::{
+    format_units, NativeHolding, NftCollection, NftItemHolding, Portfolio, TokenHolding,
+};
+pub use prices::{Asset, KaiaInfoPrices, PriceSource, StaticPrices};
+pub use proxy::{ImplementationSource, ProxyHop, ProxyKind, ResolvedContract};
+pub use rate_limit::RateLimiter;
+pub use signature::EventSignature;
+pub use standards::{
+    classify_event_log, classify_method_id, decode_standard_event, standard_events,
+    standard_methods, StandardEvent, StandardEventMatch, StandardMethod, StandardMethodMatch,
+    TokenStandard,
+};
+pub use transfers::TransferPage;
+pub use tx_type::{FeeDelegation, TransactionKind, TransactionType};
+pub use validator_rewards::{DailyRewards, ValidatorRewards};
+pub use wait::{WaitError, WaitOptions};
+
 const MAINNET_BASE_URL: &str = "https://mainnet-oapi.kaiascan.io/";
 const TESTNET_BASE_URL: &str = "https://kairos-oapi.kaiscan.io/";
 
+#[derive(Clone)]
 pub struct KaiaScan {
     client: Client,
     base_url: String,
+    api_key: String,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    batch_concurrency: usize,
+    events: Arc<RwLock<EventRegistry>>,
+    selectors: Arc<RwLock<SelectorDatabase>>,
 }
 
-const AUTH_TOKEN: &'static str = "";
+const AUTH_TOKEN: &str = "";
 const TOKENS_ENDPOINT: &str = "api/v1/tokens";
 const NFTS_ENDPOINT: &str = "api/v1/nfts";
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Address(String);
 
 impl Address {
@@ -30,7 +107,7 @@ impl AsRef<str> for Address {
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct AccountKeyHistory {
     pub address: String,
     pub key_type: String,
@@ -43,7 +120,7 @@ pub struct AccountKeyHistoryResponse {
     pub paging: Paging,
     pub results: Vec<AccountKeyHistory>,
 }
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct KlayPrice {
     pub btc_price: String,
     pub market_cap: String,
@@ -53,7 +130,7 @@ pub struct KlayPrice {
     pub volume: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct KaiaSummary {
     pub avg_block_time1h: String,
     pub avg_block_time24h: String,
@@ -61,13 +138,13 @@ pub struct KaiaSummary {
     pub consensus_node: i32,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct KaiaInfoResponse {
     pub klay_price: KlayPrice,
     pub summary: KaiaSummary,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct BlockRewardRecipient {
     pub address: String,
     pub amount: String,
@@ -76,14 +153,14 @@ pub struct BlockRewardRecipient {
     pub reward_type: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct BlockRewardDistribution {
     pub amount: String,
     #[serde(rename = "type")]
     pub distribution_type: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct BlockRewardsResponse {
     pub burnt_fee: String,
     pub distributions: Vec<BlockRewardDistribution>,
@@ -98,7 +175,7 @@ pub struct BlockRewardsResponse {
 //     pub amount: String,
 //     pub datetime: String,
 // }
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct BurnSummary {
     pub accumulate_burnt: String,
     pub accumulate_burnt_fees: String,
@@ -127,7 +204,7 @@ pub struct BlockRewardDetail {
     pub reward_type: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct InternalTransaction {
     pub block_id: i64,
     pub transaction_hash: String,
@@ -137,13 +214,13 @@ pub struct InternalTransaction {
     pub datetime: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct InternalTransactionsResponse {
     pub paging: Paging,
     pub results: Vec<InternalTransaction>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct BlockDetails {
     pub block_id: i64,
     pub datetime: String,
@@ -156,14 +233,14 @@ pub struct BlocksResponse {
     pub blocks: Vec<BlockDetails>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct BlockReward {
     pub minted: String,
     pub total_fee: String,
     pub burnt_fee: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Transaction {
     pub amount: String,
     pub block_id: i64,
@@ -187,18 +264,18 @@ pub struct Transaction {
     pub transaction_type: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct TransactionStatus {
     pub status: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct TransactionsResponse {
     pub paging: Paging,
     pub results: Vec<Transaction>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Paging {
     #[serde(rename = "current_page")]
     pub current_page: i64,
@@ -216,7 +293,7 @@ pub struct TransactionReceiptStatus {
     pub tx_hash: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct TransactionDetails {
     pub tx_hash: String,
     pub block_id: i64,
@@ -226,13 +303,35 @@ pub struct TransactionDetails {
     pub gas_used: String,
     pub status: String,
 }
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct ContractSourceCode {
     pub contract_address: String,
+    // A single Solidity file, or solc standard-JSON input for multi-file contracts.
     pub source_code: String,
+    #[serde(default, alias = "contractName")]
+    pub contract_name: Option<String>,
+    #[serde(default, alias = "compilerVersion")]
+    pub compiler_version: Option<String>,
+    #[serde(default, alias = "optimizationFlag", alias = "optimization_flag")]
+    pub optimizer_enabled: Option<bool>,
+    #[serde(default, alias = "optimizationRuns", alias = "optimization_runs")]
+    pub optimizer_runs: Option<u64>,
+    #[serde(default, alias = "evmVersion")]
+    pub evm_version: Option<String>,
+    #[serde(default, alias = "licenseType", alias = "license_type")]
+    pub license: Option<String>,
+    // The contract ABI as a JSON string.
+    #[serde(default)]
+    pub abi: Option<String>,
+    #[serde(default, alias = "constructorArguments")]
+    pub constructor_arguments: Option<String>,
+    #[serde(default, alias = "isProxy")]
+    pub is_proxy: Option<bool>,
+    #[serde(default, alias = "implementationAddress")]
+    pub implementation_address: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct LatestBlock {
     pub block_id: i64,
     pub datetime: String,
@@ -241,7 +340,6 @@ pub struct LatestBlock {
     pub block_reward: BlockReward,
 }
 
-struct KeyHistoryEntry;
 #[derive(Debug, Serialize, Deserialize)]
 pub struct EventLogEntry {
     pub results: Vec<EventLogResult>,
@@ -290,7 +388,7 @@ pub struct Pagings {
     pub total_page: u32,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct TokenTransferEntry {
     #[serde(rename = "results")]
     pub results: Vec<TokenTransfer>,
@@ -327,13 +425,24 @@ pub struct TokenTransfer {
     pub amount: u64,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ContractInfo {
     pub contract_address: String,
     pub contract_type: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AccountInfo {
+    pub address: String,
+    #[serde(default)]
+    pub account_type: String,
+    // Native balance in KAIA.
+    pub balance: String,
+    #[serde(default)]
+    pub total_transaction_count: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
 pub struct NftBalanceEntry {
     pub results: Vec<NftBalance>,
 
@@ -353,7 +462,7 @@ pub struct NftBalance {
     pub token_count: u64,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct NftTransferEntry {
     #[serde(rename = "results")]
     pub results: Vec<NftTransfer>,
@@ -486,7 +595,7 @@ pub struct TransactionParameter {
     pub value: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct TokenInfo {
     #[serde(rename = "contract_type")]
     pub contract_type: String,
@@ -506,13 +615,13 @@ pub struct TokenInfo {
     pub total_burns: i64,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct BlocksListResponse {
     pub paging: Paging,
     pub results: Vec<BlockListItem>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct BlockListItem {
     #[serde(rename = "base_fee_per_gas")]
     pub base_fee_per_gas: String,
@@ -543,7 +652,48 @@ impl KaiaScan {
             .build()
             .context("Failed to create HTTP client")?;
 
-        Ok(Self { client, base_url })
+        Ok(Self {
+            client,
+            base_url,
+            api_key: AUTH_TOKEN.to_string(),
+            rate_limiter: None,
+            batch_concurrency: batch::DEFAULT_BATCH_CONCURRENCY,
+            events: Arc::new(RwLock::new(EventRegistry::standard())),
+            selectors: Arc::new(RwLock::new(SelectorDatabase::builtin())),
+        })
+    }
+
+    pub fn with_api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.api_key = api_key.into();
+        self
+    }
+
+    // Points the client at another KaiaScan-compatible API, e.g. a private explorer.
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        let mut base_url = base_url.into();
+        if !base_url.ends_with('/') {
+            base_url.push('/');
+        }
+        self.base_url = base_url;
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Result<Self> {
+        self.client = Client::builder()
+            .timeout(timeout)
+            .build()
+            .context("Failed to create HTTP client")?;
+        Ok(self)
+    }
+
+    pub fn with_rate_limit(mut self, requests_per_second: u32) -> Self {
+        self.rate_limiter = Some(Arc::new(RateLimiter::new(requests_per_second)));
+        self
+    }
+
+    pub fn with_batch_concurrency(mut self, batch_concurrency: usize) -> Self {
+        self.batch_concurrency = batch_concurrency.max(1);
+        self
     }
 
     async fn fetch_api<T>(&self, url: &str) -> Result<T>
@@ -552,22 +702,31 @@ impl KaiaScan {
     {
         let _url = format!("{}{}", self.base_url, url);
 
+        if let Some(rate_limiter) = &self.rate_limiter {
+            rate_limiter.acquire().await;
+        }
+
         let response = self
             .client
             .get(_url)
             .header("Accept", "*/*")
-            .header("Authorization", format!("Bearer {}", AUTH_TOKEN))
+            .header("Authorization", format!("Bearer {}", self.api_key))
             .send()
             .await
-            .context("Failed to make HTTP request")?;
+            .map_err(ApiError::Request)?;
 
         let status = response.status();
-        let body_text: serde_json::Value = response.json().await?;
-        println!("📝 Response Status: {}", status);
-        println!("📝 Response Body: {}", body_text);
+        let body_text = response.text().await.map_err(ApiError::Request)?;
+
+        if !status.is_success() {
+            return Err(ApiError::Status {
+                status: status.as_u16(),
+                body: body_text,
+            }
+            .into());
+        }
 
-        let api_response: T =
-            serde_json::from_value(body_text).context("Failed to parse API response JSON")?;
+        let api_response: T = serde_json::from_str(&body_text).map_err(ApiError::Decode)?;
 
         Ok(api_response)
     }
@@ -602,8 +761,8 @@ impl KaiaScan {
         self.fetch_api(&endpoint).await
     }
     pub async fn get_latest_block(&self) -> Result<LatestBlock> {
-        let url = format!("api/v1/blocks/latest");
-        self.fetch_api(&url).await
+        let url = "api/v1/blocks/latest";
+        self.fetch_api(url).await
     }
 
     pub async fn get_block(&self, block_number: i64) -> Result<BlockDetails> {
@@ -647,6 +806,29 @@ impl KaiaScan {
         self.fetch_api(&url).await
     }
 
+    pub async fn get_transactions_of_block_page(
+        &self,
+        block_number: i64,
+        page: Option<i32>,
+        size: Option<i32>,
+    ) -> Result<TransactionsResponse> {
+        let page = page.unwrap_or(1);
+        let size = size.unwrap_or(20);
+
+        if page < 1 {
+            return Err(anyhow::anyhow!("Page must be >= 1"));
+        }
+        if !(1..=2000).contains(&size) {
+            return Err(anyhow::anyhow!("Size must be between 1 and 2000"));
+        }
+
+        let url = format!(
+            "api/v1/blocks/{}/transactions?page={}&size={}",
+            block_number, page, size
+        );
+        self.fetch_api(&url).await
+    }
+
     pub async fn get_transaction_receipt_status(
         &self,
         transaction_hash: &str,
@@ -674,6 +856,11 @@ impl KaiaScan {
         self.fetch_api(&url).await
     }
 
+    pub async fn get_account(&self, account_address: Address) -> Result<AccountInfo> {
+        let url = format!("api/v1/accounts/{}", account_address.as_ref());
+        self.fetch_api(&url).await
+    }
+
     pub async fn get_account_key_histories(
         &self,
         account_address: &str,
@@ -686,7 +873,7 @@ impl KaiaScan {
         if page < 1 {
             return Err(anyhow::anyhow!("Page must be >= 1"));
         }
-        if size < 1 || size > 2000 {
+        if !(1..=2000).contains(&size) {
             return Err(anyhow::anyhow!("Size must be between 1 and 2000"));
         }
 
@@ -698,8 +885,8 @@ impl KaiaScan {
     }
 
     pub async fn get_kaia_info(&self) -> Result<KaiaInfoResponse> {
-        let url = format!("api/v1/kaia");
-        self.fetch_api(&url).await
+        let url = "api/v1/kaia";
+        self.fetch_api(url).await
     }
 
     pub async fn get_latest_block_burns(
@@ -713,7 +900,7 @@ impl KaiaScan {
         if page < 1 {
             return Err(anyhow::anyhow!("Page must be >= 1"));
         }
-        if size < 1 || size > 2000 {
+        if !(1..=2000).contains(&size) {
             return Err(anyhow::anyhow!("Size must be between 1 and 2000"));
         }
 
@@ -748,7 +935,7 @@ impl KaiaScan {
         if page < 1 {
             return Err(anyhow::anyhow!("Page must be >= 1"));
         }
-        if size < 1 || size > 2000 {
+        if !(1..=2000).contains(&size) {
             return Err(anyhow::anyhow!("Size must be between 1 and 2000"));
         }
 
@@ -783,14 +970,14 @@ impl KaiaScan {
         if page < 1 {
             return Err(anyhow::anyhow!("Page must be >= 1"));
         }
-        if size < 1 || size > 2000 {
+        if !(1..=2000).contains(&size) {
             return Err(anyhow::anyhow!("Size must be between 1 and 2000"));
         }
 
         let mut query_params = vec![format!("page={}", page), format!("size={}", size)];
 
         if let Some(sig) = signature {
-            query_params.push(format!("signature={}", sig));
+            query_params.push(format!("signature={}", signature::query_value(&sig)));
         }
 
         if let Some(start) = block_number_start {
@@ -803,8 +990,7 @@ impl KaiaScan {
 
         let query_string = query_params.join("&");
         let url = format!(
-            "{}api/v1/accounts/{}/event-logs?{}",
-            self.base_url,
+            "api/v1/accounts/{}/event-logs?{}",
             account_address.as_ref(),
             query_string
         );
@@ -825,13 +1011,12 @@ impl KaiaScan {
         if page < 1 {
             return Err(anyhow::anyhow!("Page must be >= 1"));
         }
-        if size < 1 || size > 2000 {
+        if !(1..=2000).contains(&size) {
             return Err(anyhow::anyhow!("Size must be between 1 and 2000"));
         }
 
         let url = format!(
-            "{}api/v1/accounts/{}/nft-balances/kip17?page={}&size={}",
-            self.base_url,
+            "api/v1/accounts/{}/nft-balances/kip17?page={}&size={}",
             account_address.as_ref(),
             page,
             size
@@ -853,13 +1038,12 @@ impl KaiaScan {
         if page < 1 {
             return Err(anyhow::anyhow!("Page must be >= 1"));
         }
-        if size < 1 || size > 2000 {
+        if !(1..=2000).contains(&size) {
             return Err(anyhow::anyhow!("Size must be between 1 and 2000"));
         }
 
         let url = format!(
-            "{}api/v1/accounts/{}/nft-balances/kip37?page={}&size={}",
-            self.base_url,
+            "api/v1/accounts/{}/nft-balances/kip37?page={}&size={}",
             account_address.as_ref(),
             page,
             size
@@ -883,7 +1067,50 @@ impl KaiaScan {
         if page < 1 {
             return Err(anyhow::anyhow!("Page must be >= 1"));
         }
-        if size < 1 || size > 2000 {
+        if !(1..=2000).contains(&size) {
+            return Err(anyhow::anyhow!("Size must be between 1 and 2000"));
+        }
+
+        let mut query_params = vec![format!("page={}", page), format!("size={}", size)];
+
+        if let Some(contract) = contract_address {
+            query_params.push(format!("contractAddress={}", contract.as_ref()));
+        }
+
+        if let Some(start) = block_number_start {
+            query_params.push(format!("blockNumberStart={}", start));
+        }
+
+        if let Some(end) = block_number_end {
+            query_params.push(format!("blockNumberEnd={}", end));
+        }
+
+        let query_string = query_params.join("&");
+        let url = format!(
+            "api/v1/accounts/{}/nft-transfers?{}",
+            account_address.as_ref(),
+            query_string
+        );
+
+        self.fetch_api(&url).await
+    }
+
+    pub async fn get_account_token_transfers(
+        &self,
+        account_address: Address,
+        page: Option<u32>,
+        size: Option<u32>,
+        contract_address: Option<Address>,
+        block_number_start: Option<u64>,
+        block_number_end: Option<u64>,
+    ) -> Result<TokenTransferEntry> {
+        let page = page.unwrap_or(1);
+        let size = size.unwrap_or(20);
+
+        if page < 1 {
+            return Err(anyhow::anyhow!("Page must be >= 1"));
+        }
+        if !(1..=2000).contains(&size) {
             return Err(anyhow::anyhow!("Size must be between 1 and 2000"));
         }
 
@@ -903,15 +1130,12 @@ impl KaiaScan {
 
         let query_string = query_params.join("&");
         let url = format!(
-            "{}api/v1/accounts/{}/nft-transfers?{}",
-            self.base_url,
+            "api/v1/accounts/{}/token-transfers?{}",
             account_address.as_ref(),
             query_string
         );
 
-        let raw_data: String = self.fetch_api(&url).await?;
-        let nft_transfers: NftTransferEntry = serde_json::from_str(&raw_data)?;
-        Ok(nft_transfers)
+        self.fetch_api(&url).await
     }
 
     // Method to get account token balances
@@ -927,13 +1151,12 @@ impl KaiaScan {
         if page < 1 {
             return Err(anyhow::anyhow!("Page must be >= 1"));
         }
-        if size < 1 || size > 2000 {
+        if !(1..=2000).contains(&size) {
             return Err(anyhow::anyhow!("Size must be between 1 and 2000"));
         }
 
         let url = format!(
-            "{}api/v1/accounts/{}/token-balances?page={}&size={}",
-            self.base_url,
+            "api/v1/accounts/{}/token-balances?page={}&size={}",
             account_address.as_ref(),
             page,
             size
@@ -950,10 +1173,7 @@ impl KaiaScan {
             return Err(anyhow::anyhow!("Transaction hash is required"));
         }
 
-        let url = format!(
-            "{}api/v1/transactions/{}/input-data",
-            self.base_url, transaction_hash
-        );
+        let url = format!("api/v1/transactions/{}/input-data", transaction_hash);
 
         self.fetch_api(&url).await
     }
@@ -974,20 +1194,20 @@ impl KaiaScan {
         if page < 1 {
             return Err(anyhow::anyhow!("Page must be >= 1"));
         }
-        if size < 1 || size > 2000 {
+        if !(1..=2000).contains(&size) {
             return Err(anyhow::anyhow!("Size must be between 1 and 2000"));
         }
 
         let mut query_params = vec![format!("page={}", page), format!("size={}", size)];
 
         if let Some(sig) = signature {
-            query_params.push(format!("signature={}", sig));
+            query_params.push(format!("signature={}", signature::query_value(&sig)));
         }
 
         let query_string = query_params.join("&");
         let url = format!(
-            "{}api/v1/transactions/{}/event-logs?{}",
-            self.base_url, transaction_hash, query_string
+            "api/v1/transactions/{}/event-logs?{}",
+            transaction_hash, query_string
         );
 
         self.fetch_api(&url).await
@@ -999,6 +1219,30 @@ impl KaiaScan {
         page: Option<u32>,
         size: Option<u32>,
     ) -> Result<InternalTransactionEntry> {
+        self.fetch_transaction_internal_transactions(transaction_hash, page, size)
+            .await
+    }
+
+    // The same endpoint as a page of internal transactions with paging information.
+    pub async fn get_transaction_internal_transactions_page(
+        &self,
+        transaction_hash: &str,
+        page: Option<u32>,
+        size: Option<u32>,
+    ) -> Result<InternalTransactionsResponse> {
+        self.fetch_transaction_internal_transactions(transaction_hash, page, size)
+            .await
+    }
+
+    async fn fetch_transaction_internal_transactions<T>(
+        &self,
+        transaction_hash: &str,
+        page: Option<u32>,
+        size: Option<u32>,
+    ) -> Result<T>
+    where
+        T: for<'de> Deserialize<'de> + std::fmt::Debug,
+    {
         let page = page.unwrap_or(1);
         let size = size.unwrap_or(20);
 
@@ -1008,13 +1252,13 @@ impl KaiaScan {
         if page < 1 {
             return Err(anyhow::anyhow!("Page must be >= 1"));
         }
-        if size < 1 || size > 2000 {
+        if !(1..=2000).contains(&size) {
             return Err(anyhow::anyhow!("Size must be between 1 and 2000"));
         }
 
         let url = format!(
-            "{}api/v1/transactions/{}/internal-transactions?page={}&size={}",
-            self.base_url, transaction_hash, page, size
+            "api/v1/transactions/{}/internal-transactions?page={}&size={}",
+            transaction_hash, page, size
         );
 
         self.fetch_api(&url).await
@@ -1035,13 +1279,13 @@ impl KaiaScan {
         if page < 1 {
             return Err(anyhow::anyhow!("Page must be >= 1"));
         }
-        if size < 1 || size > 2000 {
+        if !(1..=2000).contains(&size) {
             return Err(anyhow::anyhow!("Size must be between 1 and 2000"));
         }
 
         let url = format!(
-            "{}api/v1/transactions/{}/token-transfers?page={}&size={}",
-            self.base_url, transaction_hash, page, size
+            "api/v1/transactions/{}/token-transfers?page={}&size={}",
+            transaction_hash, page, size
         );
 
         self.fetch_api(&url).await
@@ -1062,13 +1306,13 @@ impl KaiaScan {
         if page < 1 {
             return Err(anyhow::anyhow!("Page must be >= 1"));
         }
-        if size < 1 || size > 2000 {
+        if !(1..=2000).contains(&size) {
             return Err(anyhow::anyhow!("Size must be between 1 and 2000"));
         }
 
         let url = format!(
-            "{}api/v1/transactions/{}/nft-transfers?page={}&size={}",
-            self.base_url, transaction_hash, page, size
+            "api/v1/transactions/{}/nft-transfers?page={}&size={}",
+            transaction_hash, page, size
         );
 
         self.fetch_api(&url).await
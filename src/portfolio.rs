@@ -0,0 +1,532 @@
+use crate::prices::parse_number;
+use crate::{Address, Asset, ContractInfo, KaiaScan, NftBalance, PriceSource, TokenBalance};
+use anyhow::{anyhow, Result};
+use futures::stream::{self, StreamExt};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+const PORTFOLIO_PAGE_SIZE: u32 = 2000;
+
+#[derive(Debug, Serialize)]
+pub struct Portfolio {
+    pub address: Address,
+    pub native: NativeHolding,
+    pub tokens: Vec<TokenHolding>,
+    pub nft_collections: Vec<NftCollection>,
+    // Sum of every holding with a known USD value.
+    pub usd_value: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct NativeHolding {
+    pub symbol: String,
+    pub balance: String,
+    pub amount: f64,
+    pub usd_price: Option<f64>,
+    pub usd_value: Option<f64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TokenHolding {
+    pub contract: ContractInfo,
+    // Token metadata is missing when `get_fungible_token` failed for the contract.
+    pub name: Option<String>,
+    pub symbol: Option<String>,
+    pub decimals: Option<u32>,
+    pub raw_balance: u64,
+    // `raw_balance` scaled by the token decimals, e.g. "12.5".
+    pub balance: Option<String>,
+    pub amount: Option<f64>,
+    pub usd_price: Option<f64>,
+    pub usd_value: Option<f64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct NftCollection {
+    pub contract: ContractInfo,
+    pub token_count: u64,
+    pub items: Vec<NftItemHolding>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct NftItemHolding {
+    pub token_id: String,
+    pub token_count: u64,
+}
+
+// Renders an integer amount in base units with `decimals` fractional digits, dropping
+// trailing zeros: 1234500 with 6 decimals is "1.2345".
+pub fn format_units(raw: u64, decimals: u32) -> String {
+    format_base_units(raw as u128, decimals)
+}
+
+pub(crate) fn format_base_units(raw: u128, decimals: u32) -> String {
+    let digits = raw.to_string();
+    let decimals = decimals as usize;
+    if decimals == 0 {
+        return digits;
+    }
+
+    let padded = format!("{:0>width$}", digits, width = decimals + 1);
+    let (integer, fraction) = padded.split_at(padded.len() - decimals);
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.is_empty() {
+        integer.to_string()
+    } else {
+        format!("{}.{}", integer, fraction)
+    }
+}
+
+// Parses a decimal amount such as "0.000525" into base units with `decimals` fractional
+// digits. Returns `None` for malformed values and for more fractional digits than fit.
+pub(crate) fn parse_base_units(value: &str, decimals: u32) -> Option<u128> {
+    let value = value.trim().replace(',', "");
+    let (integer, fraction) = value.split_once('.').unwrap_or((&value, ""));
+    let fraction = fraction.trim_end_matches('0');
+
+    let digits = |part: &str| part.chars().all(|c| c.is_ascii_digit());
+    if (integer.is_empty() && fraction.is_empty())
+        || !digits(integer)
+        || !digits(fraction)
+        || fraction.len() > decimals as usize
+    {
+        return None;
+    }
+
+    let scale = 10u128.checked_pow(decimals)?;
+    let integer = if integer.is_empty() {
+        0
+    } else {
+        integer.parse::<u128>().ok()?
+    };
+    let fraction = if fraction.is_empty() {
+        0
+    } else {
+        fraction.parse::<u128>().ok()? * 10u128.pow(decimals - fraction.len() as u32)
+    };
+    integer.checked_mul(scale)?.checked_add(fraction)
+}
+
+// Groups KIP-17 and KIP-37 holdings by contract, ordered by contract address.
+fn nft_collections(items: impl IntoIterator<Item = NftBalance>) -> Vec<NftCollection> {
+    let mut collections: BTreeMap<String, NftCollection> = BTreeMap::new();
+    for item in items {
+        let collection = collections
+            .entry(item.contract.contract_address.clone())
+            .or_insert_with(|| NftCollection {
+                contract: item.contract,
+                token_count: 0,
+                items: Vec::new(),
+            });
+        collection.token_count += item.token_count;
+        collection.items.push(NftItemHolding {
+            token_id: item.token_id,
+            token_count: item.token_count,
+        });
+    }
+    collections.into_values().collect()
+}
+
+// KAIA amounts have 18 decimals; reports sum them in wei.
+pub(crate) const KAIA_DECIMALS: u32 = 18;
+
+pub(crate) fn wei_to_kaia(wei: u128) -> f64 {
+    wei as f64 / 1e18
+}
+
+impl KaiaScan {
+    async fn all_token_balances(&self, address: &Address) -> Result<Vec<TokenBalance>> {
+        let mut balances = Vec::new();
+        let mut page = 1;
+
+        loop {
+            let response = self
+                .get_account_token_balances(address.clone(), Some(page), Some(PORTFOLIO_PAGE_SIZE))
+                .await?;
+            balances.extend(response.results);
+
+            if response.paging.last || page >= response.paging.total_page {
+                return Ok(balances);
+            }
+            page += 1;
+        }
+    }
+
+    async fn all_nft_balances(&self, address: &Address, kip37: bool) -> Result<Vec<NftBalance>> {
+        let mut balances = Vec::new();
+        let mut page = 1;
+
+        loop {
+            let response = if kip37 {
+                self.get_account_kip37_nft_balances(
+                    address.clone(),
+                    Some(page),
+                    Some(PORTFOLIO_PAGE_SIZE),
+                )
+                .await?
+            } else {
+                self.get_account_kip17_nft_balances(
+                    address.clone(),
+                    Some(page),
+                    Some(PORTFOLIO_PAGE_SIZE),
+                )
+                .await?
+            };
+            balances.extend(response.results);
+
+            if response.paging.last || page as i64 >= response.paging.total_page {
+                return Ok(balances);
+            }
+            page += 1;
+        }
+    }
+
+    // Collects the native balance, token balances and NFT holdings of `address`, valued
+    // with the KAIA price from `get_kaia_info`.
+    pub async fn get_account_portfolio(&self, address: Address) -> Result<Portfolio> {
+        self.get_account_portfolio_with(address, &self.kaia_prices())
+            .await
+    }
+
+    // Like `get_account_portfolio`, with spot prices from `prices`. The listings and the
+    // KAIA price are fetched concurrently, then token metadata and prices in batches.
+    pub async fn get_account_portfolio_with<P: PriceSource + Sync>(
+        &self,
+        address: Address,
+        prices: &P,
+    ) -> Result<Portfolio> {
+        let (account, token_balances, kip17, kip37, kaia_usd_price) = futures::try_join!(
+            self.get_account(address.clone()),
+            self.all_token_balances(&address),
+            self.all_nft_balances(&address, false),
+            self.all_nft_balances(&address, true),
+            prices.spot_price(&Asset::Kaia),
+        )?;
+
+        let native_amount = parse_number(&account.balance)
+            .ok_or_else(|| anyhow!("Cannot parse KAIA balance {}", account.balance))?;
+        let native = NativeHolding {
+            symbol: "KAIA".to_string(),
+            amount: native_amount,
+            balance: account.balance,
+            usd_price: kaia_usd_price,
+            usd_value: kaia_usd_price.map(|price| price * native_amount),
+        };
+
+        let token_addresses: Vec<Address> = token_balances
+            .iter()
+            .map(|balance| Address::new(balance.contract.contract_address.clone()))
+            .collect();
+        let token_infos = self.get_token_infos_batch(&token_addresses).await;
+        let token_assets: Vec<Asset> = token_addresses.iter().map(Asset::token).collect();
+        let token_prices = stream::iter(&token_assets)
+            .map(|asset| prices.spot_price(asset))
+            .buffered(self.batch_concurrency)
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect::<Result<Vec<_>>>()?;
+
+        let tokens: Vec<TokenHolding> = token_balances
+            .into_iter()
+            .zip(token_infos)
+            .zip(token_prices)
+            .map(|((balance, info), usd_price)| {
+                let info = info.ok();
+                let decimals = info
+                    .as_ref()
+                    .and_then(|info| u32::try_from(info.decimal).ok());
+                let formatted = decimals.map(|decimals| format_units(balance.balance, decimals));
+                let amount = formatted.as_deref().and_then(parse_number);
+
+                TokenHolding {
+                    contract: balance.contract,
+                    name: info.as_ref().map(|info| info.name.clone()),
+                    symbol: info.map(|info| info.symbol),
+                    decimals,
+                    raw_balance: balance.balance,
+                    balance: formatted,
+                    amount,
+                    usd_price,
+                    usd_value: usd_price.zip(amount).map(|(price, amount)| price * amount),
+                }
+            })
+            .collect();
+
+        let usd_value = native.usd_value.unwrap_or(0.0)
+            + tokens
+                .iter()
+                .filter_map(|token| token.usd_value)
+                .sum::<f64>();
+
+        Ok(Portfolio {
+            address,
+            native,
+            tokens,
+            nft_collections: nft_collections(kip17.into_iter().chain(kip37)),
+            usd_value,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::StaticPrices;
+    use serde_json::json;
+
+    const ACCOUNT: &str = "0xaccount";
+    const TOKEN: &str = "0x00000000000000000000000000000000000000aa";
+    const UNKNOWN_TOKEN: &str = "0x00000000000000000000000000000000000000bb";
+
+    fn contract(address: &str, contract_type: &str) -> serde_json::Value {
+        json!({ "contract_address": address, "contract_type": contract_type })
+    }
+
+    fn nft(address: &str, contract_type: &str, token_id: &str, token_count: u64) -> NftBalance {
+        serde_json::from_value(json!({
+            "contract": contract(address, contract_type),
+            "tokenId": token_id,
+            "tokenCount": token_count,
+        }))
+        .unwrap()
+    }
+
+    fn nft_page(items: &[NftBalance], current_page: i64, total_page: i64) -> String {
+        json!({
+            "results": items,
+            "paging": {
+                "current_page": current_page,
+                "last": current_page == total_page,
+                "total_count": items.len(),
+                "total_page": total_page,
+            },
+            "property": null,
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn formats_base_units() {
+        assert_eq!(format_units(1_234_500, 6), "1.2345");
+        assert_eq!(format_units(5, 6), "0.000005");
+        assert_eq!(format_units(2_000_000, 6), "2");
+        assert_eq!(format_units(0, 18), "0");
+        assert_eq!(format_units(42, 0), "42");
+        assert_eq!(
+            format_base_units(u128::MAX, 18),
+            "340282366920938463463.374607431768211455"
+        );
+    }
+
+    #[test]
+    fn parses_base_units() {
+        assert_eq!(parse_base_units("1.2345", 6), Some(1_234_500));
+        assert_eq!(parse_base_units("0.000525", 18), Some(525_000_000_000_000));
+        assert_eq!(parse_base_units(".5", 1), Some(5));
+        assert_eq!(parse_base_units("7.", 2), Some(700));
+        assert_eq!(parse_base_units(" 1,000.10 ", 2), Some(100_010));
+        // Trailing zeros beyond the decimals are not lost precision.
+        assert_eq!(parse_base_units("1.500000", 1), Some(15));
+
+        assert_eq!(parse_base_units("1.05", 1), None);
+        assert_eq!(parse_base_units("", 18), None);
+        assert_eq!(parse_base_units(".", 18), None);
+        assert_eq!(parse_base_units("-1", 18), None);
+        assert_eq!(parse_base_units("1e18", 18), None);
+        assert_eq!(parse_base_units("340282366920938463464", 18), None);
+        assert_eq!(parse_base_units("1", 39), None);
+    }
+
+    #[test]
+    fn groups_nfts_of_both_standards_by_contract() {
+        let collections = nft_collections(vec![
+            nft("0xb", "kip17", "1", 1),
+            nft("0xa", "kip37", "7", 5),
+            nft("0xb", "kip17", "2", 1),
+            nft("0xa", "kip37", "8", 2),
+        ]);
+
+        let summary: Vec<(&str, u64, Vec<&str>)> = collections
+            .iter()
+            .map(|collection| {
+                (
+                    collection.contract.contract_address.as_str(),
+                    collection.token_count,
+                    collection
+                        .items
+                        .iter()
+                        .map(|item| item.token_id.as_str())
+                        .collect(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![("0xa", 7, vec!["7", "8"]), ("0xb", 2, vec!["1", "2"])]
+        );
+    }
+
+    #[tokio::test]
+    async fn values_a_portfolio_across_pages() {
+        let mut server = mockito::Server::new_async().await;
+        let client = KaiaScan::new(false).unwrap().with_base_url(server.url());
+
+        server
+            .mock("GET", "/api/v1/accounts/0xaccount")
+            .with_body(json!({ "address": ACCOUNT, "balance": "2.5" }).to_string())
+            .create_async()
+            .await;
+        for (page, token) in [(1, TOKEN), (2, UNKNOWN_TOKEN)] {
+            let body = json!({
+                "results": [{ "contract": contract(token, "kip7"), "balance": 1_500_000 }],
+                "paging": {
+                    "totalCount": 2,
+                    "currentPage": page,
+                    "last": page == 2,
+                    "totalPage": 2,
+                },
+                "property": null,
+            });
+            server
+                .mock(
+                    "GET",
+                    format!(
+                        "/api/v1/accounts/0xaccount/token-balances?page={}&size=2000",
+                        page
+                    )
+                    .as_str(),
+                )
+                .with_body(body.to_string())
+                .create_async()
+                .await;
+        }
+        server
+            .mock(
+                "GET",
+                "/api/v1/accounts/0xaccount/nft-balances/kip17?page=1&size=2000",
+            )
+            .with_body(nft_page(&[nft("0xnft", "kip17", "1", 1)], 1, 2))
+            .create_async()
+            .await;
+        server
+            .mock(
+                "GET",
+                "/api/v1/accounts/0xaccount/nft-balances/kip17?page=2&size=2000",
+            )
+            .with_body(nft_page(&[nft("0xnft", "kip17", "2", 1)], 2, 2))
+            .create_async()
+            .await;
+        server
+            .mock(
+                "GET",
+                "/api/v1/accounts/0xaccount/nft-balances/kip37?page=1&size=2000",
+            )
+            .with_body(nft_page(&[nft("0xmulti", "kip37", "9", 3)], 1, 1))
+            .create_async()
+            .await;
+        server
+            .mock("GET", format!("/api/v1/tokens/{}", TOKEN).as_str())
+            .with_body(
+                json!({
+                    "contract_type": "kip7",
+                    "name": "Token",
+                    "symbol": "TKN",
+                    "icon": "",
+                    "decimal": 6,
+                    "total_supply": "0",
+                    "total_transfers": 0,
+                    "official_site": "",
+                    "burn_amount": "0",
+                    "total_burns": 0,
+                })
+                .to_string(),
+            )
+            .create_async()
+            .await;
+        server
+            .mock("GET", format!("/api/v1/tokens/{}", UNKNOWN_TOKEN).as_str())
+            .with_status(404)
+            .create_async()
+            .await;
+
+        let prices = StaticPrices::new()
+            .with_spot_price(Asset::Kaia, 0.2)
+            .with_spot_price(Asset::token(TOKEN), 2.0)
+            .with_spot_price(Asset::token(UNKNOWN_TOKEN), 3.0);
+        let portfolio = client
+            .get_account_portfolio_with(Address::new(ACCOUNT), &prices)
+            .await
+            .unwrap();
+
+        assert_eq!(portfolio.native.amount, 2.5);
+        assert_eq!(portfolio.native.usd_value, Some(0.5));
+
+        let known = &portfolio.tokens[0];
+        assert_eq!(known.symbol.as_deref(), Some("TKN"));
+        assert_eq!(known.balance.as_deref(), Some("1.5"));
+        assert_eq!(known.usd_value, Some(3.0));
+
+        // Without metadata the decimals are unknown, so the balance stays raw and unvalued.
+        let unknown = &portfolio.tokens[1];
+        assert_eq!(unknown.decimals, None);
+        assert_eq!(unknown.raw_balance, 1_500_000);
+        assert_eq!(unknown.balance, None);
+        assert_eq!(unknown.usd_value, None);
+
+        let collections: Vec<(&str, usize)> = portfolio
+            .nft_collections
+            .iter()
+            .map(|collection| {
+                (
+                    collection.contract.contract_address.as_str(),
+                    collection.items.len(),
+                )
+            })
+            .collect();
+        assert_eq!(collections, vec![("0xmulti", 1), ("0xnft", 2)]);
+        assert_eq!(portfolio.usd_value, 3.5);
+    }
+
+    #[tokio::test]
+    async fn rejects_unparsable_native_balances() {
+        let mut server = mockito::Server::new_async().await;
+        let client = KaiaScan::new(false).unwrap().with_base_url(server.url());
+
+        server
+            .mock("GET", "/api/v1/accounts/0xaccount")
+            .with_body(json!({ "address": ACCOUNT, "balance": "n/a" }).to_string())
+            .create_async()
+            .await;
+        server
+            .mock(
+                "GET",
+                mockito::Matcher::Regex(r"^/api/v1/accounts/0xaccount/token-balances".into()),
+            )
+            .with_body(
+                json!({
+                    "results": [],
+                    "paging": { "totalCount": 0, "currentPage": 1, "last": true, "totalPage": 1 },
+                    "property": null,
+                })
+                .to_string(),
+            )
+            .create_async()
+            .await;
+        server
+            .mock(
+                "GET",
+                mockito::Matcher::Regex(r"^/api/v1/accounts/0xaccount/nft-balances".into()),
+            )
+            .with_body(nft_page(&[], 1, 1))
+            .create_async()
+            .await;
+
+        let prices = StaticPrices::new();
+        let err = client
+            .get_account_portfolio_with(Address::new(ACCOUNT), &prices)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("n/a"));
+    }
+}
@@ -0,0 +1,246 @@
+use crate::{Address, KaiaScan};
+use anyhow::Result;
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+const SPOT_PRICE_TTL: Duration = Duration::from_secs(60);
+// How far back the current KAIA price is still used as the historical price.
+const DEFAULT_SPOT_TOLERANCE_SECS: i64 = 60 * 60;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Asset {
+    Kaia,
+    Token(Address),
+}
+
+impl Asset {
+    // Token addresses are compared case-insensitively.
+    pub fn token(address: impl AsRef<str>) -> Self {
+        Asset::Token(Address::new(address.as_ref().to_ascii_lowercase()))
+    }
+}
+
+// USD prices for KAIA and tokens. Reports take any implementation, so custom feeds plug in
+// by implementing this trait. `None` means the source has no price for the asset.
+pub trait PriceSource {
+    fn spot_price(&self, asset: &Asset) -> impl Future<Output = Result<Option<f64>>> + Send;
+
+    // Price at `timestamp`, in Unix seconds.
+    fn price_at(
+        &self,
+        asset: &Asset,
+        timestamp: i64,
+    ) -> impl Future<Output = Result<Option<f64>>> + Send;
+}
+
+impl<T: PriceSource + Sync> PriceSource for &T {
+    fn spot_price(&self, asset: &Asset) -> impl Future<Output = Result<Option<f64>>> + Send {
+        (**self).spot_price(asset)
+    }
+
+    fn price_at(
+        &self,
+        asset: &Asset,
+        timestamp: i64,
+    ) -> impl Future<Output = Result<Option<f64>>> + Send {
+        (**self).price_at(asset, timestamp)
+    }
+}
+
+pub(crate) fn now_timestamp() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs() as i64)
+}
+
+pub(crate) fn parse_number(value: &str) -> Option<f64> {
+    value
+        .trim()
+        .replace(',', "")
+        .parse::<f64>()
+        .ok()
+        .filter(|value| value.is_finite())
+}
+
+// The KAIA price from `get_kaia_info`. KaiaScan has no price history, so `price_at` only
+// answers for timestamps within the spot tolerance of now, and tokens are never priced.
+pub struct KaiaInfoPrices<'a> {
+    client: &'a KaiaScan,
+    spot_tolerance_secs: i64,
+    cached: Mutex<Option<(Instant, Option<f64>)>>,
+}
+
+impl<'a> KaiaInfoPrices<'a> {
+    pub fn new(client: &'a KaiaScan) -> Self {
+        Self {
+            client,
+            spot_tolerance_secs: DEFAULT_SPOT_TOLERANCE_SECS,
+            cached: Mutex::new(None),
+        }
+    }
+
+    pub fn with_spot_tolerance(mut self, tolerance: Duration) -> Self {
+        self.spot_tolerance_secs = tolerance.as_secs() as i64;
+        self
+    }
+
+    async fn kaia_price(&self) -> Result<Option<f64>> {
+        if let Some((fetched_at, price)) = *self.cached.lock().unwrap() {
+            if fetched_at.elapsed() < SPOT_PRICE_TTL {
+                return Ok(price);
+            }
+        }
+
+        let info = self.client.get_kaia_info().await?;
+        let price = parse_number(&info.klay_price.usd_price);
+        *self.cached.lock().unwrap() = Some((Instant::now(), price));
+        Ok(price)
+    }
+}
+
+impl PriceSource for KaiaInfoPrices<'_> {
+    async fn spot_price(&self, asset: &Asset) -> Result<Option<f64>> {
+        match asset {
+            Asset::Kaia => self.kaia_price().await,
+            Asset::Token(_) => Ok(None),
+        }
+    }
+
+    async fn price_at(&self, asset: &Asset, timestamp: i64) -> Result<Option<f64>> {
+        if (now_timestamp() - timestamp).abs() > self.spot_tolerance_secs {
+            return Ok(None);
+        }
+        self.spot_price(asset).await
+    }
+}
+
+// Fixed prices, for tests and offline reports. A historical lookup uses the latest point at
+// or before the timestamp and falls back to the spot price when there is none.
+#[derive(Debug, Default, Clone)]
+pub struct StaticPrices {
+    spot: HashMap<Asset, f64>,
+    history: HashMap<Asset, BTreeMap<i64, f64>>,
+}
+
+impl StaticPrices {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_spot_price(mut self, asset: Asset, price: f64) -> Self {
+        self.spot.insert(asset, price);
+        self
+    }
+
+    pub fn with_price_at(mut self, asset: Asset, timestamp: i64, price: f64) -> Self {
+        self.history
+            .entry(asset)
+            .or_default()
+            .insert(timestamp, price);
+        self
+    }
+
+    fn lookup(&self, asset: &Asset, timestamp: i64) -> Option<f64> {
+        self.history
+            .get(asset)
+            .and_then(|points| points.range(..=timestamp).next_back())
+            .map(|(_, price)| *price)
+            .or_else(|| self.spot.get(asset).copied())
+    }
+}
+
+impl PriceSource for StaticPrices {
+    async fn spot_price(&self, asset: &Asset) -> Result<Option<f64>> {
+        Ok(self.spot.get(asset).copied())
+    }
+
+    async fn price_at(&self, asset: &Asset, timestamp: i64) -> Result<Option<f64>> {
+        Ok(self.lookup(asset, timestamp))
+    }
+}
+
+impl KaiaScan {
+    pub fn kaia_prices(&self) -> KaiaInfoPrices<'_> {
+        KaiaInfoPrices::new(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn static_prices_use_the_latest_point_before_the_timestamp() {
+        let token = Asset::token("0xABC");
+        let prices = StaticPrices::new()
+            .with_spot_price(Asset::Kaia, 0.2)
+            .with_price_at(Asset::Kaia, 100, 0.1)
+            .with_price_at(Asset::Kaia, 200, 0.15)
+            .with_price_at(token.clone(), 100, 2.0);
+
+        assert_eq!(prices.spot_price(&Asset::Kaia).await.unwrap(), Some(0.2));
+        assert_eq!(prices.price_at(&Asset::Kaia, 150).await.unwrap(), Some(0.1));
+        assert_eq!(
+            prices.price_at(&Asset::Kaia, 200).await.unwrap(),
+            Some(0.15)
+        );
+        // Before the first point the spot price stands in.
+        assert_eq!(prices.price_at(&Asset::Kaia, 50).await.unwrap(), Some(0.2));
+
+        assert_eq!(token, Asset::token("0xabc"));
+        assert_eq!(prices.price_at(&token, 150).await.unwrap(), Some(2.0));
+        assert_eq!(prices.price_at(&token, 50).await.unwrap(), None);
+        assert_eq!(prices.spot_price(&token).await.unwrap(), None);
+    }
+
+    #[test]
+    fn parses_price_strings() {
+        assert_eq!(parse_number(" 1,234.5 "), Some(1234.5));
+        assert_eq!(parse_number(""), None);
+        assert_eq!(parse_number("NaN"), None);
+    }
+
+    #[tokio::test]
+    async fn kaia_info_prices_answer_only_near_now() {
+        let mut server = mockito::Server::new_async().await;
+        let info = server
+            .mock("GET", "/api/v1/kaia")
+            .with_body(
+                json!({
+                    "klay_price": {
+                        "btc_price": "0", "market_cap": "0", "total_supply": "0",
+                        "usd_price": "0.125", "usd_price_changes": "0", "volume": "0",
+                    },
+                    "summary": {
+                        "avg_block_time1h": "1.0",
+                        "avg_block_time24h": "1.0",
+                        "avg_tx_per_block24h": 1.0,
+                        "consensus_node": 40,
+                    },
+                })
+                .to_string(),
+            )
+            .expect(1)
+            .create_async()
+            .await;
+        let client = KaiaScan::new(false).unwrap().with_base_url(server.url());
+        let prices = client.kaia_prices();
+
+        let now = now_timestamp();
+        assert_eq!(prices.spot_price(&Asset::Kaia).await.unwrap(), Some(0.125));
+        assert_eq!(
+            prices.price_at(&Asset::Kaia, now - 60).await.unwrap(),
+            Some(0.125)
+        );
+        assert_eq!(
+            prices.price_at(&Asset::Kaia, now - 86_400).await.unwrap(),
+            None
+        );
+        assert_eq!(prices.spot_price(&Asset::token("0x1")).await.unwrap(), None);
+        // The spot price is cached, so KaiaScan is asked once.
+        info.assert_async().await;
+    }
+}
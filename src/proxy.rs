@@ -0,0 +1,407 @@
+use crate::abi::{decode_hex, encode_hex, Abi};
+use crate::bytecode::creation_code_hex;
+use crate::{Address, ApiError, ContractSourceCode, KaiaScan};
+use anyhow::{bail, Result};
+
+const MAX_PROXY_DEPTH: usize = 5;
+const ZERO_ADDRESS: &str = "0x0000000000000000000000000000000000000000";
+
+// Runtime code of an EIP-1167 minimal proxy, split around the implementation address.
+const MINIMAL_PROXY_PREFIX: &str = "363d3d373d3d3d363d73";
+const MINIMAL_PROXY_SUFFIX: &str = "5af43d82803e903d91602b57fd5bf3";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProxyKind {
+    // ERC1967Proxy whose implementation does not expose UUPS upgrade functions.
+    Eip1967,
+    Transparent,
+    Uups,
+    // EIP-1167 minimal proxy (clone).
+    Minimal,
+    // Reported as a proxy by the explorer without a recognizable pattern.
+    Unknown,
+}
+
+// Where the implementation address of a proxy hop came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImplementationSource {
+    // The explorer's current implementation address.
+    Explorer,
+    // The proxy's constructor argument. This is the initial implementation, which is stale
+    // once the proxy has been upgraded.
+    ConstructorArgument,
+    // Decoded from the creation code of a minimal proxy, which cannot be upgraded.
+    CreationCode,
+}
+
+#[derive(Debug, Clone)]
+pub struct ProxyHop {
+    pub address: String,
+    pub kind: ProxyKind,
+    pub implementation_source: ImplementationSource,
+}
+
+#[derive(Debug)]
+pub struct ResolvedContract {
+    // The queried address when it is a proxy.
+    pub proxy_address: Option<String>,
+    pub implementation_address: String,
+    // Every proxy followed, starting with the queried address.
+    pub proxy_chain: Vec<ProxyHop>,
+    pub abi: Option<Abi>,
+    // `None` when the implementation's source is not verified.
+    pub source: Option<ContractSourceCode>,
+}
+
+impl ResolvedContract {
+    // True when a hop was resolved from constructor arguments, so the implementation may
+    // be outdated for a proxy that has since been upgraded.
+    pub fn is_constructor_fallback(&self) -> bool {
+        self.proxy_chain
+            .iter()
+            .any(|hop| hop.implementation_source == ImplementationSource::ConstructorArgument)
+    }
+}
+
+fn is_zero_or_empty(address: &str) -> bool {
+    let address = address.trim();
+    address.is_empty() || address.eq_ignore_ascii_case(ZERO_ADDRESS)
+}
+
+fn proxy_kind_from_source(source: &ContractSourceCode) -> Option<ProxyKind> {
+    let code = &source.source_code;
+    let name = source.contract_name.as_deref().unwrap_or_default();
+
+    if name.contains("TransparentUpgradeableProxy")
+        || code.contains("contract TransparentUpgradeableProxy")
+    {
+        Some(ProxyKind::Transparent)
+    } else if name.contains("ERC1967Proxy") || code.contains("contract ERC1967Proxy") {
+        Some(ProxyKind::Eip1967)
+    } else if source.is_proxy == Some(true) {
+        Some(ProxyKind::Unknown)
+    } else {
+        None
+    }
+}
+
+fn is_uups_implementation(source: &ContractSourceCode) -> bool {
+    if source.source_code.contains("UUPSUpgradeable") {
+        return true;
+    }
+
+    match source.parsed_abi() {
+        Ok(Some(abi)) => abi
+            .functions
+            .iter()
+            .any(|function| function.name == "proxiableUUID"),
+        _ => false,
+    }
+}
+
+// ERC1967Proxy and TransparentUpgradeableProxy take the initial implementation as their
+// first constructor argument. It is only used when the explorer reports no implementation.
+fn implementation_from_constructor(source: &ContractSourceCode) -> Option<String> {
+    let arguments = decode_hex(source.constructor_arguments.as_deref()?).ok()?;
+    let word = arguments.get(..32)?;
+
+    if word[..12].iter().any(|byte| *byte != 0) {
+        return None;
+    }
+    let address = encode_hex(&word[12..]);
+    (!is_zero_or_empty(&address)).then_some(address)
+}
+
+fn minimal_proxy_implementation(code: &str) -> Option<String> {
+    let code = code.to_ascii_lowercase();
+    let start = code.find(MINIMAL_PROXY_PREFIX)? + MINIMAL_PROXY_PREFIX.len();
+    let address = code.get(start..start + 40)?;
+
+    code[start + 40..]
+        .starts_with(MINIMAL_PROXY_SUFFIX)
+        .then(|| format!("0x{}", address))
+}
+
+impl KaiaScan {
+    // The verified source of a contract, or `None` when the explorer has none for it.
+    async fn verified_source(&self, address: &Address) -> Result<Option<ContractSourceCode>> {
+        match self.get_contract_source_code(address.clone()).await {
+            Ok(source) if source.source_code.trim().is_empty() => Ok(None),
+            Ok(source) => Ok(Some(source)),
+            Err(err) if ApiError::is_not_found(&err) => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    async fn detect_implementation(
+        &self,
+        address: &Address,
+        source: Option<&ContractSourceCode>,
+    ) -> Result<Option<(ProxyKind, String, ImplementationSource)>> {
+        if let Some(source) = source {
+            if let Some(kind) = proxy_kind_from_source(source) {
+                let implementation = source
+                    .implementation_address
+                    .clone()
+                    .filter(|implementation| !is_zero_or_empty(implementation))
+                    .map(|implementation| (implementation, ImplementationSource::Explorer))
+                    .or_else(|| {
+                        implementation_from_constructor(source).map(|implementation| {
+                            (implementation, ImplementationSource::ConstructorArgument)
+                        })
+                    });
+
+                if let Some((implementation, from)) = implementation {
+                    return Ok(Some((kind, implementation, from)));
+                }
+            }
+        }
+
+        // Creation code is only a fallback, so a contract without one is not a proxy.
+        let creation_code = match self.get_contract_creation_code(address.clone()).await {
+            Ok(code) => Some(code),
+            Err(err) if ApiError::is_not_found(&err) => None,
+            Err(err) => return Err(err),
+        };
+        Ok(creation_code
+            .as_ref()
+            .and_then(creation_code_hex)
+            .and_then(|code| minimal_proxy_implementation(&code))
+            .map(|implementation| {
+                (
+                    ProxyKind::Minimal,
+                    implementation,
+                    ImplementationSource::CreationCode,
+                )
+            }))
+    }
+
+    // Follows proxies from `address` to the implementation, up to a bounded number of hops,
+    // and returns the implementation's ABI and source alongside the proxy chain.
+    pub async fn resolve_contract(&self, address: Address) -> Result<ResolvedContract> {
+        let mut current = address;
+        let mut proxy_chain: Vec<ProxyHop> = Vec::new();
+
+        loop {
+            // Unverified contracts have no source but can still be minimal proxies.
+            let source = self.verified_source(&current).await?;
+
+            if let Some(last) = proxy_chain.last_mut() {
+                if last.kind == ProxyKind::Eip1967
+                    && source.as_ref().is_some_and(is_uups_implementation)
+                {
+                    last.kind = ProxyKind::Uups;
+                }
+            }
+
+            let implementation = self
+                .detect_implementation(&current, source.as_ref())
+                .await?;
+
+            match implementation {
+                Some((kind, implementation, implementation_source))
+                    if !implementation.eq_ignore_ascii_case(current.as_ref()) =>
+                {
+                    if proxy_chain.len() >= MAX_PROXY_DEPTH {
+                        bail!(
+                            "Proxy chain starting at {} exceeds {} hops",
+                            proxy_chain[0].address,
+                            MAX_PROXY_DEPTH
+                        );
+                    }
+                    if proxy_chain
+                        .iter()
+                        .any(|hop| hop.address.eq_ignore_ascii_case(&implementation))
+                    {
+                        bail!("Proxy cycle detected at {}", implementation);
+                    }
+
+                    proxy_chain.push(ProxyHop {
+                        address: current.as_ref().to_string(),
+                        kind,
+                        implementation_source,
+                    });
+                    current = Address::new(implementation);
+                }
+                _ => {
+                    let abi = match &source {
+                        Some(source) => source.parsed_abi()?,
+                        None => None,
+                    };
+
+                    return Ok(ResolvedContract {
+                        proxy_address: proxy_chain.first().map(|hop| hop.address.clone()),
+                        implementation_address: current.as_ref().to_string(),
+                        proxy_chain,
+                        abi,
+                        source,
+                    });
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const PROXY: &str = "0x1111111111111111111111111111111111111111";
+    const IMPLEMENTATION: &str = "0x2222222222222222222222222222222222222222";
+
+    fn source(name: &str, constructor_arguments: Option<String>) -> ContractSourceCode {
+        ContractSourceCode {
+            contract_address: PROXY.to_string(),
+            source_code: format!("contract {} {{}}", name),
+            contract_name: Some(name.to_string()),
+            compiler_version: None,
+            optimizer_enabled: None,
+            optimizer_runs: None,
+            evm_version: None,
+            license: None,
+            abi: None,
+            constructor_arguments,
+            is_proxy: None,
+            implementation_address: None,
+        }
+    }
+
+    fn minimal_proxy_code(implementation: &str) -> String {
+        format!(
+            "0x3d602d80600a3d3981f3{}{}{}",
+            MINIMAL_PROXY_PREFIX,
+            &implementation[2..],
+            MINIMAL_PROXY_SUFFIX
+        )
+    }
+
+    async fn mock_source(server: &mut mockito::Server, address: &str, status: usize, body: String) {
+        server
+            .mock(
+                "GET",
+                format!("/api/v1/contracts/source-code?contractAddress={}", address).as_str(),
+            )
+            .with_status(status)
+            .with_body(body)
+            .create_async()
+            .await;
+    }
+
+    async fn mock_creation_code(
+        server: &mut mockito::Server,
+        address: &str,
+        status: usize,
+        body: String,
+    ) {
+        server
+            .mock(
+                "GET",
+                format!(
+                    "/api/v1/contracts/creation-code?contractAddress={}",
+                    address
+                )
+                .as_str(),
+            )
+            .with_status(status)
+            .with_body(body)
+            .create_async()
+            .await;
+    }
+
+    #[test]
+    fn reads_implementation_from_constructor_arguments() {
+        let arguments = format!("0x{:0>64}{:0>64}", &IMPLEMENTATION[2..], "40");
+        let proxy = source("ERC1967Proxy", Some(arguments));
+
+        assert_eq!(proxy_kind_from_source(&proxy), Some(ProxyKind::Eip1967));
+        assert_eq!(
+            implementation_from_constructor(&proxy).as_deref(),
+            Some(IMPLEMENTATION)
+        );
+        assert_eq!(
+            implementation_from_constructor(&source(
+                "ERC1967Proxy",
+                Some(format!("0x{:0>64}", ""))
+            )),
+            None
+        );
+    }
+
+    #[test]
+    fn finds_minimal_proxy_implementation() {
+        assert_eq!(
+            minimal_proxy_implementation(&minimal_proxy_code(IMPLEMENTATION)).as_deref(),
+            Some(IMPLEMENTATION)
+        );
+        assert_eq!(minimal_proxy_implementation("0x6080604052"), None);
+    }
+
+    #[tokio::test]
+    async fn follows_unverified_minimal_proxies() {
+        let mut server = mockito::Server::new_async().await;
+        let client = KaiaScan::new(false).unwrap().with_base_url(server.url());
+
+        mock_source(&mut server, PROXY, 404, "not found".to_string()).await;
+        mock_creation_code(
+            &mut server,
+            PROXY,
+            200,
+            json!({ "creation_code": minimal_proxy_code(IMPLEMENTATION) }).to_string(),
+        )
+        .await;
+        mock_source(&mut server, IMPLEMENTATION, 404, "not found".to_string()).await;
+        mock_creation_code(&mut server, IMPLEMENTATION, 404, "not found".to_string()).await;
+
+        let resolved = client.resolve_contract(Address::new(PROXY)).await.unwrap();
+        assert_eq!(resolved.proxy_address.as_deref(), Some(PROXY));
+        assert_eq!(resolved.implementation_address, IMPLEMENTATION);
+        assert_eq!(resolved.proxy_chain[0].kind, ProxyKind::Minimal);
+        assert_eq!(
+            resolved.proxy_chain[0].implementation_source,
+            ImplementationSource::CreationCode
+        );
+        assert!(!resolved.is_constructor_fallback());
+        assert!(resolved.source.is_none());
+    }
+
+    #[tokio::test]
+    async fn marks_constructor_fallbacks() {
+        let mut server = mockito::Server::new_async().await;
+        let client = KaiaScan::new(false).unwrap().with_base_url(server.url());
+
+        let arguments = format!("0x{:0>64}", &IMPLEMENTATION[2..]);
+        let proxy = json!({
+            "contract_address": PROXY,
+            "source_code": "contract ERC1967Proxy {}",
+            "contract_name": "ERC1967Proxy",
+            "constructor_arguments": arguments,
+        });
+        mock_source(&mut server, PROXY, 200, proxy.to_string()).await;
+        mock_source(&mut server, IMPLEMENTATION, 404, "not found".to_string()).await;
+        mock_creation_code(&mut server, IMPLEMENTATION, 404, "not found".to_string()).await;
+
+        let resolved = client.resolve_contract(Address::new(PROXY)).await.unwrap();
+        assert_eq!(resolved.implementation_address, IMPLEMENTATION);
+        assert_eq!(
+            resolved.proxy_chain[0].implementation_source,
+            ImplementationSource::ConstructorArgument
+        );
+        assert!(resolved.is_constructor_fallback());
+    }
+
+    #[tokio::test]
+    async fn returns_lookup_errors() {
+        let mut server = mockito::Server::new_async().await;
+        let client = KaiaScan::new(false).unwrap().with_base_url(server.url());
+
+        mock_source(&mut server, PROXY, 429, "rate limited".to_string()).await;
+        assert!(client.resolve_contract(Address::new(PROXY)).await.is_err());
+
+        let mut server = mockito::Server::new_async().await;
+        let client = KaiaScan::new(false).unwrap().with_base_url(server.url());
+        mock_source(&mut server, PROXY, 404, "not found".to_string()).await;
+        mock_creation_code(&mut server, PROXY, 500, "error".to_string()).await;
+        assert!(client.resolve_contract(Address::new(PROXY)).await.is_err());
+    }
+}
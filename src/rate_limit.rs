@@ -0,0 +1,69 @@
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::time::Instant;
+
+// Spaces requests evenly so that no more than `requests_per_second` leave the client.
+#[derive(Debug)]
+pub struct RateLimiter {
+    interval: Duration,
+    next_slot: Mutex<Instant>,
+}
+
+impl RateLimiter {
+    pub fn new(requests_per_second: u32) -> Self {
+        let requests_per_second = requests_per_second.max(1);
+
+        Self {
+            interval: Duration::from_secs(1) / requests_per_second,
+            next_slot: Mutex::new(Instant::now()),
+        }
+    }
+
+    pub fn requests_per_second(&self) -> u32 {
+        (Duration::from_secs(1).as_nanos() / self.interval.as_nanos().max(1)) as u32
+    }
+
+    pub async fn acquire(&self) {
+        let slot = {
+            let mut next_slot = self.next_slot.lock().unwrap();
+            let slot = (*next_slot).max(Instant::now());
+            *next_slot = slot + self.interval;
+            slot
+        };
+
+        tokio::time::sleep_until(slot).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn spaces_requests_evenly() {
+        let limiter = RateLimiter::new(4);
+        assert_eq!(limiter.requests_per_second(), 4);
+
+        let start = Instant::now();
+        let mut slots = Vec::new();
+        for _ in 0..5 {
+            limiter.acquire().await;
+            slots.push(start.elapsed());
+        }
+
+        let expected: Vec<Duration> = (0..5).map(|i| Duration::from_millis(250 * i)).collect();
+        assert_eq!(slots, expected);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn does_not_bank_idle_time() {
+        let limiter = RateLimiter::new(10);
+        tokio::time::sleep(Duration::from_secs(5)).await;
+
+        let start = Instant::now();
+        limiter.acquire().await;
+        limiter.acquire().await;
+        assert_eq!(start.elapsed(), Duration::from_millis(100));
+        assert_eq!(RateLimiter::new(0).requests_per_second(), 1);
+    }
+}
@@ -0,0 +1,205 @@
+use crate::abi::{decode_hex, encode_hex, AbiEvent, AbiType};
+use crate::{EventLogEntry, EventLogResult};
+use anyhow::{bail, Error, Result};
+use std::fmt;
+use std::str::FromStr;
+
+// A canonical event signature such as `Transfer(address,address,uint256)`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct EventSignature {
+    name: String,
+    types: Vec<AbiType>,
+    topic0: [u8; 32],
+}
+
+impl EventSignature {
+    // Accepts canonical signatures as well as declarations with whitespace, parameter
+    // names, `indexed` markers and type aliases such as `uint`.
+    pub fn parse(signature: &str) -> Result<Self> {
+        let event = AbiEvent::parse(signature)?;
+        if event.name.contains(char::is_whitespace) {
+            bail!("Invalid event name in signature: {}", signature);
+        }
+        Ok(Self::from(&event))
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn types(&self) -> &[AbiType] {
+        &self.types
+    }
+
+    pub fn canonical(&self) -> String {
+        let types: Vec<String> = self.types.iter().map(|kind| kind.to_string()).collect();
+        format!("{}({})", self.name, types.join(","))
+    }
+
+    pub fn topic0(&self) -> [u8; 32] {
+        self.topic0
+    }
+
+    pub fn topic0_hex(&self) -> String {
+        encode_hex(&self.topic0)
+    }
+
+    // The value sent as the `signature` query parameter: the percent-encoded canonical form.
+    pub fn to_query_value(&self) -> String {
+        let mut encoded = String::new();
+        for byte in self.canonical().bytes() {
+            match byte {
+                b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                    encoded.push(byte as char)
+                }
+                _ => encoded.push_str(&format!("%{:02X}", byte)),
+            }
+        }
+        encoded
+    }
+
+    pub fn matches(&self, log: &EventLogResult) -> bool {
+        log.topics
+            .first()
+            .and_then(|topic0| decode_hex(topic0).ok())
+            .is_some_and(|topic0| topic0 == self.topic0)
+    }
+}
+
+impl From<&AbiEvent> for EventSignature {
+    fn from(event: &AbiEvent) -> Self {
+        Self {
+            name: event.name.clone(),
+            types: event
+                .inputs
+                .iter()
+                .map(|input| input.kind.clone())
+                .collect(),
+            topic0: event.topic0(),
+        }
+    }
+}
+
+impl FromStr for EventSignature {
+    type Err = Error;
+
+    fn from_str(signature: &str) -> Result<Self> {
+        Self::parse(signature)
+    }
+}
+
+impl fmt::Display for EventSignature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.canonical())
+    }
+}
+
+impl From<EventSignature> for String {
+    fn from(signature: EventSignature) -> Self {
+        signature.canonical()
+    }
+}
+
+// Query value for a `signature` argument given as a string. Signatures that parse are sent
+// in their canonical, percent-encoded form; anything else is passed through unchanged.
+pub(crate) fn query_value(signature: &str) -> String {
+    match EventSignature::parse(signature) {
+        Ok(parsed) => parsed.to_query_value(),
+        Err(_) => signature.to_string(),
+    }
+}
+
+impl EventLogEntry {
+    // Drops logs whose topic0 does not match, for when the server ignores the
+    // `signature` parameter. Paging counts are left as returned by the server.
+    pub fn retain_signature(&mut self, signature: &EventSignature) {
+        self.results.retain(|log| signature.matches(log));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TRANSFER_TOPIC: &str =
+        "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef";
+
+    fn log(topic0: &str) -> EventLogResult {
+        EventLogResult {
+            log_index: 0,
+            contract_address: "0xc".to_string(),
+            log_type: String::new(),
+            topics: vec![topic0.to_string()],
+            data: "0x".to_string(),
+            items: Vec::new(),
+            block_number: 1,
+            transaction_hash: "0xabc".to_string(),
+            estimated_event_log: false,
+        }
+    }
+
+    #[test]
+    fn canonicalizes_declarations() {
+        let signature = EventSignature::parse(
+            "event Transfer(address indexed from, address indexed to, uint value)",
+        )
+        .unwrap();
+
+        assert_eq!(signature.name(), "Transfer");
+        assert_eq!(signature.canonical(), "Transfer(address,address,uint256)");
+        assert_eq!(signature.topic0_hex(), TRANSFER_TOPIC);
+        assert_eq!(
+            " Transfer( address , address ,uint256 ) "
+                .parse::<EventSignature>()
+                .unwrap(),
+            signature
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_signatures() {
+        assert!(EventSignature::parse("Transfer").is_err());
+        assert!(EventSignature::parse("Transfer(address,uint7)").is_err());
+        assert!(EventSignature::parse("My Event(address)").is_err());
+        assert!(EventSignature::parse("(address)").is_err());
+    }
+
+    #[test]
+    fn percent_encodes_the_query_value() {
+        let signature = EventSignature::parse("Swap(address,(uint256,bool)[])").unwrap();
+        assert_eq!(
+            signature.to_query_value(),
+            "Swap%28address%2C%28uint256%2Cbool%29%5B%5D%29"
+        );
+
+        assert_eq!(
+            query_value("Transfer(address, address, uint)"),
+            "Transfer%28address%2Caddress%2Cuint256%29"
+        );
+        assert_eq!(query_value("not a signature"), "not a signature");
+        assert_eq!(String::from(signature), "Swap(address,(uint256,bool)[])");
+    }
+
+    #[test]
+    fn filters_logs_by_topic0() {
+        let signature = EventSignature::parse("Transfer(address,address,uint256)").unwrap();
+        let other = "0x8c5be1e5ebec7d5bd14f71427d1e84f3dd0314c0f7b2291e5b200ac8c7c3b925";
+
+        assert!(signature.matches(&log(TRANSFER_TOPIC)));
+        assert!(signature.matches(&log(&TRANSFER_TOPIC.to_uppercase().replace("0X", "0x"))));
+        assert!(!signature.matches(&log(other)));
+
+        let mut entry = EventLogEntry {
+            results: vec![log(TRANSFER_TOPIC), log(other)],
+            pagings: crate::Pagings {
+                total_count: 2,
+                current_page: 1,
+                last: true,
+                total_page: 1,
+            },
+            property: serde_json::Value::Null,
+        };
+        entry.retain_signature(&signature);
+        assert_eq!(entry.results.len(), 1);
+    }
+}
@@ -0,0 +1,607 @@
+// Persists API data into a SQL database. The schema is described once in `schema` and each
+// backend turns it into DDL and upserts, so rows written twice (a rerun over the same range,
+// an overlapping page) replace themselves instead of duplicating.
+
+mod schema;
+
+#[cfg(any(feature = "sqlite", feature = "postgres"))]
+mod backend;
+#[cfg(feature = "postgres")]
+mod postgres;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+
+#[cfg(feature = "postgres")]
+pub use postgres::PostgresSink;
+pub use schema::{
+    Column, ColumnKind, SqlValue, Table, BLOCKS, BLOCK_BURNS, BLOCK_REWARDS,
+    BLOCK_REWARD_DISTRIBUTIONS, BLOCK_REWARD_RECIPIENTS, EVENT_LOGS, NFT_TRANSFERS, TABLES,
+    TOKEN_TRANSFERS, TRANSACTIONS,
+};
+#[cfg(feature = "sqlite")]
+pub use sqlite::SqliteSink;
+
+use crate::{
+    Address, BlockListItem, BlockRewardsResponse, BurnSummary, EventLogResult, KaiaScan,
+    NftTransfer, TokenTransfer, Transaction,
+};
+use anyhow::Result;
+use futures::{pin_mut, StreamExt};
+use std::collections::HashMap;
+use std::future::Future;
+use std::ops::RangeInclusive;
+
+const EVENT_LOGS_PAGE_SIZE: u32 = 2000;
+
+pub trait Sink {
+    // Creates the schema or brings it up to the latest migration. Safe to run on every start.
+    fn migrate(&mut self) -> impl Future<Output = Result<()>> + Send;
+
+    // Writes `rows` into `table` in one transaction, replacing rows that share a key.
+    fn upsert(
+        &mut self,
+        table: &'static Table,
+        rows: Vec<Vec<SqlValue>>,
+    ) -> impl Future<Output = Result<()>> + Send;
+}
+
+fn text(value: &str) -> SqlValue {
+    SqlValue::Text(Some(value.to_string()))
+}
+
+// Empty strings are stored as NULL so they survive the NUMERIC cast in Postgres.
+fn amount(value: &str) -> SqlValue {
+    if value.is_empty() {
+        SqlValue::Text(None)
+    } else {
+        text(value)
+    }
+}
+
+fn json<T: serde::Serialize>(value: &T) -> Result<SqlValue> {
+    Ok(SqlValue::Text(Some(serde_json::to_string(value)?)))
+}
+
+pub fn block_rows(blocks: &[BlockListItem]) -> Vec<Vec<SqlValue>> {
+    blocks
+        .iter()
+        .map(|block| {
+            vec![
+                SqlValue::Integer(block.block_id),
+                text(&block.datetime),
+                text(&block.block_proposer),
+                SqlValue::Integer(block.block_size),
+                SqlValue::Integer(block.total_transaction_count),
+                text(&block.base_fee_per_gas),
+                amount(&block.burnt_fees),
+                amount(&block.reward),
+            ]
+        })
+        .collect()
+}
+
+pub fn transaction_rows(transactions: &[Transaction]) -> Vec<Vec<SqlValue>> {
+    transactions
+        .iter()
+        .map(|transaction| {
+            vec![
+                text(&transaction.transaction_hash),
+                SqlValue::Integer(transaction.block_id),
+                SqlValue::Integer(transaction.transaction_index),
+                text(&transaction.datetime),
+                text(&transaction.from),
+                text(&transaction.to),
+                text(&transaction.fee_payer),
+                amount(&transaction.amount),
+                amount(&transaction.transaction_fee),
+                text(&transaction.effective_gas_price),
+                text(&transaction.method_id),
+                text(&transaction.transaction_type),
+                text(&transaction.status.status),
+            ]
+        })
+        .collect()
+}
+
+// Numbers transfers of one transaction that share contract, parties and amount, so each
+// gets its own row. Identical transfers are interchangeable, so the numbering is the same
+// whichever account's feed they come from. Carried across the pages of one feed, which is
+// ordered by block; counts are dropped when the feed moves to another block.
+#[derive(Debug, Default)]
+pub struct TransferOrdinals {
+    block_id: u64,
+    counts: HashMap<Vec<String>, i64>,
+}
+
+impl TransferOrdinals {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn next(&mut self, block_id: u64, key: Vec<String>) -> i64 {
+        if block_id != self.block_id {
+            self.block_id = block_id;
+            self.counts.clear();
+        }
+        let count = self.counts.entry(key).or_insert(0);
+        *count += 1;
+        *count - 1
+    }
+}
+
+pub fn token_transfer_rows(
+    transfers: &[TokenTransfer],
+    ordinals: &mut TransferOrdinals,
+) -> Vec<Vec<SqlValue>> {
+    transfers
+        .iter()
+        .map(|transfer| {
+            let amount = transfer.amount.to_string();
+            let ordinal = ordinals.next(
+                transfer.block_id,
+                vec![
+                    transfer.transaction_hash.clone(),
+                    transfer.contract.contract_address.clone(),
+                    transfer.from.clone(),
+                    transfer.to.clone(),
+                    amount.clone(),
+                ],
+            );
+            vec![
+                text(&transfer.transaction_hash),
+                text(&transfer.contract.contract_address),
+                text(&transfer.from),
+                text(&transfer.to),
+                text(&amount),
+                SqlValue::Integer(transfer.block_id as i64),
+                SqlValue::Integer(transfer.transaction_index as i64),
+                text(&transfer.datetime),
+                text(&transfer.contract.contract_type),
+                text(&transfer.fee_payer),
+                SqlValue::Integer(ordinal),
+            ]
+        })
+        .collect()
+}
+
+pub fn nft_transfer_rows(
+    transfers: &[NftTransfer],
+    ordinals: &mut TransferOrdinals,
+) -> Vec<Vec<SqlValue>> {
+    transfers
+        .iter()
+        .map(|transfer| {
+            let token_count = transfer.token_count.to_string();
+            let ordinal = ordinals.next(
+                transfer.block_id,
+                vec![
+                    transfer.transaction_hash.clone(),
+                    transfer.contract.contract_address.clone(),
+                    transfer.token_id.clone(),
+                    transfer.from.clone(),
+                    transfer.to.clone(),
+                    token_count.clone(),
+                ],
+            );
+            vec![
+                text(&transfer.transaction_hash),
+                text(&transfer.contract.contract_address),
+                text(&transfer.token_id),
+                text(&transfer.from),
+                text(&transfer.to),
+                text(&token_count),
+                SqlValue::Integer(transfer.block_id as i64),
+                SqlValue::Integer(transfer.transaction_index as i64),
+                text(&transfer.datetime),
+                text(&transfer.contract.contract_type),
+                text(&transfer.fee_payer),
+                SqlValue::Integer(ordinal),
+            ]
+        })
+        .collect()
+}
+
+pub fn event_log_rows(logs: &[EventLogResult]) -> Result<Vec<Vec<SqlValue>>> {
+    logs.iter()
+        .map(|log| {
+            Ok(vec![
+                text(&log.transaction_hash),
+                SqlValue::Integer(log.log_index as i64),
+                SqlValue::Integer(log.block_number as i64),
+                text(&log.contract_address),
+                text(&log.log_type),
+                json(&log.topics)?,
+                text(&log.data),
+                json(&log.items)?,
+                SqlValue::Bool(log.estimated_event_log),
+            ])
+        })
+        .collect()
+}
+
+// Block rewards span three tables: the totals, the recipients and the distributions.
+pub fn block_reward_rows(
+    block_id: i64,
+    rewards: &BlockRewardsResponse,
+) -> (Vec<SqlValue>, Vec<Vec<SqlValue>>, Vec<Vec<SqlValue>>) {
+    let totals = vec![
+        SqlValue::Integer(block_id),
+        amount(&rewards.minted),
+        amount(&rewards.total_fee),
+        amount(&rewards.burnt_fee),
+    ];
+    let recipients = rewards
+        .recipients
+        .iter()
+        .map(|recipient| {
+            vec![
+                SqlValue::Integer(block_id),
+                text(&recipient.address),
+                text(&recipient.reward_type),
+                text(&recipient.name),
+                amount(&recipient.amount),
+            ]
+        })
+        .collect();
+    let distributions = rewards
+        .distributions
+        .iter()
+        .map(|distribution| {
+            vec![
+                SqlValue::Integer(block_id),
+                text(&distribution.distribution_type),
+                amount(&distribution.amount),
+            ]
+        })
+        .collect();
+
+    (totals, recipients, distributions)
+}
+
+pub fn block_burn_row(block_id: i64, burns: &BurnSummary) -> Vec<SqlValue> {
+    vec![
+        SqlValue::Integer(block_id),
+        amount(&burns.accumulate_burnt),
+        amount(&burns.accumulate_burnt_fees),
+        amount(&burns.accumulate_burnt_kaia),
+        amount(&burns.kip103_burnt),
+        amount(&burns.kip160_burnt),
+    ]
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct IngestStats {
+    pub blocks: usize,
+    pub transactions: usize,
+    pub token_transfers: usize,
+    pub nft_transfers: usize,
+    pub event_logs: usize,
+    pub rewards: usize,
+    pub burns: usize,
+}
+
+// Pulls data from KaiaScan and writes it to a sink. Every write is an upsert, so a failed
+// run can simply be started again over the same range.
+pub struct Ingestor<'a, S> {
+    client: &'a KaiaScan,
+    sink: S,
+}
+
+impl<'a, S: Sink> Ingestor<'a, S> {
+    pub fn new(client: &'a KaiaScan, sink: S) -> Self {
+        Self { client, sink }
+    }
+
+    pub fn sink(&mut self) -> &mut S {
+        &mut self.sink
+    }
+
+    pub fn into_sink(self) -> S {
+        self.sink
+    }
+
+    // Stores every block in `range` together with all pages of its transactions, its rewards
+    // and burns. The block row is written last, so a block present in `blocks` is complete.
+    pub async fn ingest_blocks(&mut self, range: RangeInclusive<i64>) -> Result<IngestStats> {
+        self.sink.migrate().await?;
+
+        let client = self.client;
+        let mut stats = IngestStats::default();
+        let blocks = client.blocks_in_range(range);
+        pin_mut!(blocks);
+
+        while let Some(block) = blocks.next().await {
+            let block = block?;
+            let block_id = block.block_id;
+
+            let (transactions, rewards, burns) = futures::try_join!(
+                client.block_transactions(block_id),
+                client.get_block_rewards(block_id),
+                client.get_block_burns(block_id),
+            )?;
+
+            stats.transactions += transactions.len();
+            self.sink
+                .upsert(&TRANSACTIONS, transaction_rows(&transactions))
+                .await?;
+
+            let (totals, recipients, distributions) = block_reward_rows(block_id, &rewards);
+            self.sink.upsert(&BLOCK_REWARDS, vec![totals]).await?;
+            self.sink
+                .upsert(&BLOCK_REWARD_RECIPIENTS, recipients)
+                .await?;
+            self.sink
+                .upsert(&BLOCK_REWARD_DISTRIBUTIONS, distributions)
+                .await?;
+            stats.rewards += 1;
+
+            self.sink
+                .upsert(&BLOCK_BURNS, vec![block_burn_row(block_id, &burns)])
+                .await?;
+            stats.burns += 1;
+
+            self.sink
+                .upsert(&BLOCKS, block_rows(std::slice::from_ref(&block)))
+                .await?;
+            stats.blocks += 1;
+        }
+
+        Ok(stats)
+    }
+
+    // Stores the token transfers, NFT transfers and event logs of `address` within `range`.
+    // Each page is written in its own transaction.
+    pub async fn ingest_account(
+        &mut self,
+        address: Address,
+        range: RangeInclusive<u64>,
+    ) -> Result<IngestStats> {
+        self.sink.migrate().await?;
+
+        let client = self.client;
+        let mut stats = IngestStats::default();
+
+        let pages = client.account_token_transfer_pages(address.clone(), range.clone(), 1);
+        pin_mut!(pages);
+        let mut ordinals = TransferOrdinals::new();
+        while let Some(page) = pages.next().await {
+            let page = page?;
+            stats.token_transfers += page.results.len();
+            let rows = token_transfer_rows(&page.results, &mut ordinals);
+            self.sink.upsert(&TOKEN_TRANSFERS, rows).await?;
+        }
+
+        let pages = client.account_nft_transfer_pages(address.clone(), range.clone(), 1);
+        pin_mut!(pages);
+        let mut ordinals = TransferOrdinals::new();
+        while let Some(page) = pages.next().await {
+            let page = page?;
+            stats.nft_transfers += page.results.len();
+            let rows = nft_transfer_rows(&page.results, &mut ordinals);
+            self.sink.upsert(&NFT_TRANSFERS, rows).await?;
+        }
+
+        let mut page = 1;
+        loop {
+            let response = client
+                .get_account_event_logs(
+                    address.clone(),
+                    Some(page),
+                    Some(EVENT_LOGS_PAGE_SIZE),
+                    None,
+                    Some(*range.start()),
+                    Some(*range.end()),
+                )
+                .await?;
+
+            stats.event_logs += response.results.len();
+            self.sink
+                .upsert(&EVENT_LOGS, event_log_rows(&response.results)?)
+                .await?;
+
+            if response.pagings.last || page >= response.pagings.total_page {
+                break;
+            }
+            page += 1;
+        }
+
+        Ok(stats)
+    }
+}
+
+#[cfg(all(test, feature = "sqlite"))]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    async fn memory_sink() -> SqliteSink {
+        let mut sink = SqliteSink::connect("sqlite::memory:").await.unwrap();
+        sink.migrate().await.unwrap();
+        sink
+    }
+
+    async fn count(sink: &mut SqliteSink, table: &str) -> i64 {
+        sqlx::query_scalar(&format!("SELECT COUNT(*) FROM {}", table))
+            .fetch_one(sink.connection())
+            .await
+            .unwrap()
+    }
+
+    fn token_transfer(amount: u64) -> TokenTransfer {
+        serde_json::from_value(json!({
+            "contract": { "contract_address": "0xc", "contract_type": "kip7" },
+            "blockId": 7,
+            "transactionHash": "0xt",
+            "feePayer": "",
+            "transactionIndex": 0,
+            "datetime": "2024-01-01T00:00:00Z",
+            "from": "0xa",
+            "to": "0xb",
+            "amount": amount,
+        }))
+        .unwrap()
+    }
+
+    fn transaction(hash: &str, fee: &str) -> serde_json::Value {
+        json!({
+            "amount": "0",
+            "block_id": 5,
+            "datetime": "2024-01-01T00:00:00Z",
+            "effective_gas_price": "25000000000",
+            "fee_payer": "",
+            "from": "0xa",
+            "method_id": "",
+            "status": { "status": "Success" },
+            "to": "0xb",
+            "transaction_fee": fee,
+            "transaction_hash": hash,
+            "transaction_index": 0,
+            "transaction_type": "Legacy",
+        })
+    }
+
+    #[tokio::test]
+    async fn migrations_are_idempotent() {
+        let mut sink = memory_sink().await;
+        sink.migrate().await.unwrap();
+
+        let versions: Vec<i64> =
+            sqlx::query_scalar("SELECT version FROM schema_migrations ORDER BY version")
+                .fetch_all(sink.connection())
+                .await
+                .unwrap();
+        assert_eq!(versions, [1]);
+        for table in TABLES {
+            assert_eq!(count(&mut sink, table.name).await, 0);
+        }
+    }
+
+    #[tokio::test]
+    async fn upserts_replace_rows_with_the_same_key() {
+        let mut sink = memory_sink().await;
+        let first: Vec<Transaction> =
+            serde_json::from_value(json!([transaction("0x1", "0.1")])).unwrap();
+        let second: Vec<Transaction> = serde_json::from_value(json!([
+            transaction("0x1", "0.2"),
+            transaction("0x2", "0.3")
+        ]))
+        .unwrap();
+
+        sink.upsert(&TRANSACTIONS, transaction_rows(&first))
+            .await
+            .unwrap();
+        sink.upsert(&TRANSACTIONS, transaction_rows(&second))
+            .await
+            .unwrap();
+
+        assert_eq!(count(&mut sink, "transactions").await, 2);
+        let fee: String = sqlx::query_scalar(
+            "SELECT transaction_fee FROM transactions WHERE transaction_hash = '0x1'",
+        )
+        .fetch_one(sink.connection())
+        .await
+        .unwrap();
+        assert_eq!(fee, "0.2");
+    }
+
+    #[tokio::test]
+    async fn keeps_identical_transfers_of_one_transaction() {
+        let mut sink = memory_sink().await;
+        let transfers = [token_transfer(5), token_transfer(5), token_transfer(6)];
+
+        // Two pages of the same feed, then the same transfers again from another feed.
+        let mut ordinals = TransferOrdinals::new();
+        let rows = token_transfer_rows(&transfers[..1], &mut ordinals);
+        sink.upsert(&TOKEN_TRANSFERS, rows).await.unwrap();
+        let rows = token_transfer_rows(&transfers[1..], &mut ordinals);
+        sink.upsert(&TOKEN_TRANSFERS, rows).await.unwrap();
+        let rows = token_transfer_rows(&transfers, &mut TransferOrdinals::new());
+        sink.upsert(&TOKEN_TRANSFERS, rows).await.unwrap();
+
+        assert_eq!(count(&mut sink, "token_transfers").await, 3);
+    }
+
+    #[tokio::test]
+    async fn ingests_every_page_of_block_transactions() {
+        let mut server = mockito::Server::new_async().await;
+        let paging = |page: i64, last: bool| json!({ "current_page": page, "last": last, "total_count": 3, "total_page": 2 });
+        server
+            .mock(
+                "GET",
+                "/api/v1/blocks?blockNumberStart=5&blockNumberEnd=5&page=1&size=2000",
+            )
+            .with_body(
+                json!({
+                    "paging": paging(1, true),
+                    "results": [{
+                        "base_fee_per_gas": "25000000000",
+                        "block_id": 5,
+                        "block_proposer": "0xp",
+                        "block_size": 1000,
+                        "burnt_fees": "0",
+                        "datetime": "2024-01-01T00:00:00Z",
+                        "reward": "9.6",
+                        "total_transaction_count": 3,
+                    }],
+                })
+                .to_string(),
+            )
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/api/v1/blocks/5/transactions?page=1&size=2000")
+            .with_body(
+                json!({
+                    "paging": paging(1, false),
+                    "results": [transaction("0x1", "0.1"), transaction("0x2", "0.1")],
+                })
+                .to_string(),
+            )
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/api/v1/blocks/5/transactions?page=2&size=2000")
+            .with_body(
+                json!({ "paging": paging(2, true), "results": [transaction("0x3", "0.1")] })
+                    .to_string(),
+            )
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/api/v1/blocks/5/rewards")
+            .with_body(
+                json!({
+                    "burnt_fee": "0",
+                    "distributions": [],
+                    "minted": "9.6",
+                    "recipients": [],
+                    "total_fee": "0.3",
+                })
+                .to_string(),
+            )
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/api/v1/blocks/5/burns")
+            .with_body(
+                json!({
+                    "accumulate_burnt": "0",
+                    "accumulate_burnt_fees": "0",
+                    "accumulate_burnt_kaia": "0",
+                    "kip103_burnt": "0",
+                    "kip160_burnt": "0",
+                })
+                .to_string(),
+            )
+            .create_async()
+            .await;
+
+        let client = KaiaScan::new(false).unwrap().with_base_url(server.url());
+        let mut ingestor = Ingestor::new(&client, memory_sink().await);
+        let stats = ingestor.ingest_blocks(5..=5).await.unwrap();
+
+        assert_eq!(stats.blocks, 1);
+        assert_eq!(stats.transactions, 3);
+        assert_eq!(count(ingestor.sink(), "transactions").await, 3);
+    }
+}
@@ -0,0 +1,99 @@
+// Migration and upsert code shared by the sqlx backends, which differ only in their
+// connection type and SQL dialect.
+
+use super::schema::{self, ColumnKind, Dialect, SqlValue, Table};
+use anyhow::{Context, Result};
+use sqlx::query::Query;
+use sqlx::{ColumnIndex, Connection, Database, Decode, Encode, Executor, IntoArguments, Type};
+
+fn bind<'q, DB>(
+    query: Query<'q, DB, DB::Arguments<'q>>,
+    value: SqlValue,
+) -> Query<'q, DB, DB::Arguments<'q>>
+where
+    DB: Database,
+    i64: Encode<'q, DB> + Type<DB>,
+    Option<String>: Encode<'q, DB> + Type<DB>,
+    bool: Encode<'q, DB> + Type<DB>,
+{
+    match value {
+        SqlValue::Integer(value) => query.bind(value),
+        SqlValue::Text(value) => query.bind(value),
+        SqlValue::Bool(value) => query.bind(value),
+    }
+}
+
+pub(super) async fn migrate<DB>(connection: &mut DB::Connection, dialect: Dialect) -> Result<()>
+where
+    DB: Database,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+    for<'q> i64: Encode<'q, DB> + Type<DB> + Decode<'q, DB>,
+    usize: ColumnIndex<DB::Row>,
+    for<'q> Option<String>: Encode<'q, DB> + Type<DB>,
+    for<'q> bool: Encode<'q, DB> + Type<DB>,
+{
+    sqlx::query(schema::MIGRATIONS_TABLE)
+        .execute(&mut *connection)
+        .await?;
+    let current: i64 =
+        sqlx::query_scalar("SELECT COALESCE(MAX(version), 0) FROM schema_migrations")
+            .fetch_one(&mut *connection)
+            .await?;
+
+    let record = format!(
+        "INSERT INTO schema_migrations (version) VALUES ({})",
+        dialect.placeholder(1, ColumnKind::Integer)
+    );
+    for (version, statements) in schema::migrations(dialect) {
+        if version <= current {
+            continue;
+        }
+        let mut transaction = connection.begin().await?;
+        for statement in &statements {
+            sqlx::query(statement)
+                .execute(&mut *transaction)
+                .await
+                .with_context(|| format!("Migration {} failed", version))?;
+        }
+        sqlx::query(&record)
+            .bind(version)
+            .execute(&mut *transaction)
+            .await?;
+        transaction.commit().await?;
+    }
+
+    Ok(())
+}
+
+pub(super) async fn upsert<DB>(
+    connection: &mut DB::Connection,
+    dialect: Dialect,
+    table: &'static Table,
+    rows: Vec<Vec<SqlValue>>,
+) -> Result<()>
+where
+    DB: Database,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+    for<'q> i64: Encode<'q, DB> + Type<DB>,
+    for<'q> Option<String>: Encode<'q, DB> + Type<DB>,
+    for<'q> bool: Encode<'q, DB> + Type<DB>,
+{
+    if rows.is_empty() {
+        return Ok(());
+    }
+
+    let statement = schema::upsert(table, dialect);
+    let mut transaction = connection.begin().await?;
+    for row in rows {
+        let query = row.into_iter().fold(sqlx::query(&statement), bind);
+        query
+            .execute(&mut *transaction)
+            .await
+            .with_context(|| format!("Failed to write to {}", table.name))?;
+    }
+    transaction.commit().await?;
+
+    Ok(())
+}
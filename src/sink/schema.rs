@@ -0,0 +1,322 @@
+// Table definitions shared by the SQL backends. Migration 1 is generated from these, so a
+// schema change needs a new migration rather than an edit here.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnKind {
+    Integer,
+    Text,
+    // Decimal amount kept exact: TEXT in SQLite, NUMERIC in Postgres.
+    Amount,
+    // JSON document: TEXT in SQLite, JSONB in Postgres.
+    Json,
+    Bool,
+}
+
+#[derive(Debug)]
+pub struct Column {
+    pub name: &'static str,
+    pub kind: ColumnKind,
+}
+
+#[derive(Debug)]
+pub struct Table {
+    pub name: &'static str,
+    pub columns: &'static [Column],
+    // Columns of the primary key; an upsert replaces the row with the same key.
+    pub key: &'static [&'static str],
+}
+
+// Values are bound in the order of `Table::columns`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SqlValue {
+    Integer(i64),
+    Text(Option<String>),
+    Bool(bool),
+}
+
+const fn column(name: &'static str, kind: ColumnKind) -> Column {
+    Column { name, kind }
+}
+
+use ColumnKind::{Amount, Bool, Integer, Json, Text};
+
+pub const BLOCKS: Table = Table {
+    name: "blocks",
+    columns: &[
+        column("block_id", Integer),
+        column("datetime", Text),
+        column("block_proposer", Text),
+        column("block_size", Integer),
+        column("total_transaction_count", Integer),
+        column("base_fee_per_gas", Text),
+        column("burnt_fees", Amount),
+        column("reward", Amount),
+    ],
+    key: &["block_id"],
+};
+
+pub const TRANSACTIONS: Table = Table {
+    name: "transactions",
+    columns: &[
+        column("transaction_hash", Text),
+        column("block_id", Integer),
+        column("transaction_index", Integer),
+        column("datetime", Text),
+        column("from_address", Text),
+        column("to_address", Text),
+        column("fee_payer", Text),
+        column("amount", Amount),
+        column("transaction_fee", Amount),
+        column("effective_gas_price", Text),
+        column("method_id", Text),
+        column("transaction_type", Text),
+        column("status", Text),
+    ],
+    key: &["transaction_hash"],
+};
+
+// Transfers carry no log index. `transfer_ordinal` numbers the transfers of a transaction
+// that are otherwise identical (same contract, parties and amount), so a transaction that
+// moves the same amount twice keeps both rows.
+pub const TOKEN_TRANSFERS: Table = Table {
+    name: "token_transfers",
+    columns: &[
+        column("transaction_hash", Text),
+        column("contract_address", Text),
+        column("from_address", Text),
+        column("to_address", Text),
+        column("amount", Amount),
+        column("block_id", Integer),
+        column("transaction_index", Integer),
+        column("datetime", Text),
+        column("contract_type", Text),
+        column("fee_payer", Text),
+        column("transfer_ordinal", Integer),
+    ],
+    key: &[
+        "transaction_hash",
+        "contract_address",
+        "from_address",
+        "to_address",
+        "amount",
+        "transfer_ordinal",
+    ],
+};
+
+// An ERC-1155 batch can move the same token between the same parties more than once.
+pub const NFT_TRANSFERS: Table = Table {
+    name: "nft_transfers",
+    columns: &[
+        column("transaction_hash", Text),
+        column("contract_address", Text),
+        column("token_id", Text),
+        column("from_address", Text),
+        column("to_address", Text),
+        column("token_count", Amount),
+        column("block_id", Integer),
+        column("transaction_index", Integer),
+        column("datetime", Text),
+        column("contract_type", Text),
+        column("fee_payer", Text),
+        column("transfer_ordinal", Integer),
+    ],
+    key: &[
+        "transaction_hash",
+        "contract_address",
+        "token_id",
+        "from_address",
+        "to_address",
+        "token_count",
+        "transfer_ordinal",
+    ],
+};
+
+pub const EVENT_LOGS: Table = Table {
+    name: "event_logs",
+    columns: &[
+        column("transaction_hash", Text),
+        column("log_index", Integer),
+        column("block_number", Integer),
+        column("contract_address", Text),
+        column("log_type", Text),
+        column("topics", Json),
+        column("data", Text),
+        column("items", Json),
+        column("estimated_event_log", Bool),
+    ],
+    key: &["transaction_hash", "log_index"],
+};
+
+pub const BLOCK_REWARDS: Table = Table {
+    name: "block_rewards",
+    columns: &[
+        column("block_id", Integer),
+        column("minted", Amount),
+        column("total_fee", Amount),
+        column("burnt_fee", Amount),
+    ],
+    key: &["block_id"],
+};
+
+pub const BLOCK_REWARD_RECIPIENTS: Table = Table {
+    name: "block_reward_recipients",
+    columns: &[
+        column("block_id", Integer),
+        column("address", Text),
+        column("reward_type", Text),
+        column("name", Text),
+        column("amount", Amount),
+    ],
+    key: &["block_id", "address", "reward_type"],
+};
+
+pub const BLOCK_REWARD_DISTRIBUTIONS: Table = Table {
+    name: "block_reward_distributions",
+    columns: &[
+        column("block_id", Integer),
+        column("distribution_type", Text),
+        column("amount", Amount),
+    ],
+    key: &["block_id", "distribution_type"],
+};
+
+pub const BLOCK_BURNS: Table = Table {
+    name: "block_burns",
+    columns: &[
+        column("block_id", Integer),
+        column("accumulate_burnt", Amount),
+        column("accumulate_burnt_fees", Amount),
+        column("accumulate_burnt_kaia", Amount),
+        column("kip103_burnt", Amount),
+        column("kip160_burnt", Amount),
+    ],
+    key: &["block_id"],
+};
+
+pub const TABLES: [&Table; 9] = [
+    &BLOCKS,
+    &TRANSACTIONS,
+    &TOKEN_TRANSFERS,
+    &NFT_TRANSFERS,
+    &EVENT_LOGS,
+    &BLOCK_REWARDS,
+    &BLOCK_REWARD_RECIPIENTS,
+    &BLOCK_REWARD_DISTRIBUTIONS,
+    &BLOCK_BURNS,
+];
+
+// Tables that get an index on their block column for range queries.
+const BLOCK_INDEXES: [(&str, &str); 4] = [
+    ("transactions", "block_id"),
+    ("token_transfers", "block_id"),
+    ("nft_transfers", "block_id"),
+    ("event_logs", "block_number"),
+];
+
+// Records which migrations have been applied.
+pub(crate) const MIGRATIONS_TABLE: &str =
+    "CREATE TABLE IF NOT EXISTS schema_migrations (version BIGINT PRIMARY KEY)";
+
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Dialect {
+    Sqlite,
+    Postgres,
+}
+
+impl Dialect {
+    fn column_type(self, kind: ColumnKind) -> &'static str {
+        match (self, kind) {
+            (Dialect::Sqlite, Integer | Bool) => "INTEGER",
+            (Dialect::Sqlite, Text | Amount | Json) => "TEXT",
+            (Dialect::Postgres, Integer) => "BIGINT",
+            (Dialect::Postgres, Text) => "TEXT",
+            (Dialect::Postgres, Amount) => "NUMERIC",
+            (Dialect::Postgres, Json) => "JSONB",
+            (Dialect::Postgres, Bool) => "BOOLEAN",
+        }
+    }
+
+    pub(crate) fn placeholder(self, index: usize, kind: ColumnKind) -> String {
+        match (self, kind) {
+            (Dialect::Sqlite, _) => "?".to_string(),
+            (Dialect::Postgres, Amount) => format!("CAST(${} AS NUMERIC)", index),
+            (Dialect::Postgres, Json) => format!("CAST(${} AS JSONB)", index),
+            (Dialect::Postgres, _) => format!("${}", index),
+        }
+    }
+}
+
+fn create_table(table: &Table, dialect: Dialect) -> String {
+    let mut definitions: Vec<String> = table
+        .columns
+        .iter()
+        .map(|column| {
+            let not_null = if table.key.contains(&column.name) {
+                " NOT NULL"
+            } else {
+                ""
+            };
+            format!(
+                "{} {}{}",
+                column.name,
+                dialect.column_type(column.kind),
+                not_null
+            )
+        })
+        .collect();
+    definitions.push(format!("PRIMARY KEY ({})", table.key.join(", ")));
+
+    format!(
+        "CREATE TABLE IF NOT EXISTS {} ({})",
+        table.name,
+        definitions.join(", ")
+    )
+}
+
+// Statements of each migration, by version.
+pub(crate) fn migrations(dialect: Dialect) -> Vec<(i64, Vec<String>)> {
+    let mut initial: Vec<String> = TABLES
+        .iter()
+        .map(|table| create_table(table, dialect))
+        .collect();
+    initial.extend(BLOCK_INDEXES.iter().map(|(table, column)| {
+        format!(
+            "CREATE INDEX IF NOT EXISTS {0}_{1}_idx ON {0} ({1})",
+            table, column
+        )
+    }));
+
+    vec![(1, initial)]
+}
+
+pub(crate) fn upsert(table: &Table, dialect: Dialect) -> String {
+    let names: Vec<&str> = table.columns.iter().map(|column| column.name).collect();
+    let placeholders: Vec<String> = table
+        .columns
+        .iter()
+        .enumerate()
+        .map(|(index, column)| dialect.placeholder(index + 1, column.kind))
+        .collect();
+    let updates: Vec<String> = table
+        .columns
+        .iter()
+        .filter(|column| !table.key.contains(&column.name))
+        .map(|column| format!("{0} = excluded.{0}", column.name))
+        .collect();
+
+    let conflict = if updates.is_empty() {
+        "DO NOTHING".to_string()
+    } else {
+        format!("DO UPDATE SET {}", updates.join(", "))
+    };
+
+    format!(
+        "INSERT INTO {} ({}) VALUES ({}) ON CONFLICT ({}) {}",
+        table.name,
+        names.join(", "),
+        placeholders.join(", "),
+        table.key.join(", "),
+        conflict
+    )
+}
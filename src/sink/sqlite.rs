@@ -0,0 +1,39 @@
+use super::backend;
+use super::schema::{Dialect, SqlValue, Table};
+use super::Sink;
+use anyhow::{Context, Result};
+use sqlx::sqlite::{Sqlite, SqliteConnectOptions, SqliteConnection};
+use sqlx::ConnectOptions;
+use std::str::FromStr;
+
+pub struct SqliteSink {
+    connection: SqliteConnection,
+}
+
+impl SqliteSink {
+    // Opens `url` ("sqlite://path/to/file.db" or "sqlite::memory:"), creating the file if
+    // it does not exist yet.
+    pub async fn connect(url: &str) -> Result<Self> {
+        let connection = SqliteConnectOptions::from_str(url)
+            .with_context(|| format!("Invalid SQLite URL {}", url))?
+            .create_if_missing(true)
+            .connect()
+            .await
+            .with_context(|| format!("Failed to open SQLite database {}", url))?;
+        Ok(Self { connection })
+    }
+
+    pub fn connection(&mut self) -> &mut SqliteConnection {
+        &mut self.connection
+    }
+}
+
+impl Sink for SqliteSink {
+    async fn migrate(&mut self) -> Result<()> {
+        backend::migrate::<Sqlite>(&mut self.connection, Dialect::Sqlite).await
+    }
+
+    async fn upsert(&mut self, table: &'static Table, rows: Vec<Vec<SqlValue>>) -> Result<()> {
+        backend::upsert::<Sqlite>(&mut self.connection, Dialect::Sqlite, table, rows).await
+    }
+}
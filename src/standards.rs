@@ -0,0 +1,318 @@
+use crate::abi::{decode_hex, AbiEvent, AbiFunction};
+use crate::events::{decode_log, DecodedEvent};
+use crate::{EventLogResult, Transaction};
+use anyhow::{anyhow, Result};
+use std::sync::OnceLock;
+
+// Kaia token standards. KIP-7, KIP-17 and KIP-37 are ABI-compatible with ERC-20,
+// ERC-721 and ERC-1155 respectively.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TokenStandard {
+    Kip7,
+    Kip17,
+    Kip37,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StandardEvent {
+    Transfer,
+    Approval,
+    ApprovalForAll,
+    TransferSingle,
+    TransferBatch,
+    Uri,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StandardMethod {
+    Transfer,
+    TransferFrom,
+    Approve,
+    SafeTransfer,
+    SafeTransferFrom,
+    SafeBatchTransferFrom,
+    SetApprovalForAll,
+}
+
+use StandardEvent as E;
+use StandardMethod as M;
+use TokenStandard::{Kip17, Kip37, Kip7};
+
+struct StandardEventDef {
+    event: StandardEvent,
+    standards: &'static [TokenStandard],
+    declaration: &'static str,
+}
+
+struct StandardMethodDef {
+    method: StandardMethod,
+    standards: &'static [TokenStandard],
+    declaration: &'static str,
+}
+
+// KIP-7 and KIP-17 `Transfer`/`Approval` share a topic0 and differ only in whether the
+// last parameter is indexed, so they are told apart by topic count.
+const STANDARD_EVENTS: &[StandardEventDef] = &[
+    StandardEventDef {
+        event: E::Transfer,
+        standards: &[Kip7],
+        declaration: "event Transfer(address indexed from, address indexed to, uint256 value)",
+    },
+    StandardEventDef {
+        event: E::Approval,
+        standards: &[Kip7],
+        declaration: "event Approval(address indexed owner, address indexed spender, uint256 value)",
+    },
+    StandardEventDef {
+        event: E::Transfer,
+        standards: &[Kip17],
+        declaration:
+            "event Transfer(address indexed from, address indexed to, uint256 indexed tokenId)",
+    },
+    StandardEventDef {
+        event: E::Approval,
+        standards: &[Kip17],
+        declaration:
+            "event Approval(address indexed owner, address indexed approved, uint256 indexed tokenId)",
+    },
+    StandardEventDef {
+        event: E::ApprovalForAll,
+        standards: &[Kip17, Kip37],
+        declaration:
+            "event ApprovalForAll(address indexed owner, address indexed operator, bool approved)",
+    },
+    StandardEventDef {
+        event: E::TransferSingle,
+        standards: &[Kip37],
+        declaration: "event TransferSingle(address indexed operator, address indexed from, address indexed to, uint256 id, uint256 value)",
+    },
+    StandardEventDef {
+        event: E::TransferBatch,
+        standards: &[Kip37],
+        declaration: "event TransferBatch(address indexed operator, address indexed from, address indexed to, uint256[] ids, uint256[] values)",
+    },
+    StandardEventDef {
+        event: E::Uri,
+        standards: &[Kip37],
+        declaration: "event URI(string value, uint256 indexed id)",
+    },
+];
+
+const STANDARD_METHODS: &[StandardMethodDef] = &[
+    StandardMethodDef {
+        method: M::Transfer,
+        standards: &[Kip7],
+        declaration: "function transfer(address recipient, uint256 amount)",
+    },
+    StandardMethodDef {
+        method: M::TransferFrom,
+        standards: &[Kip7, Kip17],
+        declaration: "function transferFrom(address from, address to, uint256 value)",
+    },
+    StandardMethodDef {
+        method: M::Approve,
+        standards: &[Kip7, Kip17],
+        declaration: "function approve(address spender, uint256 value)",
+    },
+    StandardMethodDef {
+        method: M::SafeTransfer,
+        standards: &[Kip7],
+        declaration: "function safeTransfer(address recipient, uint256 amount)",
+    },
+    StandardMethodDef {
+        method: M::SafeTransfer,
+        standards: &[Kip7],
+        declaration: "function safeTransfer(address recipient, uint256 amount, bytes data)",
+    },
+    StandardMethodDef {
+        method: M::SafeTransferFrom,
+        standards: &[Kip7, Kip17],
+        declaration: "function safeTransferFrom(address from, address to, uint256 value)",
+    },
+    StandardMethodDef {
+        method: M::SafeTransferFrom,
+        standards: &[Kip7, Kip17],
+        declaration:
+            "function safeTransferFrom(address from, address to, uint256 value, bytes data)",
+    },
+    StandardMethodDef {
+        method: M::SafeTransferFrom,
+        standards: &[Kip37],
+        declaration: "function safeTransferFrom(address from, address to, uint256 id, uint256 value, bytes data)",
+    },
+    StandardMethodDef {
+        method: M::SafeBatchTransferFrom,
+        standards: &[Kip37],
+        declaration: "function safeBatchTransferFrom(address from, address to, uint256[] ids, uint256[] values, bytes data)",
+    },
+    StandardMethodDef {
+        method: M::SetApprovalForAll,
+        standards: &[Kip17, Kip37],
+        declaration: "function setApprovalForAll(address operator, bool approved)",
+    },
+];
+
+#[derive(Debug, Clone)]
+pub struct StandardEventMatch {
+    pub event: StandardEvent,
+    pub standards: &'static [TokenStandard],
+    pub abi: AbiEvent,
+}
+
+#[derive(Debug, Clone)]
+pub struct StandardMethodMatch {
+    pub method: StandardMethod,
+    pub standards: &'static [TokenStandard],
+    pub abi: AbiFunction,
+}
+
+pub fn standard_events() -> &'static [StandardEventMatch] {
+    static EVENTS: OnceLock<Vec<StandardEventMatch>> = OnceLock::new();
+    EVENTS.get_or_init(|| {
+        STANDARD_EVENTS
+            .iter()
+            .map(|def| StandardEventMatch {
+                event: def.event,
+                standards: def.standards,
+                abi: AbiEvent::parse(def.declaration).expect("standard events are valid"),
+            })
+            .collect()
+    })
+}
+
+pub fn standard_methods() -> &'static [StandardMethodMatch] {
+    static METHODS: OnceLock<Vec<StandardMethodMatch>> = OnceLock::new();
+    METHODS.get_or_init(|| {
+        STANDARD_METHODS
+            .iter()
+            .map(|def| StandardMethodMatch {
+                method: def.method,
+                standards: def.standards,
+                abi: AbiFunction::parse(def.declaration).expect("standard methods are valid"),
+            })
+            .collect()
+    })
+}
+
+// Identifies a log as one of the standard token events by topic0 and topic count.
+pub fn classify_event_log(log: &EventLogResult) -> Option<&'static StandardEventMatch> {
+    let topic0 = decode_hex(log.topics.first()?).ok()?;
+
+    standard_events().iter().find(|standard| {
+        standard.abi.topic0().as_slice() == topic0.as_slice()
+            && standard.abi.topic_count() == log.topics.len()
+    })
+}
+
+pub fn decode_standard_event(
+    log: &EventLogResult,
+) -> Result<(&'static StandardEventMatch, DecodedEvent)> {
+    let standard = classify_event_log(log)
+        .ok_or_else(|| anyhow!("Event log {} is not a standard token event", log.log_index))?;
+    let decoded = decode_log(&standard.abi, &log.topics, &log.data)?;
+    Ok((standard, decoded))
+}
+
+// Returns every standard method whose selector matches `method_id`. Overloads that share a
+// selector across standards are all returned.
+pub fn classify_method_id(method_id: &str) -> Vec<&'static StandardMethodMatch> {
+    let selector = match decode_hex(method_id) {
+        Ok(selector) if selector.len() >= 4 => selector,
+        _ => return Vec::new(),
+    };
+
+    standard_methods()
+        .iter()
+        .filter(|standard| standard.abi.selector() == selector[..4])
+        .collect()
+}
+
+impl Transaction {
+    pub fn standard_methods(&self) -> Vec<&'static StandardMethodMatch> {
+        classify_method_id(&self.method_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abi::{encode_hex, AbiValue};
+    use primitive_types::U256;
+
+    fn word(value: u64) -> String {
+        let mut word = [0u8; 32];
+        word[24..].copy_from_slice(&value.to_be_bytes());
+        encode_hex(&word)
+    }
+
+    fn log(topics: Vec<String>, data: String) -> EventLogResult {
+        EventLogResult {
+            log_index: 3,
+            contract_address: "0xc".to_string(),
+            log_type: String::new(),
+            topics,
+            data,
+            items: Vec::new(),
+            block_number: 1,
+            transaction_hash: "0xabc".to_string(),
+            estimated_event_log: false,
+        }
+    }
+
+    fn topic0(event: StandardEvent, standard: TokenStandard) -> String {
+        let standard = standard_events()
+            .iter()
+            .find(|def| def.event == event && def.standards.contains(&standard))
+            .unwrap();
+        encode_hex(&standard.abi.topic0())
+    }
+
+    #[test]
+    fn classifies_transfers_by_topic_count() {
+        let transfer = topic0(E::Transfer, Kip7);
+        assert_eq!(transfer, topic0(E::Transfer, Kip17));
+
+        let token = log(vec![transfer.clone(), word(1), word(2)], word(500));
+        assert_eq!(classify_event_log(&token).unwrap().standards, &[Kip7]);
+
+        let nft = log(vec![transfer, word(1), word(2), word(9)], "0x".to_string());
+        assert_eq!(classify_event_log(&nft).unwrap().standards, &[Kip17]);
+
+        let unknown = log(vec![word(1)], "0x".to_string());
+        assert!(classify_event_log(&unknown).is_none());
+    }
+
+    #[test]
+    fn decodes_kip37_transfer_single() {
+        let data = format!("{}{}", word(7), &word(25)[2..]);
+        let single = log(
+            vec![topic0(E::TransferSingle, Kip37), word(1), word(2), word(3)],
+            data,
+        );
+
+        let (standard, decoded) = decode_standard_event(&single).unwrap();
+        assert_eq!(standard.event, E::TransferSingle);
+        assert_eq!(
+            decoded.params[3],
+            ("id".to_string(), AbiValue::Uint(U256::from(7)))
+        );
+        assert_eq!(
+            decoded.params[4],
+            ("value".to_string(), AbiValue::Uint(U256::from(25)))
+        );
+    }
+
+    #[test]
+    fn classifies_method_ids() {
+        let transfer_from = classify_method_id("0x23b872dd");
+        assert_eq!(transfer_from.len(), 1);
+        assert_eq!(transfer_from[0].method, M::TransferFrom);
+        assert_eq!(transfer_from[0].standards, &[Kip7, Kip17]);
+
+        let batch = classify_method_id("0x2eb2c2d6");
+        assert_eq!(batch[0].method, M::SafeBatchTransferFrom);
+
+        assert!(classify_method_id("0x12345678").is_empty());
+        assert!(classify_method_id("0x12").is_empty());
+    }
+}
@@ -0,0 +1,154 @@
+use crate::{Address, KaiaScan, NftTransfer, Paging, TokenTransfer};
+use anyhow::Result;
+use async_stream::stream;
+use futures::stream::Stream;
+use std::future::Future;
+use std::ops::RangeInclusive;
+
+const TRANSFERS_PAGE_SIZE: u32 = 2000;
+
+#[derive(Debug)]
+pub struct TransferPage<T> {
+    pub page: u32,
+    pub total_page: u32,
+    pub last: bool,
+    pub results: Vec<T>,
+}
+
+// Walks a paged listing from `start_page`, calling `fetch` with each page number. The stream
+// ends after the last page or the first error, so an interrupted walk resumes from the page
+// after the last one received.
+fn transfer_pages<T, F, Fut>(
+    start_page: u32,
+    fetch: F,
+) -> impl Stream<Item = Result<TransferPage<T>>>
+where
+    F: Fn(u32) -> Fut,
+    Fut: Future<Output = Result<(Vec<T>, Paging)>>,
+{
+    stream! {
+        let mut page = start_page.max(1);
+
+        loop {
+            match fetch(page).await {
+                Ok((results, paging)) => {
+                    let last = paging.last || page as i64 >= paging.total_page;
+                    yield Ok(TransferPage {
+                        page,
+                        total_page: paging.total_page as u32,
+                        last,
+                        results,
+                    });
+                    if last {
+                        break;
+                    }
+                    page += 1;
+                }
+                Err(err) => {
+                    yield Err(err);
+                    break;
+                }
+            }
+        }
+    }
+}
+
+impl KaiaScan {
+    // Streams the token transfers of `address` within `block_range` one page at a time,
+    // starting at `start_page`.
+    pub fn account_token_transfer_pages(
+        &self,
+        address: Address,
+        block_range: RangeInclusive<u64>,
+        start_page: u32,
+    ) -> impl Stream<Item = Result<TransferPage<TokenTransfer>>> + '_ {
+        transfer_pages(start_page, move |page| {
+            let address = address.clone();
+            let (start, end) = (*block_range.start(), *block_range.end());
+            async move {
+                let response = self
+                    .get_account_token_transfers(
+                        address,
+                        Some(page),
+                        Some(TRANSFERS_PAGE_SIZE),
+                        None,
+                        Some(start),
+                        Some(end),
+                    )
+                    .await?;
+                Ok((response.results, response.paging))
+            }
+        })
+    }
+
+    // NFT counterpart of `account_token_transfer_pages`.
+    pub fn account_nft_transfer_pages(
+        &self,
+        address: Address,
+        block_range: RangeInclusive<u64>,
+        start_page: u32,
+    ) -> impl Stream<Item = Result<TransferPage<NftTransfer>>> + '_ {
+        transfer_pages(start_page, move |page| {
+            let address = address.clone();
+            let (start, end) = (*block_range.start(), *block_range.end());
+            async move {
+                let response = self
+                    .get_account_nft_transfers(
+                        address,
+                        Some(page),
+                        Some(TRANSFERS_PAGE_SIZE),
+                        None,
+                        Some(start),
+                        Some(end),
+                    )
+                    .await?;
+                Ok((response.results, response.paging))
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+
+    fn paging(page: u32, total_page: i64) -> Paging {
+        Paging {
+            current_page: page as i64,
+            last: false,
+            total_count: total_page * 2,
+            total_page,
+        }
+    }
+
+    #[tokio::test]
+    async fn walks_pages_from_the_start_page() {
+        let pages: Vec<TransferPage<u32>> = transfer_pages(2, |page| async move {
+            Ok((vec![page * 10, page * 10 + 1], paging(page, 3)))
+        })
+        .map(Result::unwrap)
+        .collect()
+        .await;
+
+        let numbers: Vec<(u32, bool)> = pages.iter().map(|page| (page.page, page.last)).collect();
+        assert_eq!(numbers, vec![(2, false), (3, true)]);
+        assert_eq!(pages[1].results, vec![30, 31]);
+    }
+
+    #[tokio::test]
+    async fn stops_after_the_first_error() {
+        let results: Vec<Result<TransferPage<u32>>> = transfer_pages(1, |page| async move {
+            if page == 2 {
+                anyhow::bail!("page {} failed", page);
+            }
+            Ok((vec![page], paging(page, 5)))
+        })
+        .collect()
+        .await;
+
+        assert_eq!(results.len(), 2);
+        assert!(results[0].is_ok());
+        assert!(results[1].is_err());
+    }
+}
@@ -0,0 +1,235 @@
+use crate::Transaction;
+use serde::Serialize;
+use std::fmt;
+
+// The transaction kinds of Kaia, without their fee delegation variant.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+pub enum TransactionKind {
+    Legacy,
+    ValueTransfer,
+    ValueTransferMemo,
+    AccountUpdate,
+    SmartContractDeploy,
+    SmartContractExecution,
+    Cancel,
+    ChainDataAnchoring,
+    EthereumAccessList,
+    EthereumDynamicFee,
+    Unknown(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+pub enum FeeDelegation {
+    None,
+    // The fee payer covers the whole fee.
+    Full,
+    // The fee payer covers a share of the fee set by the sender ("with ratio").
+    Partial,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+pub struct TransactionType {
+    pub kind: TransactionKind,
+    pub fee_delegation: FeeDelegation,
+}
+
+impl TransactionType {
+    // Accepts type names in the forms KaiaScan and node RPCs use ("TxTypeFeeDelegated
+    // ValueTransfer", "fee_delegated_value_transfer", "Fee Delegated Value Transfer With
+    // Ratio", ...) as well as numeric type codes ("0x09", "48").
+    pub fn parse(value: &str) -> Self {
+        let value = value.trim();
+        if let Some(parsed) = Self::from_code(value) {
+            return parsed;
+        }
+
+        let normalized: String = value
+            .chars()
+            .filter(char::is_ascii_alphanumeric)
+            .collect::<String>()
+            .to_ascii_lowercase();
+        let name = normalized
+            .strip_prefix("txtype")
+            .unwrap_or(&normalized)
+            .to_string();
+
+        let fee_delegation = if !name.contains("feedelegated") {
+            FeeDelegation::None
+        } else if name.contains("withratio") || name.contains("partial") {
+            FeeDelegation::Partial
+        } else {
+            FeeDelegation::Full
+        };
+        let base = name
+            .replace("feedelegated", "")
+            .replace("withratio", "")
+            .replace("partial", "");
+
+        let kind = match base.as_str() {
+            "legacy" | "legacytransaction" => TransactionKind::Legacy,
+            "valuetransfer" => TransactionKind::ValueTransfer,
+            "valuetransfermemo" => TransactionKind::ValueTransferMemo,
+            "accountupdate" => TransactionKind::AccountUpdate,
+            "smartcontractdeploy" | "contractdeploy" => TransactionKind::SmartContractDeploy,
+            "smartcontractexecution" | "contractexecution" => {
+                TransactionKind::SmartContractExecution
+            }
+            "cancel" => TransactionKind::Cancel,
+            "chaindataanchoring" => TransactionKind::ChainDataAnchoring,
+            "ethereumaccesslist" | "accesslist" => TransactionKind::EthereumAccessList,
+            "ethereumdynamicfee" | "dynamicfee" => TransactionKind::EthereumDynamicFee,
+            _ => TransactionKind::Unknown(value.to_string()),
+        };
+
+        Self {
+            kind,
+            fee_delegation,
+        }
+    }
+
+    // Kaia type codes: each basic type is a multiple of 8, plus 1 for its fee delegated
+    // variant and 2 for the one with a fee ratio.
+    fn from_code(value: &str) -> Option<Self> {
+        let code = match value.strip_prefix("0x") {
+            Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+            None => value.parse::<u32>().ok()?,
+        };
+
+        let kind = match code {
+            0x7801 => return Some(Self::new(TransactionKind::EthereumAccessList)),
+            0x7802 => return Some(Self::new(TransactionKind::EthereumDynamicFee)),
+            0x00 => return Some(Self::new(TransactionKind::Legacy)),
+            _ => match code & !0x7 {
+                0x08 => TransactionKind::ValueTransfer,
+                0x10 => TransactionKind::ValueTransferMemo,
+                0x20 => TransactionKind::AccountUpdate,
+                0x28 => TransactionKind::SmartContractDeploy,
+                0x30 => TransactionKind::SmartContractExecution,
+                0x38 => TransactionKind::Cancel,
+                0x48 => TransactionKind::ChainDataAnchoring,
+                _ => return None,
+            },
+        };
+        let fee_delegation = match code & 0x7 {
+            0 => FeeDelegation::None,
+            1 => FeeDelegation::Full,
+            2 => FeeDelegation::Partial,
+            _ => return None,
+        };
+
+        Some(Self {
+            kind,
+            fee_delegation,
+        })
+    }
+
+    fn new(kind: TransactionKind) -> Self {
+        Self {
+            kind,
+            fee_delegation: FeeDelegation::None,
+        }
+    }
+
+    pub fn is_fee_delegated(&self) -> bool {
+        self.fee_delegation != FeeDelegation::None
+    }
+}
+
+// Formats as the canonical name, e.g. "FeeDelegatedValueTransferWithRatio".
+impl fmt::Display for TransactionType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let TransactionKind::Unknown(name) = &self.kind {
+            return f.write_str(name);
+        }
+        if self.is_fee_delegated() {
+            f.write_str("FeeDelegated")?;
+        }
+        write!(f, "{:?}", self.kind)?;
+        if self.fee_delegation == FeeDelegation::Partial {
+            f.write_str("WithRatio")?;
+        }
+        Ok(())
+    }
+}
+
+impl Transaction {
+    pub fn tx_type(&self) -> TransactionType {
+        TransactionType::parse(&self.transaction_type)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tx_type(kind: TransactionKind, fee_delegation: FeeDelegation) -> TransactionType {
+        TransactionType {
+            kind,
+            fee_delegation,
+        }
+    }
+
+    #[test]
+    fn parses_type_names() {
+        use TransactionKind::*;
+
+        assert_eq!(
+            TransactionType::parse("TxTypeFeeDelegatedValueTransfer"),
+            tx_type(ValueTransfer, FeeDelegation::Full)
+        );
+        assert_eq!(
+            TransactionType::parse("fee_delegated_smart_contract_execution_with_ratio"),
+            tx_type(SmartContractExecution, FeeDelegation::Partial)
+        );
+        assert_eq!(
+            TransactionType::parse(" Legacy Transaction "),
+            tx_type(Legacy, FeeDelegation::None)
+        );
+        assert_eq!(
+            TransactionType::parse("EthereumDynamicFee"),
+            tx_type(EthereumDynamicFee, FeeDelegation::None)
+        );
+        assert_eq!(
+            TransactionType::parse("Teleport").kind,
+            Unknown("Teleport".to_string())
+        );
+    }
+
+    #[test]
+    fn parses_type_codes() {
+        use TransactionKind::*;
+
+        assert_eq!(
+            TransactionType::from_code("0x09"),
+            Some(tx_type(ValueTransfer, FeeDelegation::Full))
+        );
+        assert_eq!(
+            TransactionType::from_code("50"),
+            Some(tx_type(SmartContractExecution, FeeDelegation::Partial))
+        );
+        assert_eq!(
+            TransactionType::from_code("0x7802"),
+            Some(tx_type(EthereumDynamicFee, FeeDelegation::None))
+        );
+        assert_eq!(TransactionType::from_code("0x0b"), None);
+        assert_eq!(TransactionType::from_code("0x40"), None);
+        assert_eq!(TransactionType::from_code("ValueTransfer"), None);
+        assert_eq!(
+            TransactionType::parse("0x32"),
+            tx_type(SmartContractExecution, FeeDelegation::Partial)
+        );
+    }
+
+    #[test]
+    fn displays_canonical_names() {
+        assert_eq!(
+            TransactionType::parse("0x0a").to_string(),
+            "FeeDelegatedValueTransferWithRatio"
+        );
+        assert_eq!(
+            TransactionType::parse("0x30").to_string(),
+            "SmartContractExecution"
+        );
+        assert!(TransactionType::parse("0x31").is_fee_delegated());
+    }
+}
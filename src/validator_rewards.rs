@@ -0,0 +1,332 @@
+use crate::datetime::{unix_timestamp, utc_date};
+use crate::portfolio::{parse_base_units, wei_to_kaia, KAIA_DECIMALS};
+use crate::{Address, Asset, KaiaScan, PriceSource};
+use anyhow::{anyhow, Result};
+use futures::stream::TryStreamExt;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::ops::RangeInclusive;
+#[derive(Debug, Clone, Serialize)]
+pub struct DailyRewards {
+    pub date: String,
+    // Blocks of the day in which the address received a reward.
+    pub rewarded_blocks: usize,
+    pub amount: f64,
+    pub by_type: BTreeMap<String, f64>,
+    // `amount` at the KAIA price at the start of the day, when priced.
+    pub usd_value: Option<f64>,
+}
+
+// Rewards of one address over a block range. Amounts are in KAIA.
+#[derive(Debug, Clone, Serialize)]
+pub struct ValidatorRewards {
+    pub address: Address,
+    pub start_block: i64,
+    pub end_block: i64,
+    pub blocks: usize,
+    pub rewarded_blocks: usize,
+    pub amount: f64,
+    // Recipient amounts of the address by reward type.
+    pub by_type: BTreeMap<String, f64>,
+    // Network-wide distribution totals over the range by distribution type.
+    pub distributions: BTreeMap<String, f64>,
+    pub minted: f64,
+    pub total_fees: f64,
+    pub burnt_fees: f64,
+    pub daily: Vec<DailyRewards>,
+}
+
+// Parses a KAIA amount of a block's rewards into wei. An empty amount counts as zero.
+fn wei(amount: &str, block_id: i64) -> Result<u128> {
+    if amount.trim().is_empty() {
+        return Ok(0);
+    }
+    parse_base_units(amount, KAIA_DECIMALS).ok_or_else(|| {
+        anyhow!(
+            "Cannot parse reward amount {} of block {}",
+            amount,
+            block_id
+        )
+    })
+}
+
+fn to_kaia_by_type(amounts: BTreeMap<String, u128>) -> BTreeMap<String, f64> {
+    amounts
+        .into_iter()
+        .map(|(reward_type, wei)| (reward_type, wei_to_kaia(wei)))
+        .collect()
+}
+
+// Sums of one day, in wei.
+#[derive(Default)]
+struct DayTotals {
+    rewarded_blocks: usize,
+    amount: u128,
+    by_type: BTreeMap<String, u128>,
+}
+
+impl KaiaScan {
+    // Walks the blocks in `block_range` and sums the rewards `address` received by reward
+    // type and by UTC day, along with the distribution totals of every block. Rewards are
+    // fetched up to `batch_concurrency` blocks at a time. Amounts are summed in wei and
+    // converted to KAIA for the report; an amount that does not parse is an error.
+    pub async fn validator_rewards(
+        &self,
+        address: Address,
+        block_range: RangeInclusive<i64>,
+    ) -> Result<ValidatorRewards> {
+        let (start_block, end_block) = (*block_range.start(), *block_range.end());
+        let mut blocks = 0;
+        let mut rewarded_blocks = 0;
+        let (mut amount, mut minted, mut total_fees, mut burnt_fees) = (0u128, 0u128, 0u128, 0u128);
+        let mut by_type: BTreeMap<String, u128> = BTreeMap::new();
+        let mut distributions: BTreeMap<String, u128> = BTreeMap::new();
+        let mut daily: BTreeMap<String, DayTotals> = BTreeMap::new();
+
+        let rewards = self
+            .blocks_in_range(block_range)
+            .map_ok(|block| async move {
+                let rewards = self.get_block_rewards(block.block_id).await?;
+                Ok((block, rewards))
+            })
+            .try_buffered(self.batch_concurrency);
+        futures::pin_mut!(rewards);
+
+        while let Some((block, rewards)) = rewards.try_next().await? {
+            let block_id = block.block_id;
+            blocks += 1;
+            minted += wei(&rewards.minted, block_id)?;
+            total_fees += wei(&rewards.total_fee, block_id)?;
+            burnt_fees += wei(&rewards.burnt_fee, block_id)?;
+            for distribution in &rewards.distributions {
+                *distributions
+                    .entry(distribution.distribution_type.clone())
+                    .or_default() += wei(&distribution.amount, block_id)?;
+            }
+
+            let received = rewards
+                .recipients
+                .iter()
+                .filter(|recipient| recipient.address.eq_ignore_ascii_case(address.as_ref()))
+                .map(|recipient| {
+                    Ok((
+                        recipient.reward_type.as_str(),
+                        wei(&recipient.amount, block_id)?,
+                    ))
+                })
+                .collect::<Result<Vec<(&str, u128)>>>()?;
+            if received.is_empty() {
+                continue;
+            }
+
+            let date = unix_timestamp(&block.datetime)
+                .and_then(utc_date)
+                .unwrap_or_else(|| block.datetime.clone());
+            let day = daily.entry(date).or_default();
+            rewarded_blocks += 1;
+            day.rewarded_blocks += 1;
+
+            for (reward_type, received) in received {
+                amount += received;
+                *by_type.entry(reward_type.to_string()).or_default() += received;
+                day.amount += received;
+                *day.by_type.entry(reward_type.to_string()).or_default() += received;
+            }
+        }
+
+        Ok(ValidatorRewards {
+            address,
+            start_block,
+            end_block,
+            blocks,
+            rewarded_blocks,
+            amount: wei_to_kaia(amount),
+            by_type: to_kaia_by_type(by_type),
+            distributions: to_kaia_by_type(distributions),
+            minted: wei_to_kaia(minted),
+            total_fees: wei_to_kaia(total_fees),
+            burnt_fees: wei_to_kaia(burnt_fees),
+            daily: daily
+                .into_iter()
+                .map(|(date, day)| DailyRewards {
+                    date,
+                    rewarded_blocks: day.rewarded_blocks,
+                    amount: wei_to_kaia(day.amount),
+                    by_type: to_kaia_by_type(day.by_type),
+                    usd_value: None,
+                })
+                .collect(),
+        })
+    }
+
+    // Like `validator_rewards`, with each day valued at the KAIA price from `prices` at the
+    // start of the day.
+    pub async fn validator_rewards_with<P: PriceSource + Sync>(
+        &self,
+        address: Address,
+        block_range: RangeInclusive<i64>,
+        prices: &P,
+    ) -> Result<ValidatorRewards> {
+        let mut report = self.validator_rewards(address, block_range).await?;
+
+        for day in &mut report.daily {
+            let Some(start) = unix_timestamp(&format!("{}T00:00:00Z", day.date)) else {
+                continue;
+            };
+            let price = prices.price_at(&Asset::Kaia, start).await?;
+            day.usd_value = price.map(|price| price * day.amount);
+        }
+
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::StaticPrices;
+    use serde_json::json;
+
+    const VALIDATOR: &str = "0xabcd";
+
+    fn block(block_id: i64, datetime: &str) -> serde_json::Value {
+        json!({
+            "base_fee_per_gas": "25000000000",
+            "block_id": block_id,
+            "block_proposer": VALIDATOR,
+            "block_size": 1024,
+            "burnt_fees": "0",
+            "datetime": datetime,
+            "reward": "0",
+            "total_transaction_count": 0,
+        })
+    }
+
+    fn rewards(recipients: serde_json::Value) -> String {
+        json!({
+            "burnt_fee": "0.05",
+            "distributions": [
+                { "amount": "6.4", "type": "proposer" },
+                { "amount": "0.1", "type": "kgf" },
+            ],
+            "minted": "6.4",
+            "recipients": recipients,
+            "total_fee": "0.1",
+        })
+        .to_string()
+    }
+
+    async fn mock_blocks(server: &mut mockito::Server, recipients: [serde_json::Value; 3]) {
+        let blocks = json!({
+            "paging": { "current_page": 1, "last": true, "total_count": 3, "total_page": 1 },
+            "results": [
+                block(1, "2024-01-01T10:00:00Z"),
+                block(2, "2024-01-01T23:59:59Z"),
+                block(3, "2024-01-02T00:00:01Z"),
+            ],
+        });
+        server
+            .mock(
+                "GET",
+                "/api/v1/blocks?blockNumberStart=1&blockNumberEnd=3&page=1&size=2000",
+            )
+            .with_body(blocks.to_string())
+            .create_async()
+            .await;
+        for (block_id, recipients) in (1..).zip(recipients) {
+            server
+                .mock(
+                    "GET",
+                    format!("/api/v1/blocks/{}/rewards", block_id).as_str(),
+                )
+                .with_body(rewards(recipients))
+                .create_async()
+                .await;
+        }
+    }
+
+    #[tokio::test]
+    async fn sums_rewards_by_type_and_day() {
+        let mut server = mockito::Server::new_async().await;
+        let client = KaiaScan::new(false).unwrap().with_base_url(server.url());
+        mock_blocks(
+            &mut server,
+            [
+                json!([
+                    { "address": "0xABCD", "amount": "0.1", "name": "", "type": "proposer" },
+                    { "address": "0xother", "amount": "5", "name": "", "type": "proposer" },
+                ]),
+                json!([
+                    { "address": VALIDATOR, "amount": "0.2", "name": "", "type": "proposer" },
+                    { "address": VALIDATOR, "amount": "1.5", "name": "", "type": "stakers" },
+                ]),
+                json!([
+                    { "address": VALIDATOR, "amount": "0.3", "name": "", "type": "stakers" },
+                ]),
+            ],
+        )
+        .await;
+
+        let prices = StaticPrices::new()
+            .with_price_at(Asset::Kaia, 1_704_067_200, 0.2)
+            .with_price_at(Asset::Kaia, 1_704_153_600, 0.1);
+        let report = client
+            .validator_rewards_with(Address::new(VALIDATOR), 1..=3, &prices)
+            .await
+            .unwrap();
+
+        assert_eq!(report.blocks, 3);
+        assert_eq!(report.rewarded_blocks, 3);
+        // Summed in wei, so 0.1 + 0.2 is exactly 0.3.
+        assert_eq!(report.by_type["proposer"], 0.3);
+        assert_eq!(report.by_type["stakers"], 1.8);
+        assert_eq!(report.amount, 2.1);
+        assert_eq!(report.distributions["proposer"], 19.2);
+        assert_eq!(report.distributions["kgf"], 0.3);
+        assert_eq!(report.minted, 19.2);
+        assert_eq!(report.total_fees, 0.3);
+        assert_eq!(report.burnt_fees, 0.15);
+
+        let days: Vec<(&str, usize, f64, Option<f64>)> = report
+            .daily
+            .iter()
+            .map(|day| {
+                (
+                    day.date.as_str(),
+                    day.rewarded_blocks,
+                    day.amount,
+                    day.usd_value,
+                )
+            })
+            .collect();
+        assert_eq!(
+            days,
+            vec![
+                ("2024-01-01", 2, 1.8, Some(1.8 * 0.2)),
+                ("2024-01-02", 1, 0.3, Some(0.3 * 0.1)),
+            ]
+        );
+        assert_eq!(report.daily[0].by_type["proposer"], 0.3);
+    }
+
+    #[tokio::test]
+    async fn rejects_unparsable_amounts() {
+        let mut server = mockito::Server::new_async().await;
+        let client = KaiaScan::new(false).unwrap().with_base_url(server.url());
+        mock_blocks(
+            &mut server,
+            [
+                json!([]),
+                json!([{ "address": VALIDATOR, "amount": "1e18", "name": "", "type": "proposer" }]),
+                json!([]),
+            ],
+        )
+        .await;
+
+        let err = client
+            .validator_rewards(Address::new(VALIDATOR), 1..=3)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("block 2"));
+    }
+}
@@ -0,0 +1,311 @@
+use crate::{ApiError, KaiaScan, TransactionDetails, TransactionReceiptStatus};
+use std::fmt;
+use std::time::Duration;
+use tokio::time::Instant;
+
+#[derive(Debug, Clone)]
+pub struct WaitOptions {
+    pub timeout: Duration,
+    pub poll_interval: Duration,
+    // Number of blocks that must follow the transaction's block.
+    pub confirmations: u64,
+}
+
+impl Default for WaitOptions {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(60),
+            poll_interval: Duration::from_secs(1),
+            confirmations: 1,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum WaitError {
+    // The explorer never indexed a receipt for the transaction before the timeout.
+    NotFound {
+        transaction_hash: String,
+    },
+    // The receipt was found but the transaction failed on chain.
+    Reverted {
+        transaction_hash: String,
+        block_id: i64,
+    },
+    // The receipt was found but not enough blocks followed it before the timeout.
+    Timeout {
+        transaction_hash: String,
+        block_id: i64,
+        confirmations: u64,
+    },
+    Api(anyhow::Error),
+}
+
+impl fmt::Display for WaitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WaitError::NotFound { transaction_hash } => {
+                write!(f, "Transaction {} was not found", transaction_hash)
+            }
+            WaitError::Reverted {
+                transaction_hash,
+                block_id,
+            } => write!(
+                f,
+                "Transaction {} reverted in block {}",
+                transaction_hash, block_id
+            ),
+            WaitError::Timeout {
+                transaction_hash,
+                block_id,
+                confirmations,
+            } => write!(
+                f,
+                "Timed out waiting for transaction {} in block {} to reach {} confirmations",
+                transaction_hash, block_id, confirmations
+            ),
+            WaitError::Api(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for WaitError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            WaitError::Api(err) => Some(err.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<anyhow::Error> for WaitError {
+    fn from(err: anyhow::Error) -> Self {
+        WaitError::Api(err)
+    }
+}
+
+enum ReceiptState {
+    Pending,
+    Succeeded(i64),
+    Reverted(i64),
+}
+
+fn receipt_state(receipt: &TransactionReceiptStatus) -> ReceiptState {
+    match receipt.status.to_ascii_lowercase().as_str() {
+        "success" | "succeeded" | "0x1" | "1" => ReceiptState::Succeeded(receipt.block_id),
+        "fail" | "failed" | "failure" | "reverted" | "0x0" | "0" => {
+            ReceiptState::Reverted(receipt.block_id)
+        }
+        _ => ReceiptState::Pending,
+    }
+}
+
+impl KaiaScan {
+    // Polls the receipt until the transaction is indexed and `confirmations` blocks have
+    // been produced on top of it, then returns the final transaction details.
+    pub async fn wait_for_transaction(
+        &self,
+        transaction_hash: &str,
+        options: WaitOptions,
+    ) -> Result<TransactionDetails, WaitError> {
+        let deadline = Instant::now() + options.timeout;
+        let mut mined_in: Option<i64> = None;
+
+        loop {
+            if mined_in.is_none() {
+                // A missing receipt surfaces as a 404 until the explorer indexes it; any
+                // other failure is returned.
+                match self.get_transaction_receipt_status(transaction_hash).await {
+                    Ok(receipt) => match receipt_state(&receipt) {
+                        ReceiptState::Succeeded(block_id) => mined_in = Some(block_id),
+                        ReceiptState::Reverted(block_id) => {
+                            return Err(WaitError::Reverted {
+                                transaction_hash: transaction_hash.to_string(),
+                                block_id,
+                            });
+                        }
+                        ReceiptState::Pending => {}
+                    },
+                    Err(err) if ApiError::is_not_found(&err) => {}
+                    Err(err) => return Err(err.into()),
+                }
+            }
+
+            if let Some(block_id) = mined_in {
+                let latest = self.get_latest_block().await?;
+                let confirmations = latest.block_id.saturating_sub(block_id).max(0) as u64;
+
+                if confirmations >= options.confirmations {
+                    return Ok(self.get_transaction(transaction_hash).await?);
+                }
+            }
+
+            if Instant::now() + options.poll_interval > deadline {
+                return Err(match mined_in {
+                    Some(block_id) => WaitError::Timeout {
+                        transaction_hash: transaction_hash.to_string(),
+                        block_id,
+                        confirmations: options.confirmations,
+                    },
+                    None => WaitError::NotFound {
+                        transaction_hash: transaction_hash.to_string(),
+                    },
+                });
+            }
+
+            tokio::time::sleep(options.poll_interval).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const HASH: &str = "0xabc";
+
+    fn options() -> WaitOptions {
+        WaitOptions {
+            timeout: Duration::from_secs(2),
+            poll_interval: Duration::from_millis(10),
+            confirmations: 2,
+        }
+    }
+
+    fn receipt(status: &str) -> String {
+        json!({ "status": status, "block_id": 100, "tx_hash": HASH }).to_string()
+    }
+
+    fn latest_block(block_id: i64) -> String {
+        json!({
+            "block_id": block_id,
+            "datetime": "2024-01-01T00:00:00Z",
+            "hash": "0x0",
+            "total_transaction_count": 0,
+            "block_reward": { "minted": "0", "total_fee": "0", "burnt_fee": "0" },
+        })
+        .to_string()
+    }
+
+    async fn mock_receipt(server: &mut mockito::Server, status: usize, body: String) {
+        server
+            .mock(
+                "GET",
+                "/api/v1/transaction-receipts/status?transactionHash=0xabc",
+            )
+            .with_status(status)
+            .with_body(body)
+            .expect(1)
+            .create_async()
+            .await;
+    }
+
+    #[test]
+    fn classifies_receipt_status() {
+        let state = |status: &str| {
+            receipt_state(&TransactionReceiptStatus {
+                status: status.to_string(),
+                block_id: 7,
+                tx_hash: HASH.to_string(),
+            })
+        };
+
+        assert!(matches!(state("Success"), ReceiptState::Succeeded(7)));
+        assert!(matches!(state("0x1"), ReceiptState::Succeeded(7)));
+        assert!(matches!(state("Fail"), ReceiptState::Reverted(7)));
+        assert!(matches!(state("0x0"), ReceiptState::Reverted(7)));
+        assert!(matches!(state("pending"), ReceiptState::Pending));
+    }
+
+    #[tokio::test]
+    async fn waits_for_receipt_and_confirmations() {
+        let mut server = mockito::Server::new_async().await;
+        let client = KaiaScan::new(false).unwrap().with_base_url(server.url());
+
+        mock_receipt(&mut server, 404, "not found".to_string()).await;
+        mock_receipt(&mut server, 200, receipt("success")).await;
+        server
+            .mock("GET", "/api/v1/blocks/latest")
+            .with_body(latest_block(101))
+            .expect(1)
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/api/v1/blocks/latest")
+            .with_body(latest_block(102))
+            .create_async()
+            .await;
+        let details = server
+            .mock("GET", "/api/v1/transactions/0xabc")
+            .with_body(
+                json!({
+                    "tx_hash": HASH, "block_id": 100, "from": "0x1", "to": "0x2",
+                    "value": "0", "gas_used": "21000", "status": "success",
+                })
+                .to_string(),
+            )
+            .create_async()
+            .await;
+
+        let transaction = client.wait_for_transaction(HASH, options()).await.unwrap();
+        assert_eq!(transaction.block_id, 100);
+        details.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn reports_reverted_transactions() {
+        let mut server = mockito::Server::new_async().await;
+        let client = KaiaScan::new(false).unwrap().with_base_url(server.url());
+        mock_receipt(&mut server, 200, receipt("fail")).await;
+
+        let err = client
+            .wait_for_transaction(HASH, options())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, WaitError::Reverted { block_id: 100, .. }));
+    }
+
+    #[tokio::test]
+    async fn reports_not_found_after_timeout() {
+        let mut server = mockito::Server::new_async().await;
+        let client = KaiaScan::new(false).unwrap().with_base_url(server.url());
+        server
+            .mock(
+                "GET",
+                "/api/v1/transaction-receipts/status?transactionHash=0xabc",
+            )
+            .with_status(404)
+            .create_async()
+            .await;
+
+        let options = WaitOptions {
+            timeout: Duration::from_millis(50),
+            ..options()
+        };
+        let err = client
+            .wait_for_transaction(HASH, options)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, WaitError::NotFound { .. }));
+    }
+
+    #[tokio::test]
+    async fn returns_receipt_errors_other_than_not_found() {
+        let mut server = mockito::Server::new_async().await;
+        let client = KaiaScan::new(false).unwrap().with_base_url(server.url());
+        mock_receipt(&mut server, 401, "unauthorized".to_string()).await;
+
+        let err = client
+            .wait_for_transaction(HASH, options())
+            .await
+            .unwrap_err();
+        let WaitError::Api(err) = err else {
+            panic!("expected an API error, got {:?}", err);
+        };
+        assert!(matches!(
+            err.downcast_ref::<ApiError>(),
+            Some(ApiError::Status { status: 401, .. })
+        ));
+    }
+}
@@ -0,0 +1,238 @@
+use crate::{BlockDetails, KaiaScan, LatestBlock};
+use anyhow::Result;
+use async_stream::stream;
+use futures::stream::Stream;
+use std::time::Duration;
+
+const DEFAULT_BLOCK_POLL_INTERVAL: Duration = Duration::from_secs(1);
+const MIN_BLOCK_POLL_INTERVAL: Duration = Duration::from_millis(250);
+const MAX_BLOCK_POLL_INTERVAL: Duration = Duration::from_secs(10);
+const POLL_INTERVAL_REFRESH_POLLS: u32 = 300;
+
+impl From<BlockDetails> for LatestBlock {
+    fn from(block: BlockDetails) -> Self {
+        Self {
+            block_id: block.block_id,
+            datetime: block.datetime,
+            hash: block.hash,
+            total_transaction_count: block.total_transaction_count,
+            block_reward: block.block_reward,
+        }
+    }
+}
+
+impl KaiaScan {
+    // Derives the poll interval from the network's average block time over the last hour.
+    pub(crate) async fn block_poll_interval(&self) -> Duration {
+        let average = match self.get_kaia_info().await {
+            Ok(info) => parse_block_time(&info.summary.avg_block_time1h),
+            Err(_) => None,
+        };
+
+        average
+            .unwrap_or(DEFAULT_BLOCK_POLL_INTERVAL)
+            .clamp(MIN_BLOCK_POLL_INTERVAL, MAX_BLOCK_POLL_INTERVAL)
+    }
+
+    // Emits every new block exactly once, in order. Blocks skipped between two polls are
+    // backfilled through `get_block`. Dropping the stream stops the watcher.
+    pub fn watch_blocks(&self) -> impl Stream<Item = Result<LatestBlock>> + '_ {
+        stream! {
+            let mut last_block: Option<i64> = None;
+            let mut poll_interval = DEFAULT_BLOCK_POLL_INTERVAL;
+            let mut polls: u32 = 0;
+
+            loop {
+                if polls.is_multiple_of(POLL_INTERVAL_REFRESH_POLLS) {
+                    poll_interval = self.block_poll_interval().await;
+                }
+                polls = polls.wrapping_add(1);
+
+                match self.get_latest_block().await {
+                    Ok(latest) => match last_block {
+                        Some(last) if latest.block_id <= last => {}
+                        Some(last) => {
+                            let missing: Vec<i64> = (last + 1..latest.block_id).collect();
+                            let mut caught_up = true;
+
+                            for block in self.get_blocks_batch(&missing).await {
+                                match block {
+                                    Ok(block) => {
+                                        last_block = Some(block.block_id);
+                                        yield Ok(block.into());
+                                    }
+                                    Err(err) => {
+                                        // Retry from this block on the next poll.
+                                        caught_up = false;
+                                        yield Err(err);
+                                        break;
+                                    }
+                                }
+                            }
+
+                            if caught_up {
+                                last_block = Some(latest.block_id);
+                                yield Ok(latest);
+                            }
+                        }
+                        None => {
+                            last_block = Some(latest.block_id);
+                            yield Ok(latest);
+                        }
+                    },
+                    Err(err) => yield Err(err),
+                }
+
+                tokio::time::sleep(poll_interval).await;
+            }
+        }
+    }
+}
+
+// Accepts values such as "1.0", "1.02s" or "1,000ms".
+fn parse_block_time(value: &str) -> Option<Duration> {
+    let value = value.trim().replace(',', "");
+    let (number, scale) = match value.strip_suffix("ms") {
+        Some(number) => (number, 0.001),
+        None => (value.trim_end_matches('s'), 1.0),
+    };
+
+    let seconds = number.trim().parse::<f64>().ok()? * scale;
+    if seconds.is_finite() && seconds > 0.0 {
+        Some(Duration::from_secs_f64(seconds))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+    use serde_json::json;
+
+    fn block(block_id: i64) -> String {
+        json!({
+            "block_id": block_id,
+            "datetime": "2024-01-01T00:00:00Z",
+            "hash": format!("0x{:064x}", block_id),
+            "total_transaction_count": 0,
+            "block_reward": { "minted": "6.4", "total_fee": "0", "burnt_fee": "0" },
+        })
+        .to_string()
+    }
+
+    fn kaia_info(avg_block_time: &str) -> String {
+        json!({
+            "klay_price": {
+                "btc_price": "0", "market_cap": "0", "total_supply": "0",
+                "usd_price": "0.1", "usd_price_changes": "0", "volume": "0",
+            },
+            "summary": {
+                "avg_block_time1h": avg_block_time,
+                "avg_block_time24h": avg_block_time,
+                "avg_tx_per_block24h": 1.0,
+                "consensus_node": 40,
+            },
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn parses_block_times() {
+        assert_eq!(parse_block_time("1.0"), Some(Duration::from_secs(1)));
+        assert_eq!(
+            parse_block_time(" 1.5s "),
+            Some(Duration::from_millis(1500))
+        );
+        assert_eq!(parse_block_time("1,000ms"), Some(Duration::from_secs(1)));
+        assert_eq!(parse_block_time("0"), None);
+        assert_eq!(parse_block_time("fast"), None);
+    }
+
+    #[tokio::test]
+    async fn clamps_poll_interval_to_bounds() {
+        let mut server = mockito::Server::new_async().await;
+        let client = KaiaScan::new(false).unwrap().with_base_url(server.url());
+
+        let _slow = server
+            .mock("GET", "/api/v1/kaia")
+            .with_body(kaia_info("60"))
+            .expect(1)
+            .create_async()
+            .await;
+        let _fast = server
+            .mock("GET", "/api/v1/kaia")
+            .with_body(kaia_info("10ms"))
+            .expect(1)
+            .create_async()
+            .await;
+
+        assert_eq!(client.block_poll_interval().await, MAX_BLOCK_POLL_INTERVAL);
+        assert_eq!(client.block_poll_interval().await, MIN_BLOCK_POLL_INTERVAL);
+    }
+
+    #[tokio::test]
+    async fn falls_back_to_default_interval_on_error() {
+        let mut server = mockito::Server::new_async().await;
+        let client = KaiaScan::new(false).unwrap().with_base_url(server.url());
+        let _info = server
+            .mock("GET", "/api/v1/kaia")
+            .with_status(500)
+            .create_async()
+            .await;
+
+        assert_eq!(
+            client.block_poll_interval().await,
+            DEFAULT_BLOCK_POLL_INTERVAL
+        );
+    }
+
+    #[tokio::test]
+    async fn backfills_gaps_without_duplicates() {
+        let mut server = mockito::Server::new_async().await;
+        let client = KaiaScan::new(false).unwrap().with_base_url(server.url());
+
+        let _info = server
+            .mock("GET", "/api/v1/kaia")
+            .with_body(kaia_info("0.25"))
+            .create_async()
+            .await;
+        let _first = server
+            .mock("GET", "/api/v1/blocks/latest")
+            .with_body(block(10))
+            .expect(1)
+            .create_async()
+            .await;
+        let _repeat = server
+            .mock("GET", "/api/v1/blocks/latest")
+            .with_body(block(10))
+            .expect(1)
+            .create_async()
+            .await;
+        let _ahead = server
+            .mock("GET", "/api/v1/blocks/latest")
+            .with_body(block(13))
+            .create_async()
+            .await;
+        for block_id in [11, 12] {
+            server
+                .mock(
+                    "GET",
+                    format!("/api/v1/blocks?blockNumber={}", block_id).as_str(),
+                )
+                .with_body(block(block_id))
+                .create_async()
+                .await;
+        }
+
+        let blocks: Vec<i64> = client
+            .watch_blocks()
+            .take(4)
+            .map(|block| block.unwrap().block_id)
+            .collect()
+            .await;
+
+        assert_eq!(blocks, vec![10, 11, 12, 13]);
+    }
+}